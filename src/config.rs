@@ -1,5 +1,6 @@
 use config::{Config, Environment, File};
 use serde::Deserialize;
+use std::collections::HashMap;
 use std::path::PathBuf;
 
 /// 应用配置总结构
@@ -30,15 +31,322 @@ pub struct DatabaseSettings {
 /// 插件配置（WASM 插件文件位置及运行时限制）
 #[derive(Debug, Deserialize, Clone)]
 pub struct PluginSettings {
-    /// 插件根目录或 URI 前缀
+    /// 插件根目录或 URI 前缀（未配置 `roots` 时使用）
     pub location: String,
+    /// 多个插件根目录，可混用 `file://` 与 `s3://`
+    #[serde(default)]
+    pub roots: Vec<PluginRootSettings>,
     /// 单个插件实例允许使用的最大内存（单位：MB），默认 100MB
     pub max_memory_mb: u64,
     /// 单次 buffer 读取的最大大小（单位：MB），默认 16MB
     pub max_buffer_read_mb: u64,
-    /// 指定用于鉴权的插件 ID
-    /// 若设置，系统将直接调用该插件进行鉴权，不再遍历所有插件
+    /// 管理接口上传插件包的最大大小（单位：MB），默认 64MB
+    pub max_upload_mb: u64,
+    /// 转发给插件的请求体最大大小（单位：MB），默认 8MB
+    pub max_request_body_mb: u64,
+    /// 转发给插件的请求头白名单（不区分大小写），插件通过 `vtx-request:headers` 读取
+    #[serde(default = "default_request_headers")]
+    pub request_headers: Vec<String>,
+    /// 编译产物缓存目录，未设置时每次加载都重新编译；条目签名密钥保存在同级的 `<目录名>.key` 文件中
+    pub component_cache_dir: Option<PathBuf>,
+    /// 每个插件保留的迁移前数据快照数量，0 表示不创建快照，默认 5
+    pub snapshot_retention: usize,
+    /// 每个插件在内存中保留的 stdout / stderr 行数，0 表示只写入 tracing，默认 1000
+    pub log_buffer_lines: usize,
+    /// 插件包签名校验
+    #[serde(default)]
+    pub signature: SignatureSettings,
+    /// 插件目录热重载监听
+    #[serde(default)]
+    pub watch: WatchSettings,
+    /// 预实例化实例池
+    #[serde(default)]
+    pub pool: PoolSettings,
+    /// 插件调用执行期限
+    #[serde(default)]
+    pub deadlines: DeadlineSettings,
+    /// 燃料计量（CPU 消耗统计）
+    #[serde(default)]
+    pub fuel: FuelSettings,
+    /// 失败插件熔断
+    #[serde(default)]
+    pub breaker: BreakerSettings,
+    /// 插件临时目录
+    #[serde(default)]
+    pub scratch: ScratchSettings,
+    /// 单个鉴权插件 ID（未配置 `auth_providers` 时使用）
     pub auth_provider: Option<String>,
+    /// 按顺序调用的鉴权插件链
+    /// 为空时依次询问 `auth:provider` 能力已审批授予的插件（按插件 ID 排序）
+    #[serde(default)]
+    pub auth_providers: Vec<AuthProviderSettings>,
+    /// 鉴权结果缓存
+    #[serde(default)]
+    pub auth_cache: AuthCacheSettings,
+    /// 覆盖插件声明的路由访问策略：plugin_id -> `public` / `optional-auth` / `required-auth` / `required-group:<g>`
+    #[serde(default)]
+    pub route_policies: HashMap<String, String>,
+}
+
+impl PluginSettings {
+    /// 生效的插件根目录列表；未配置 `roots` 时退化为 `location` 单个可写根目录
+    pub fn plugin_roots(&self) -> Vec<PluginRootSettings> {
+        if !self.roots.is_empty() {
+            return self.roots.clone();
+        }
+        vec![PluginRootSettings {
+            location: self.location.clone(),
+            priority: 0,
+            read_only: false,
+        }]
+    }
+
+    /// 生效的鉴权插件链；未配置 `auth_providers` 时退化为 `auth_provider` 单个插件
+    pub fn auth_chain(&self) -> Vec<AuthProviderSettings> {
+        if !self.auth_providers.is_empty() {
+            return self.auth_providers.clone();
+        }
+        self.auth_provider
+            .iter()
+            .map(|plugin_id| AuthProviderSettings {
+                plugin_id: plugin_id.clone(),
+                continue_on: default_continue_on(),
+                cache_headers: Vec::new(),
+                legacy: true,
+            })
+            .collect()
+    }
+}
+
+/// 鉴权链中的一个插件
+#[derive(Debug, Deserialize, Clone)]
+pub struct AuthProviderSettings {
+    pub plugin_id: String,
+    /// 返回这些状态码时继续询问下一个插件，其余结果（成功、403、5xx 等）立即生效，默认 `[401]`
+    #[serde(default = "default_continue_on")]
+    pub continue_on: Vec<u16>,
+    /// 决定鉴权结果的请求头（如 `authorization`、`cookie`），作为缓存键
+    /// 为空时该插件的结果不缓存；链中任一插件未配置时整条链都不缓存
+    #[serde(default)]
+    pub cache_headers: Vec<String>,
+    /// 来自旧版 `auth_provider` 设置：未声明 `auth:provider` 能力时仅告警，视为已批准
+    #[serde(skip)]
+    pub legacy: bool,
+}
+
+/// 鉴权结果缓存配置
+#[derive(Debug, Deserialize, Clone)]
+pub struct AuthCacheSettings {
+    /// 缓存有效期（单位：秒），0 表示关闭缓存，默认 60 秒
+    pub ttl_secs: u64,
+    /// 最多缓存的条目数，默认 1024
+    pub max_entries: usize,
+}
+
+impl Default for AuthCacheSettings {
+    fn default() -> Self {
+        Self {
+            ttl_secs: 60,
+            max_entries: 1024,
+        }
+    }
+}
+
+fn default_continue_on() -> Vec<u16> {
+    vec![401]
+}
+
+fn default_request_headers() -> Vec<String> {
+    [
+        "accept",
+        "accept-language",
+        "content-type",
+        "content-length",
+        "range",
+        "if-none-match",
+        "if-modified-since",
+        "user-agent",
+    ]
+    .into_iter()
+    .map(String::from)
+    .collect()
+}
+
+/// 插件根目录配置
+#[derive(Debug, Deserialize, Clone)]
+pub struct PluginRootSettings {
+    /// 根目录路径或 URI 前缀
+    pub location: String,
+    /// 优先级，数值越大越优先；同一插件 ID 出现在多个根目录时以优先级高者为准
+    #[serde(default)]
+    pub priority: i32,
+    /// 只读根目录不接受管理接口上传安装
+    #[serde(default)]
+    pub read_only: bool,
+}
+
+/// 插件包签名校验配置
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct SignatureSettings {
+    /// 校验模式：`enforce` 拒绝未签名或签名无效的包，`warn` 仅告警，`off` 不校验
+    #[serde(default)]
+    pub mode: SignatureMode,
+    /// 受信任的发布者公钥
+    #[serde(default)]
+    pub trusted_keys: Vec<TrustedKeySettings>,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum SignatureMode {
+    Enforce,
+    Warn,
+    #[default]
+    Off,
+}
+
+/// 受信任的发布者公钥（base64 编码的 32 字节 ed25519 公钥）
+#[derive(Debug, Deserialize, Clone)]
+pub struct TrustedKeySettings {
+    pub publisher: String,
+    pub public_key: String,
+}
+
+/// 插件目录监听配置
+#[derive(Debug, Deserialize, Clone)]
+pub struct WatchSettings {
+    /// `auto`：`file://` 根目录使用文件系统通知，其余轮询；`poll`：始终轮询
+    pub backend: WatchBackend,
+    /// 轮询间隔（单位：毫秒）
+    pub poll_interval_ms: u64,
+    /// 文件事件去抖时间（单位：毫秒），文件大小与修改时间在该时间内保持不变才加载
+    pub debounce_ms: u64,
+}
+
+impl Default for WatchSettings {
+    fn default() -> Self {
+        Self {
+            backend: WatchBackend::Auto,
+            poll_interval_ms: 2000,
+            debounce_ms: 500,
+        }
+    }
+}
+
+/// 插件实例池配置
+///
+/// 仅对在包元数据中声明 `"stateless": true`，或列在 `plugins` 中的插件启用。
+#[derive(Debug, Deserialize, Clone)]
+pub struct PoolSettings {
+    /// 每个插件保留的空闲实例数量，0 表示关闭实例池
+    pub size: usize,
+    /// 由管理员指定启用实例池的插件 ID
+    #[serde(default)]
+    pub plugins: Vec<String>,
+}
+
+impl Default for PoolSettings {
+    fn default() -> Self {
+        Self {
+            size: 4,
+            plugins: Vec::new(),
+        }
+    }
+}
+
+/// 插件调用执行期限（基于 wasmtime epoch 中断），各期限为 0 表示不限制
+#[derive(Debug, Deserialize, Clone)]
+pub struct DeadlineSettings {
+    /// epoch 计时粒度（单位：毫秒）
+    pub epoch_tick_ms: u64,
+    /// HTTP 请求处理 `handle` 的期限（单位：毫秒）
+    pub handle_ms: u64,
+    /// 事件处理 `handle_event` 的期限（单位：毫秒）
+    pub event_ms: u64,
+    /// 鉴权 `authenticate` 的期限（单位：毫秒）
+    pub auth_ms: u64,
+    /// 加载检查与冒烟验证的期限（单位：毫秒），涵盖实例化与 manifest 等元数据导出
+    pub inspect_ms: u64,
+    /// 按插件 ID 覆盖的期限
+    #[serde(default)]
+    pub overrides: HashMap<String, DeadlineOverride>,
+}
+
+impl Default for DeadlineSettings {
+    fn default() -> Self {
+        Self {
+            epoch_tick_ms: 10,
+            handle_ms: 30_000,
+            event_ms: 30_000,
+            auth_ms: 5_000,
+            inspect_ms: 5_000,
+            overrides: HashMap::new(),
+        }
+    }
+}
+
+/// 燃料计量配置
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct FuelSettings {
+    /// 是否启用燃料计量，启用后每次调用都会记录消耗
+    pub enabled: bool,
+    /// 单次调用的燃料预算，耗尽时中断插件；0 表示不限制
+    pub call_budget: u64,
+}
+
+/// 插件熔断配置
+#[derive(Debug, Deserialize, Clone)]
+pub struct BreakerSettings {
+    /// 窗口内失败多少次后熔断，0 表示关闭熔断
+    pub failure_threshold: u32,
+    /// 失败计数窗口（单位：秒）
+    pub window_secs: u64,
+    /// 熔断持续时间（单位：秒），到期后放行一个探测调用
+    pub open_secs: u64,
+}
+
+impl Default for BreakerSettings {
+    fn default() -> Self {
+        Self {
+            failure_threshold: 5,
+            window_secs: 60,
+            open_secs: 30,
+        }
+    }
+}
+
+/// 插件临时目录配置
+#[derive(Debug, Deserialize, Clone)]
+pub struct ScratchSettings {
+    /// 数据根目录，未设置时不提供临时目录
+    pub root: Option<PathBuf>,
+    /// 单个插件的配额（单位：MB），0 表示不限制，默认 256MB
+    pub quota_mb: u64,
+}
+
+impl Default for ScratchSettings {
+    fn default() -> Self {
+        Self {
+            root: None,
+            quota_mb: 256,
+        }
+    }
+}
+
+/// 单个插件的期限覆盖，未设置的项沿用全局配置
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct DeadlineOverride {
+    pub handle_ms: Option<u64>,
+    pub event_ms: Option<u64>,
+    pub auth_ms: Option<u64>,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum WatchBackend {
+    #[default]
+    Auto,
+    Poll,
 }
 
 /// VtxFfmpeg 中间层专用配置
@@ -114,6 +422,29 @@ impl VtxSettings {
             .set_default("plugins.max_memory_mb", 100)?
             // 默认限制单次读取 16MB
             .set_default("plugins.max_buffer_read_mb", 16)?
+            // 默认限制上传包 64MB
+            .set_default("plugins.max_upload_mb", 64)?
+            .set_default("plugins.max_request_body_mb", 8)?
+            .set_default("plugins.component_cache_dir", "./cache/components")?
+            .set_default("plugins.snapshot_retention", 5)?
+            .set_default("plugins.log_buffer_lines", 1000)?
+            .set_default("plugins.watch.backend", "auto")?
+            .set_default("plugins.watch.poll_interval_ms", 2000)?
+            .set_default("plugins.watch.debounce_ms", 500)?
+            .set_default("plugins.pool.size", 4)?
+            .set_default("plugins.deadlines.epoch_tick_ms", 10)?
+            .set_default("plugins.deadlines.handle_ms", 30_000)?
+            .set_default("plugins.deadlines.event_ms", 30_000)?
+            .set_default("plugins.deadlines.auth_ms", 5_000)?
+            .set_default("plugins.deadlines.inspect_ms", 5_000)?
+            .set_default("plugins.fuel.enabled", false)?
+            .set_default("plugins.fuel.call_budget", 0)?
+            .set_default("plugins.breaker.failure_threshold", 5)?
+            .set_default("plugins.breaker.window_secs", 60)?
+            .set_default("plugins.breaker.open_secs", 30)?
+            .set_default("plugins.scratch.quota_mb", 256)?
+            .set_default("plugins.auth_cache.ttl_secs", 60)?
+            .set_default("plugins.auth_cache.max_entries", 1024)?
             .set_default::<&str, Option<String>>("plugins.auth_provider", None)?
             .set_default("vtx_ffmpeg.binary_root", "./bin/ffmpeg")?
             .set_default("vtx_ffmpeg.execution_timeout_secs", 600)?
@@ -6,7 +6,8 @@ mod vtx_vfs;
 mod web;
 
 use axum::{
-    routing::{any, delete, get, post},
+    extract::DefaultBodyLimit,
+    routing::{any, delete, get, post, put},
     Router,
 };
 use std::io;
@@ -59,6 +60,8 @@ async fn main() -> anyhow::Result<()> {
     let mut wasm_config = wasmtime::Config::new();
     wasm_config.wasm_component_model(true);
     wasm_config.async_support(true);
+    wasm_config.epoch_interruption(true);
+    wasm_config.consume_fuel(settings.plugins.fuel.enabled);
 
     let mut pooling_strategy = wasmtime::PoolingAllocationConfig::default();
 
@@ -74,6 +77,10 @@ async fn main() -> anyhow::Result<()> {
     ));
 
     let engine = wasmtime::Engine::new(&wasm_config)?;
+    runtime::deadline::spawn_epoch_ticker(
+        &engine,
+        std::time::Duration::from_millis(settings.plugins.deadlines.epoch_tick_ms.max(1)),
+    )?;
     let mut linker = Linker::<runtime::context::StreamContext>::new(&engine);
     add_to_linker_async(&mut linker)?;
     api::vtx_vfs::add_to_linker::<_, HasSelf<_>>(&mut linker, |ctx| ctx)?;
@@ -96,15 +103,28 @@ async fn main() -> anyhow::Result<()> {
 
     let plugin_manager = PluginManager::new(PluginManagerConfig {
         engine: engine.clone(),
-        plugin_root: settings.plugins.location.clone(),
+        plugin_roots: settings.plugins.plugin_roots(),
         registry: registry.clone(),
         vfs: vfs.clone(),
         linker,
-        auth_provider: settings.plugins.auth_provider.clone(),
+        auth_providers: settings.plugins.auth_chain(),
+        auth_cache: settings.plugins.auth_cache.clone(),
+        route_policies: settings.plugins.route_policies.clone(),
         vtx_ffmpeg: vtx_ffmpeg_manager.clone(),
         max_buffer_read_bytes: settings.plugins.max_buffer_read_mb * 1024 * 1024,
         max_memory_bytes: max_memory_bytes as usize,
         event_bus: event_bus.clone(),
+        component_cache_dir: settings.plugins.component_cache_dir.clone(),
+        signature: settings.plugins.signature.clone(),
+        snapshot_retention: settings.plugins.snapshot_retention,
+        watch: settings.plugins.watch.clone(),
+        pool: settings.plugins.pool.clone(),
+        deadlines: settings.plugins.deadlines.clone(),
+        fuel: settings.plugins.fuel.clone(),
+        breaker: settings.plugins.breaker.clone(),
+        scratch: settings.plugins.scratch.clone(),
+        secrets_key: std::env::var(runtime::manager::plugin_config::SECRETS_KEY_ENV).ok(),
+        log_buffer_lines: settings.plugins.log_buffer_lines,
     })
     .await?;
 
@@ -133,7 +153,79 @@ async fn main() -> anyhow::Result<()> {
                 .route("/scan-roots", delete(admin::remove_scan_root_handler))
                 .route("/videos", get(admin::list_handler))
                 .route("/plugins", get(admin::list_plugins_handler))
+                .route(
+                    "/plugins",
+                    post(admin::install_plugin_handler).layer(DefaultBodyLimit::max(
+                        settings.plugins.max_upload_mb as usize * 1024 * 1024,
+                    )),
+                )
+                .route(
+                    "/plugins/validate",
+                    post(admin::validate_plugin_handler).layer(DefaultBodyLimit::max(
+                        settings.plugins.max_upload_mb as usize * 1024 * 1024,
+                    )),
+                )
+                .route(
+                    "/plugins/cache",
+                    get(admin::component_cache_stats_handler)
+                        .delete(admin::purge_component_cache_handler),
+                )
+                .route("/plugins/pool", get(admin::instance_pool_stats_handler))
+                .route("/plugins/fuel", get(admin::fuel_usage_handler))
+                .route("/plugins/{id}/enable", post(admin::enable_plugin_handler))
+                .route("/plugins/{id}/disable", post(admin::disable_plugin_handler))
+                .route(
+                    "/plugins/{id}/breaker/reset",
+                    post(admin::reset_plugin_breaker_handler),
+                )
+                .route(
+                    "/plugins/{id}/config",
+                    get(admin::list_plugin_config_handler),
+                )
+                .route(
+                    "/plugins/{id}/config/{key}",
+                    put(admin::set_plugin_config_handler)
+                        .delete(admin::delete_plugin_config_handler),
+                )
+                .route("/plugins/{id}/logs", get(admin::plugin_logs_handler))
+                .route(
+                    "/plugins/{id}/permissions",
+                    get(admin::list_plugin_permissions_handler),
+                )
+                .route(
+                    "/plugins/{id}/permissions/approve",
+                    post(admin::approve_plugin_permissions_handler),
+                )
+                .route(
+                    "/plugins/{id}/permissions/deny",
+                    post(admin::deny_plugin_permissions_handler),
+                )
+                .route(
+                    "/plugins/{id}/reloads",
+                    get(admin::list_plugin_reloads_handler),
+                )
+                .route(
+                    "/plugins/{id}/versions",
+                    get(admin::list_plugin_versions_handler),
+                )
+                .route(
+                    "/plugins/{id}/rollback",
+                    post(admin::rollback_plugin_handler),
+                )
+                .route(
+                    "/plugins/{id}/snapshots",
+                    get(admin::list_plugin_snapshots_handler),
+                )
+                .route(
+                    "/plugins/{id}/snapshots/{snapshot_id}/restore",
+                    post(admin::restore_plugin_snapshot_handler),
+                )
                 .route("/plugin", delete(admin::uninstall_handler))
+                .route("/auth/cache", delete(admin::flush_auth_cache_handler))
+                .route(
+                    "/auth/requests/{request_id}",
+                    get(admin::auth_trace_handler),
+                )
                 .route("/jobs", post(admin::submit_job_handler))
                 .route("/jobs", get(admin::list_jobs_handler))
                 .route("/jobs/{id}", get(admin::get_job_handler))
@@ -144,7 +236,12 @@ async fn main() -> anyhow::Result<()> {
                     auth_middleware,
                 )),
         )
-        .route("/{*path}", any(plugin::gateway_handler))
+        .route(
+            "/{*path}",
+            any(plugin::gateway_handler).layer(DefaultBodyLimit::max(
+                settings.plugins.max_request_body_mb as usize * 1024 * 1024,
+            )),
+        )
         .with_state(state)
         .layer(CorsLayer::permissive())
         .layer(CatchPanicLayer::new())
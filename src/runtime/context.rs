@@ -1,8 +1,14 @@
-use std::sync::Arc;
-use wasmtime_wasi::{ResourceTable, WasiCtx, WasiCtxBuilder, WasiCtxView, WasiView};
+use axum::http::{HeaderName, HeaderValue};
+use std::sync::{Arc, Mutex};
+use wasmtime_wasi::{
+    DirPerms, FilePerms, ResourceTable, WasiCtx, WasiCtxBuilder, WasiCtxView, WasiView,
+};
 
 use crate::runtime::bus::EventBus;
 use crate::runtime::ffmpeg::VtxFfmpegManager;
+use crate::runtime::manager::scratch::{ScratchMount, SCRATCH_GUEST_PATH};
+use crate::runtime::plugin_logs::{LogStream, PluginLogs, PluginOutput};
+use crate::runtime::request_data::RequestData;
 use crate::runtime::vtx_host_impl::api::vtx_types::HttpAllowRule;
 use crate::storage::VtxVideoRegistry;
 use crate::vtx_vfs::VtxVfsManager;
@@ -28,6 +34,12 @@ pub struct StreamContext {
     pub event_bus: Arc<EventBus>,
     pub permissions: std::collections::HashSet<String>,
     pub http_allowlist: Vec<HttpAllowRule>,
+    /// 当前调用的请求 ID，与 stdout / stderr 捕获共享
+    pub request_id: Arc<Mutex<Option<String>>>,
+    /// 当前 HTTP 调用转发的请求头与请求体
+    pub request: Option<Arc<RequestData>>,
+    /// 插件通过 `vtx-response:headers` 设置的响应头
+    pub response_headers: Vec<(HeaderName, HeaderValue)>,
 
     pub vtx_ffmpeg: Arc<VtxFfmpegManager>,
     pub vfs: Arc<VtxVfsManager>,
@@ -45,6 +57,24 @@ pub struct StreamContextConfig {
     pub permissions: std::collections::HashSet<String>,
     pub http_allowlist: Vec<HttpAllowRule>,
     pub vfs: Arc<VtxVfsManager>,
+    /// 插件配置项，以环境变量注入 WASI
+    pub env: Vec<(String, String)>,
+    /// 以 `/scratch` 挂载的插件临时目录
+    pub scratch_dir: Option<ScratchMount>,
+    /// 插件 stdout / stderr 的捕获缓冲，`None` 时丢弃输出
+    pub logs: Option<Arc<PluginLogs>>,
+    pub request_id: Option<String>,
+    pub request: Option<Arc<RequestData>>,
+}
+
+/// 单次调用的上下文，复用实例时按请求替换
+pub struct InvocationScope {
+    pub policy: SecurityPolicy,
+    pub current_user: Option<CurrentUser>,
+    pub permissions: std::collections::HashSet<String>,
+    pub http_allowlist: Vec<HttpAllowRule>,
+    pub request_id: Option<String>,
+    pub request: Option<Arc<RequestData>>,
 }
 
 #[derive(Debug, Clone)]
@@ -68,12 +98,53 @@ impl StreamContext {
             permissions,
             http_allowlist,
             vfs,
+            env,
+            scratch_dir,
+            logs,
+            request_id,
+            request,
         } = config;
-        let wasi = WasiCtxBuilder::new()
-            .inherit_stdio()
+        let request_id = Arc::new(Mutex::new(request_id));
+        let mut builder = WasiCtxBuilder::new();
+        // 插件输出不直接写入宿主 stdio，避免干扰结构化日志与 stdout 上的 IPC 通道
+        if let (Some(logs), Some(id)) = (&logs, &plugin_id) {
+            builder
+                .stdout(PluginOutput::new(
+                    logs.clone(),
+                    id,
+                    LogStream::Stdout,
+                    request_id.clone(),
+                ))
+                .stderr(PluginOutput::new(
+                    logs.clone(),
+                    id,
+                    LogStream::Stderr,
+                    request_id.clone(),
+                ));
+        }
+        builder
+            .envs(&env)
             .env("VTX_ENV", "production")
-            .args(&["plugin_worker"])
-            .build();
+            .args(&["plugin_worker"]);
+        if let Some(mount) = &scratch_dir {
+            // 超出配额时去掉文件写权限，插件仍可删除文件释放空间
+            let file_perms = if mount.writable {
+                FilePerms::all()
+            } else {
+                FilePerms::READ
+            };
+            if let Err(e) =
+                builder.preopened_dir(&mount.path, SCRATCH_GUEST_PATH, DirPerms::all(), file_perms)
+            {
+                tracing::warn!(
+                    "[Scratch] Failed to preopen {} for {:?}: {}",
+                    mount.path.display(),
+                    plugin_id,
+                    e
+                );
+            }
+        }
+        let wasi = builder.build();
 
         Self {
             table: ResourceTable::new(),
@@ -87,6 +158,9 @@ impl StreamContext {
             event_bus,
             permissions,
             http_allowlist,
+            request_id,
+            request,
+            response_headers: Vec::new(),
             vtx_ffmpeg,
             vfs,
         }
@@ -97,6 +171,28 @@ impl StreamContext {
     pub fn has_permission(&self, perm: &str) -> bool {
         self.permissions.iter().any(|p| p == perm)
     }
+
+    /// 切换到新的调用上下文
+    pub fn enter_scope(&mut self, scope: InvocationScope) {
+        self.policy = scope.policy;
+        self.current_user = scope.current_user;
+        self.permissions = scope.permissions;
+        self.http_allowlist = scope.http_allowlist;
+        *self.request_id.lock().unwrap() = scope.request_id;
+        self.request = scope.request;
+        self.response_headers.clear();
+    }
+
+    /// 调用结束后清除用户身份与权限，避免泄漏到下一次调用
+    pub fn leave_scope(&mut self) {
+        self.policy = SecurityPolicy::Restricted;
+        self.current_user = None;
+        self.permissions.clear();
+        self.http_allowlist.clear();
+        *self.request_id.lock().unwrap() = None;
+        self.request = None;
+        self.response_headers.clear();
+    }
 }
 
 impl WasiView for StreamContext {
@@ -0,0 +1,84 @@
+use std::time::Duration;
+use tracing::info;
+use wasmtime::{Engine, Store, Trap};
+
+use crate::config::DeadlineSettings;
+
+/// 未设置期限时使用的 epoch 增量（保留余量避免与当前 epoch 相加溢出）
+pub const NO_DEADLINE: u64 = u64::MAX / 2;
+
+/// 插件调用类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PluginCall {
+    Handle,
+    Event,
+    Authenticate,
+    /// 加载检查与冒烟验证（`get_manifest` / `get_capabilities` / `get_resources` / `get_migrations`）
+    Inspect,
+}
+
+impl PluginCall {
+    /// 对应的插件导出函数名
+    pub fn export_name(self) -> &'static str {
+        match self {
+            Self::Handle => "handle",
+            Self::Event => "handle_event",
+            Self::Authenticate => "authenticate",
+            Self::Inspect => "get_manifest",
+        }
+    }
+}
+
+/// 插件在该调用类型上的期限（毫秒），优先使用按插件覆盖的配置；0 表示不限制
+///
+/// 加载检查时插件 ID 尚未确定，不支持按插件覆盖。
+pub fn limit_ms(settings: &DeadlineSettings, plugin_id: &str, call: PluginCall) -> u64 {
+    let overrides = settings.overrides.get(plugin_id);
+    match call {
+        PluginCall::Handle => overrides
+            .and_then(|o| o.handle_ms)
+            .unwrap_or(settings.handle_ms),
+        PluginCall::Event => overrides
+            .and_then(|o| o.event_ms)
+            .unwrap_or(settings.event_ms),
+        PluginCall::Authenticate => overrides
+            .and_then(|o| o.auth_ms)
+            .unwrap_or(settings.auth_ms),
+        PluginCall::Inspect => settings.inspect_ms,
+    }
+}
+
+/// 为下一次调用设置 epoch 期限
+///
+/// 期限向上取整到计时粒度，并额外多留一个刻度，保证实际可用时间不少于配置值。
+pub fn arm<T>(store: &mut Store<T>, settings: &DeadlineSettings, limit_ms: u64) {
+    let ticks = if limit_ms == 0 {
+        NO_DEADLINE
+    } else {
+        limit_ms.div_ceil(settings.epoch_tick_ms.max(1)) + 1
+    };
+    store.set_epoch_deadline(ticks);
+}
+
+/// 错误是否由 epoch 期限触发
+pub fn is_deadline_exceeded(err: &anyhow::Error) -> bool {
+    err.downcast_ref::<Trap>() == Some(&Trap::Interrupt)
+}
+
+/// 启动 epoch 计时线程，引擎释放后自动退出
+///
+/// 使用独立线程而非 tokio 任务：陷入死循环的插件可能占满所有 tokio 工作线程。
+pub fn spawn_epoch_ticker(engine: &Engine, tick: Duration) -> std::io::Result<()> {
+    let engine = engine.weak();
+    std::thread::Builder::new()
+        .name("vtx-epoch-ticker".to_string())
+        .spawn(move || loop {
+            std::thread::sleep(tick);
+            match engine.upgrade() {
+                Some(engine) => engine.increment_epoch(),
+                None => break,
+            }
+        })?;
+    info!("[Deadline] Epoch ticker started ({}ms)", tick.as_millis());
+    Ok(())
+}
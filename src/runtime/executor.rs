@@ -1,17 +1,83 @@
 use crate::common::buffer::RealBuffer;
+use crate::config::DeadlineSettings;
 use crate::runtime::bus::EventBus;
-use crate::runtime::context::{CurrentUser, SecurityPolicy, StreamContext, StreamContextConfig};
+use crate::runtime::context::{
+    CurrentUser, InvocationScope, SecurityPolicy, StreamContext, StreamContextConfig,
+};
+use crate::runtime::deadline::{self, PluginCall};
 use crate::runtime::ffmpeg::VtxFfmpegManager;
+use crate::runtime::fuel::FuelMeter;
+use crate::runtime::manager::plugin_config::PluginConfigStore;
+use crate::runtime::manager::scratch::ScratchSpace;
 use crate::runtime::manager::PluginRuntime;
-use crate::runtime::vtx_host_impl::{api, VtxPlugin};
+use crate::runtime::plugin_logs::PluginLogs;
+use crate::runtime::request_data::RequestData;
+use crate::runtime::vtx_host_impl::api;
 use crate::storage::VtxVideoRegistry;
 use crate::vtx_vfs::VtxVfsManager;
 use crate::web::state::AppState;
+use axum::http::{HeaderName, HeaderValue};
 use std::sync::Arc;
 use wasmtime::Store;
 
 pub struct VtxPluginExecutor;
 
+/// 插件调用失败原因
+#[derive(Debug)]
+pub enum ExecutionError {
+    /// 超过执行期限被中断
+    DeadlineExceeded {
+        plugin_id: String,
+        limit_ms: u64,
+    },
+    /// 事件处理函数返回错误（插件自身运行正常）
+    Rejected(String),
+    Failed(String),
+}
+
+impl std::fmt::Display for ExecutionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::DeadlineExceeded {
+                plugin_id,
+                limit_ms,
+            } => write!(
+                f,
+                "Plugin '{}' exceeded its execution deadline of {}ms",
+                plugin_id, limit_ms
+            ),
+            Self::Rejected(message) | Self::Failed(message) => f.write_str(message),
+        }
+    }
+}
+
+impl From<String> for ExecutionError {
+    fn from(message: String) -> Self {
+        Self::Failed(message)
+    }
+}
+
+impl ExecutionError {
+    fn from_call(err: anyhow::Error, plugin_id: &str, limit_ms: u64, context: &str) -> Self {
+        if deadline::is_deadline_exceeded(&err) {
+            Self::DeadlineExceeded {
+                plugin_id: plugin_id.to_string(),
+                limit_ms,
+            }
+        } else {
+            Self::Failed(format!("{}: {}", context, err))
+        }
+    }
+}
+
+/// 插件处理 HTTP 请求的结果
+pub struct PluginResponse {
+    pub body: Option<RealBuffer>,
+    pub status: u16,
+    /// 插件通过 `vtx-response:headers` 设置的响应头
+    pub headers: Vec<(HeaderName, HeaderValue)>,
+}
+
 pub struct EventDispatchContext {
     pub engine: wasmtime::Engine,
     pub registry: VtxVideoRegistry,
@@ -20,6 +86,11 @@ pub struct EventDispatchContext {
     pub event_bus: Arc<EventBus>,
     pub max_memory_bytes: usize,
     pub max_buffer_read_bytes: u64,
+    pub deadlines: Arc<DeadlineSettings>,
+    pub fuel: Arc<FuelMeter>,
+    pub config: Arc<PluginConfigStore>,
+    pub scratch: Arc<ScratchSpace>,
+    pub logs: Arc<PluginLogs>,
 }
 
 impl VtxPluginExecutor {
@@ -31,17 +102,28 @@ impl VtxPluginExecutor {
             .build()
     }
 
+    fn plugin_scope(
+        runtime: &PluginRuntime,
+        current_user: Option<CurrentUser>,
+        request_id: Option<String>,
+        request: Option<Arc<RequestData>>,
+    ) -> InvocationScope {
+        let grants = runtime.grants();
+        InvocationScope {
+            policy: SecurityPolicy::Plugin,
+            current_user,
+            permissions: grants.permissions,
+            http_allowlist: grants.http,
+            request_id,
+            request,
+        }
+    }
+
     fn build_context(
         state: &AppState,
         runtime: &PluginRuntime,
-        current_user: Option<CurrentUser>,
+        scope: InvocationScope,
     ) -> StreamContext {
-        let permissions = runtime
-            .policy
-            .permissions
-            .iter()
-            .cloned()
-            .collect::<std::collections::HashSet<_>>();
         let memory_limit_bytes = state.config.plugins.max_memory_mb as usize * 1024 * 1024;
         let max_buffer_read_bytes = state.config.plugins.max_buffer_read_mb * 1024 * 1024;
         let limits = Self::build_limits(memory_limit_bytes);
@@ -51,33 +133,22 @@ impl VtxPluginExecutor {
             vtx_ffmpeg: state.vtx_ffmpeg.clone(),
             vfs: state.vfs.clone(),
             limiter: limits,
-            policy: SecurityPolicy::Plugin,
+            policy: scope.policy,
             plugin_id: Some(runtime.id.clone()),
             max_buffer_read_bytes,
-            current_user,
+            current_user: scope.current_user,
             event_bus: state.event_bus.clone(),
-            permissions,
-            http_allowlist: runtime.policy.http.clone(),
+            permissions: scope.permissions,
+            http_allowlist: scope.http_allowlist,
+            env: state.plugin_manager.plugin_env(&runtime.id),
+            scratch_dir: state.plugin_manager.scratch_dir(runtime),
+            logs: Some(state.plugin_manager.plugin_logs()),
+            request_id: scope.request_id,
+            request: scope.request,
         })
     }
 
-    fn build_store(engine: &wasmtime::Engine, ctx: StreamContext) -> Store<StreamContext> {
-        let mut store = Store::new(engine, ctx);
-        store.limiter(|s| &mut s.limiter);
-        store
-    }
-
-    async fn instantiate_plugin(
-        store: &mut Store<StreamContext>,
-        instance_pre: &wasmtime::component::InstancePre<StreamContext>,
-    ) -> Result<VtxPlugin, String> {
-        let instance = instance_pre
-            .instantiate_async(&mut *store)
-            .await
-            .map_err(|e| format!("Fast instantiation failed: {}", e))?;
-        VtxPlugin::new(store, &instance).map_err(|e| format!("Plugin binding failed: {}", e))
-    }
-
+    /// 构造传给插件的请求（请求头与请求体通过 [`RequestData`] 的保留 URI 读取）
     fn build_request(
         method: String,
         sub_path: String,
@@ -93,19 +164,25 @@ impl VtxPluginExecutor {
     fn resolve_response(
         store: &mut Store<StreamContext>,
         response: api::vtx_types::HttpResponse,
-    ) -> Result<(Option<RealBuffer>, u16), String> {
-        if let Some(resource_handle) = response.body {
-            let buffer = store
-                .data_mut()
-                .table
-                .delete(resource_handle)
-                .map_err(|_| "Invalid buffer handle".to_string())?;
-            Ok((Some(buffer), response.status))
-        } else {
-            Ok((None, response.status))
-        }
+    ) -> Result<PluginResponse, String> {
+        let body = match response.body {
+            Some(resource_handle) => Some(
+                store
+                    .data_mut()
+                    .table
+                    .delete(resource_handle)
+                    .map_err(|_| "Invalid buffer handle".to_string())?,
+            ),
+            None => None,
+        };
+        Ok(PluginResponse {
+            body,
+            status: response.status,
+            headers: std::mem::take(&mut store.data_mut().response_headers),
+        })
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub async fn execute_runtime(
         state: &Arc<AppState>,
         runtime: Arc<PluginRuntime>,
@@ -113,18 +190,50 @@ impl VtxPluginExecutor {
         method: String,
         query: String,
         current_user: Option<CurrentUser>,
-    ) -> Result<(Option<RealBuffer>, u16), String> {
-        let ctx = Self::build_context(state.as_ref(), runtime.as_ref(), current_user);
-        let mut store = Self::build_store(&state.engine, ctx);
-        let plugin = Self::instantiate_plugin(&mut store, &runtime.instance_pre).await?;
-        let req = Self::build_request(method, sub_path, query);
-
-        let response = plugin
-            .call_handle(&mut store, &req)
+        request_id: String,
+        request: RequestData,
+    ) -> Result<PluginResponse, ExecutionError> {
+        let scope = Self::plugin_scope(
+            &runtime,
+            current_user,
+            Some(request_id),
+            Some(Arc::new(request)),
+        );
+        state.plugin_manager.scratch_space().admit(&runtime).await;
+        let mut instance = runtime
+            .instances
+            .checkout(&state.engine, &runtime.instance_pre, scope, |scope| {
+                Self::build_context(state.as_ref(), runtime.as_ref(), scope)
+            })
             .await
-            .map_err(|e| format!("Execution failed: {}", e))?;
+            .map_err(|e| e.to_string())?;
+        let req = Self::build_request(method, sub_path, query);
+        let deadlines = &state.config.plugins.deadlines;
+        let limit_ms = deadline::limit_ms(deadlines, &runtime.id, PluginCall::Handle);
+        deadline::arm(&mut instance.store, deadlines, limit_ms);
+        let fuel = state.plugin_manager.fuel_meter();
+        fuel.arm(&mut instance.store);
 
-        Self::resolve_response(&mut store, response)
+        let outcome = instance.plugin.call_handle(&mut instance.store, &req).await;
+        fuel.record(
+            &instance.store,
+            &runtime.id,
+            PluginCall::Handle,
+            outcome.as_ref().err(),
+        );
+        let result = match outcome {
+            Ok(response) => {
+                Self::resolve_response(&mut instance.store, response).map_err(ExecutionError::from)
+            }
+            Err(e) => Err(ExecutionError::from_call(
+                e,
+                &runtime.id,
+                limit_ms,
+                "Execution failed",
+            )),
+        };
+        runtime.instances.checkin(instance, result.is_ok());
+        result
     }
 
     #[allow(dead_code)]
@@ -132,7 +241,7 @@ impl VtxPluginExecutor {
         state: &Arc<AppState>,
         runtime: Arc<PluginRuntime>,
         event: crate::common::events::VtxEvent,
-    ) -> Result<(), String> {
+    ) -> Result<(), ExecutionError> {
         Self::dispatch_event_with(
             EventDispatchContext {
                 engine: state.engine.clone(),
@@ -142,6 +251,11 @@ impl VtxPluginExecutor {
                 event_bus: state.event_bus.clone(),
                 max_memory_bytes: state.config.plugins.max_memory_mb as usize * 1024 * 1024,
                 max_buffer_read_bytes: state.config.plugins.max_buffer_read_mb * 1024 * 1024,
+                deadlines: Arc::new(state.config.plugins.deadlines.clone()),
+                fuel: state.plugin_manager.fuel_meter(),
+                config: state.plugin_manager.config_store(),
+                scratch: state.plugin_manager.scratch_space(),
+                logs: state.plugin_manager.plugin_logs(),
             },
             runtime,
             event,
@@ -153,7 +267,7 @@ impl VtxPluginExecutor {
         context: EventDispatchContext,
         runtime: Arc<PluginRuntime>,
         event: crate::common::events::VtxEvent,
-    ) -> Result<(), String> {
+    ) -> Result<(), ExecutionError> {
         let EventDispatchContext {
             engine,
             registry,
@@ -162,51 +276,24 @@ impl VtxPluginExecutor {
             event_bus,
             max_memory_bytes,
             max_buffer_read_bytes,
+            deadlines,
+            fuel,
+            config,
+            scratch,
+            logs,
         } = context;
-        let plugin_id = runtime.id.clone();
-        let permissions = runtime
-            .policy
-            .permissions
-            .iter()
-            .cloned()
-            .collect::<std::collections::HashSet<_>>();
-        let limits = wasmtime::StoreLimitsBuilder::new()
-            .memory_size(max_memory_bytes)
-            .instances(5)
-            .tables(1000)
-            .build();
-
         let current_user = event.context.user_id.as_ref().map(|user_id| CurrentUser {
             user_id: user_id.clone(),
             username: event.context.username.clone().unwrap_or_default(),
             groups: Vec::new(),
         });
-
-        let ctx = StreamContext::new_secure(StreamContextConfig {
-            registry,
-            vtx_ffmpeg,
-            vfs,
-            limiter: limits,
-            policy: SecurityPolicy::Plugin,
-            plugin_id: Some(plugin_id),
-            max_buffer_read_bytes,
-            current_user,
-            event_bus,
-            permissions,
-            http_allowlist: runtime.policy.http.clone(),
-        });
-
-        let mut store = Store::new(&engine, ctx);
-        store.limiter(|s| &mut s.limiter);
-
-        let instance = runtime
-            .instance_pre
-            .instantiate_async(&mut store)
-            .await
-            .map_err(|e| format!("Event instantiation failed: {}", e))?;
-
-        let plugin = VtxPlugin::new(&mut store, &instance)
-            .map_err(|e| format!("Plugin binding failed: {}", e))?;
+        // 事件没有关联请求时，以事件 ID 标记插件输出
+        let request_id = event
+            .context
+            .request_id
+            .clone()
+            .unwrap_or_else(|| event.id.clone());
+        let scope = Self::plugin_scope(&runtime, current_user, Some(request_id), None);
 
         let event_payload = serde_json::to_string(&event.payload)
             .map_err(|_| "Event payload serialize failed".to_string())?;
@@ -224,10 +311,50 @@ impl VtxPluginExecutor {
             occurred_at: event.occurred_at,
         };
 
-        plugin
-            .call_handle_event(&mut store, &wit_event)
+        scratch.admit(&runtime).await;
+        let mut instance = runtime
+            .instances
+            .checkout(&engine, &runtime.instance_pre, scope, |scope| {
+                StreamContext::new_secure(StreamContextConfig {
+                    registry,
+                    vtx_ffmpeg,
+                    vfs,
+                    limiter: Self::build_limits(max_memory_bytes),
+                    policy: scope.policy,
+                    plugin_id: Some(runtime.id.clone()),
+                    max_buffer_read_bytes,
+                    current_user: scope.current_user,
+                    event_bus,
+                    permissions: scope.permissions,
+                    http_allowlist: scope.http_allowlist,
+                    env: config.env(&runtime.id).as_ref().clone(),
+                    scratch_dir: scratch.dir_for(&runtime.id, &runtime.grants().permissions),
+                    logs: Some(logs),
+                    request_id: scope.request_id,
+                    request: scope.request,
+                })
+            })
             .await
-            .map_err(|e| format!("Event dispatch failed: {}", e))?
-            .map_err(|e| format!("Event handler rejected: {}", e))
+            .map_err(|e| format!("Event instantiation failed: {}", e))?;
+        let limit_ms = deadline::limit_ms(&deadlines, &runtime.id, PluginCall::Event);
+        deadline::arm(&mut instance.store, &deadlines, limit_ms);
+        fuel.arm(&mut instance.store);
+
+        let result = instance
+            .plugin
+            .call_handle_event(&mut instance.store, &wit_event)
+            .await;
+        fuel.record(
+            &instance.store,
+            &runtime.id,
+            PluginCall::Event,
+            result.as_ref().err(),
+        );
+        runtime.instances.checkin(instance, result.is_ok());
+        result
+            .map_err(|e| {
+                ExecutionError::from_call(e, &runtime.id, limit_ms, "Event dispatch failed")
+            })?
+            .map_err(|e| ExecutionError::Rejected(format!("Event handler rejected: {}", e)))
     }
 }
@@ -0,0 +1,118 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use wasmtime::{Store, Trap};
+
+use crate::config::FuelSettings;
+use crate::runtime::deadline::PluginCall;
+
+/// 未设置单次预算时装填的燃料
+const UNLIMITED_FUEL: u64 = u64::MAX;
+
+/// 插件燃料计量与累计
+///
+/// 每次调用前装填燃料，调用结束后按插件与导出函数累计消耗。
+pub struct FuelMeter {
+    enabled: bool,
+    call_budget: u64,
+    usage: Mutex<HashMap<(String, PluginCall), FuelUsage>>,
+}
+
+/// 单个插件导出函数的燃料消耗
+#[derive(Debug, Clone, Serialize)]
+pub struct FuelUsage {
+    pub plugin_id: String,
+    pub entrypoint: &'static str,
+    pub calls: u64,
+    pub fuel_consumed: u64,
+    /// 单次调用的最大消耗
+    pub max_call: u64,
+    /// 因超出单次预算被中断的次数
+    pub budget_exhausted: u64,
+}
+
+impl FuelMeter {
+    pub fn new(settings: &FuelSettings) -> Self {
+        Self {
+            enabled: settings.enabled,
+            call_budget: settings.call_budget,
+            usage: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    fn budget(&self) -> u64 {
+        if self.call_budget == 0 {
+            UNLIMITED_FUEL
+        } else {
+            self.call_budget
+        }
+    }
+
+    /// 调用前装填单次预算
+    pub fn arm<T>(&self, store: &mut Store<T>) {
+        if self.enabled {
+            let _ = store.set_fuel(self.budget());
+        }
+    }
+
+    /// 调用结束后累计消耗
+    pub fn record<T>(
+        &self,
+        store: &Store<T>,
+        plugin_id: &str,
+        call: PluginCall,
+        error: Option<&anyhow::Error>,
+    ) {
+        if !self.enabled {
+            return;
+        }
+        let remaining = store.get_fuel().unwrap_or(0);
+        let consumed = self.budget().saturating_sub(remaining);
+        let exhausted = error.is_some_and(is_fuel_exhausted);
+
+        let mut usage = self.usage.lock().unwrap();
+        let entry = usage
+            .entry((plugin_id.to_string(), call))
+            .or_insert_with(|| FuelUsage {
+                plugin_id: plugin_id.to_string(),
+                entrypoint: call.export_name(),
+                calls: 0,
+                fuel_consumed: 0,
+                max_call: 0,
+                budget_exhausted: 0,
+            });
+        entry.calls += 1;
+        entry.fuel_consumed = entry.fuel_consumed.saturating_add(consumed);
+        entry.max_call = entry.max_call.max(consumed);
+        if exhausted {
+            entry.budget_exhausted += 1;
+        }
+    }
+
+    /// 按插件 ID 与导出函数排序的累计消耗
+    pub fn usage(&self) -> Vec<FuelUsage> {
+        let mut usage: Vec<FuelUsage> = self.usage.lock().unwrap().values().cloned().collect();
+        usage.sort_by(|a, b| {
+            a.plugin_id
+                .cmp(&b.plugin_id)
+                .then(a.entrypoint.cmp(b.entrypoint))
+        });
+        usage
+    }
+}
+
+/// 为新建的 Store 装填燃料，使实例化与非计量调用不受影响
+///
+/// 引擎未启用燃料计量时 `set_fuel` 返回错误，忽略即可。
+pub fn prime<T>(store: &mut Store<T>) {
+    let _ = store.set_fuel(UNLIMITED_FUEL);
+}
+
+/// 错误是否由燃料耗尽触发
+pub fn is_fuel_exhausted(err: &anyhow::Error) -> bool {
+    err.downcast_ref::<Trap>() == Some(&Trap::OutOfFuel)
+}
@@ -0,0 +1,133 @@
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::config::{AuthCacheSettings, AuthProviderSettings};
+use crate::runtime::vtx_host_impl::api::vtx_auth_types::UserContext;
+
+/// 缓存键：鉴权链声明的请求头的 SHA-256
+pub type AuthCacheKey = [u8; 32];
+
+struct CachedIdentity {
+    provider: String,
+    user: UserContext,
+    /// 得出结论前询问过的插件，任一重载都会使该条目失效
+    consulted: Vec<String>,
+    expires_at: Instant,
+}
+
+/// 鉴权结果缓存
+///
+/// 只缓存成功的结果；键只覆盖鉴权链声明的请求头，未声明的链不参与缓存。
+pub struct AuthCache {
+    ttl: Duration,
+    max_entries: usize,
+    entries: Mutex<HashMap<AuthCacheKey, CachedIdentity>>,
+}
+
+impl AuthCache {
+    pub fn new(settings: &AuthCacheSettings) -> Self {
+        Self {
+            ttl: Duration::from_secs(settings.ttl_secs),
+            max_entries: settings.max_entries,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn enabled(&self) -> bool {
+        !self.ttl.is_zero() && self.max_entries > 0
+    }
+
+    /// 按鉴权链声明的请求头计算缓存键；链为空或任一插件未声明请求头时返回 `None`
+    pub fn key(
+        &self,
+        chain: &[AuthProviderSettings],
+        headers: &axum::http::HeaderMap,
+    ) -> Option<AuthCacheKey> {
+        if !self.enabled() || chain.is_empty() || chain.iter().any(|p| p.cache_headers.is_empty()) {
+            return None;
+        }
+        let mut names: Vec<String> = chain
+            .iter()
+            .flat_map(|p| p.cache_headers.iter().map(|h| h.to_ascii_lowercase()))
+            .collect();
+        names.sort();
+        names.dedup();
+
+        let mut hasher = Sha256::new();
+        for provider in chain {
+            hasher.update(provider.plugin_id.as_bytes());
+            hasher.update([0]);
+        }
+        for name in &names {
+            hasher.update(name.as_bytes());
+            hasher.update([0]);
+            for value in headers.get_all(name.as_str()) {
+                hasher.update(value.as_bytes());
+                hasher.update([0]);
+            }
+            hasher.update([1]);
+        }
+        Some(hasher.finalize().into())
+    }
+
+    /// 未过期的缓存结果：（给出结论的插件，用户）
+    pub fn get(&self, key: &AuthCacheKey) -> Option<(String, UserContext)> {
+        let mut entries = self.entries.lock().unwrap();
+        let entry = entries.get(key)?;
+        if entry.expires_at <= Instant::now() {
+            entries.remove(key);
+            return None;
+        }
+        Some((entry.provider.clone(), entry.user.clone()))
+    }
+
+    pub fn insert(
+        &self,
+        key: AuthCacheKey,
+        provider: &str,
+        user: &UserContext,
+        consulted: Vec<String>,
+    ) {
+        let now = Instant::now();
+        let mut entries = self.entries.lock().unwrap();
+        if entries.len() >= self.max_entries && !entries.contains_key(&key) {
+            entries.retain(|_, entry| entry.expires_at > now);
+            if entries.len() >= self.max_entries {
+                let oldest = entries
+                    .iter()
+                    .min_by_key(|(_, entry)| entry.expires_at)
+                    .map(|(key, _)| *key);
+                if let Some(oldest) = oldest {
+                    entries.remove(&oldest);
+                }
+            }
+        }
+        entries.insert(
+            key,
+            CachedIdentity {
+                provider: provider.to_string(),
+                user: user.clone(),
+                consulted,
+                expires_at: now + self.ttl,
+            },
+        );
+    }
+
+    /// 移除询问过该插件的条目，返回移除数量
+    pub fn invalidate_plugin(&self, plugin_id: &str) -> usize {
+        let mut entries = self.entries.lock().unwrap();
+        let before = entries.len();
+        entries.retain(|_, entry| !entry.consulted.iter().any(|id| id == plugin_id));
+        before - entries.len()
+    }
+
+    /// 清空缓存，返回移除数量
+    pub fn clear(&self) -> usize {
+        let mut entries = self.entries.lock().unwrap();
+        let count = entries.len();
+        entries.clear();
+        count
+    }
+}
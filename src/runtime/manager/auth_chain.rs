@@ -0,0 +1,293 @@
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tracing::{error, info, warn};
+
+use super::auth_cache::AuthCacheKey;
+use super::{PluginManager, PluginRuntime};
+use crate::config::AuthProviderSettings;
+use crate::runtime::vtx_host_impl::api::vtx_auth_types::UserContext;
+
+/// 插件声明可参与鉴权的能力（写在 capabilities 的 permissions 中）
+///
+/// 写入 `auth_providers` 配置的插件视为已由管理员批准；未配置鉴权链时按能力发现，
+/// 只有该能力经权限审批授予后插件才会加入鉴权。旧版 `auth_provider` 设置指定的插件
+/// 即使未声明该能力也照常参与鉴权，以免升级后无法启动。
+pub const AUTH_CAPABILITY: &str = "auth:provider";
+
+/// 保留的鉴权记录数量
+const TRACE_CAPACITY: usize = 1024;
+
+/// 鉴权链中单个插件的结果，`status` 为 200 表示通过
+#[derive(Debug, Clone, Serialize)]
+pub struct AuthAttempt {
+    pub plugin_id: String,
+    pub status: u16,
+}
+
+/// 一次请求的鉴权过程
+#[derive(Debug, Clone, Serialize)]
+pub struct AuthTrace {
+    pub request_id: String,
+    /// 最终给出结论的插件（通过或拒绝），所有插件都放行时为 `None`
+    pub provider: Option<String>,
+    pub status: u16,
+    pub user_id: Option<String>,
+    /// 命中鉴权缓存，未调用插件
+    pub cached: bool,
+    pub attempts: Vec<AuthAttempt>,
+    /// Unix 时间戳（毫秒）
+    pub timestamp_ms: u64,
+}
+
+/// 最近的鉴权记录，按请求 ID 查询
+pub struct AuthTraces {
+    traces: Mutex<VecDeque<AuthTrace>>,
+}
+
+impl AuthTraces {
+    pub fn new() -> Self {
+        Self {
+            traces: Mutex::new(VecDeque::with_capacity(TRACE_CAPACITY)),
+        }
+    }
+
+    fn push(&self, trace: AuthTrace) {
+        let mut traces = self.traces.lock().unwrap();
+        if traces.len() >= TRACE_CAPACITY {
+            traces.pop_front();
+        }
+        traces.push_back(trace);
+    }
+
+    pub fn get(&self, request_id: &str) -> Option<AuthTrace> {
+        let traces = self.traces.lock().unwrap();
+        traces
+            .iter()
+            .rev()
+            .find(|t| t.request_id == request_id)
+            .cloned()
+    }
+}
+
+impl Default for AuthTraces {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 鉴权能力是否已经审批授予（按能力发现时使用）
+pub fn granted_auth(runtime: &PluginRuntime) -> bool {
+    runtime.grants().permissions.contains(AUTH_CAPABILITY)
+}
+
+/// 插件是否声明了鉴权能力
+pub fn declares_auth(runtime: &PluginRuntime) -> bool {
+    runtime
+        .policy
+        .permissions
+        .iter()
+        .any(|p| p == AUTH_CAPABILITY)
+}
+
+impl PluginManager {
+    /// 当前鉴权链下请求的缓存键，链中有插件未声明缓存请求头时为 `None`
+    pub fn auth_cache_key(&self, headers: &axum::http::HeaderMap) -> Option<AuthCacheKey> {
+        self.auth_cache.key(&self.auth_providers, headers)
+    }
+
+    /// 命中鉴权缓存时直接返回用户，不实例化插件
+    pub fn cached_identity(
+        &self,
+        cache_key: Option<&AuthCacheKey>,
+        request_id: &str,
+    ) -> Option<UserContext> {
+        let (provider, user) = self.auth_cache.get(cache_key?)?;
+        self.auth_traces.push(AuthTrace {
+            request_id: request_id.to_string(),
+            provider: Some(provider),
+            status: 200,
+            user_id: Some(user.user_id.clone()),
+            cached: true,
+            attempts: Vec::new(),
+            timestamp_ms: now_ms(),
+        });
+        Some(user)
+    }
+
+    /// 按鉴权链顺序验证请求身份
+    ///
+    /// 插件返回 `continue_on` 中的状态码时询问下一个插件，其余结果立即生效；
+    /// 链中所有插件都放行时返回 401。每次鉴权都会按请求 ID 记录下来。
+    /// `cache_key` 由调用方通过 [`Self::auth_cache_key`] 计算，并已用它查询过缓存。
+    pub async fn verify_identity(
+        &self,
+        headers: &axum::http::HeaderMap,
+        cache_key: Option<AuthCacheKey>,
+        request_id: &str,
+    ) -> Result<UserContext, u16> {
+        let wit_headers: Vec<(String, String)> = headers
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_str().unwrap_or("").to_string()))
+            .collect();
+
+        let mut attempts = Vec::new();
+        let mut decided: Option<(String, Result<UserContext, u16>)> = None;
+        for (settings, runtime) in self.auth_chain() {
+            let result = match runtime {
+                Some(runtime) => {
+                    self.invoke_authenticate(&runtime, &wit_headers, request_id)
+                        .await
+                }
+                None => {
+                    error!(
+                        "[Auth] Configured auth provider '{}' is unavailable",
+                        settings.plugin_id
+                    );
+                    Err(500)
+                }
+            };
+            let status = *result.as_ref().err().unwrap_or(&200);
+            attempts.push(AuthAttempt {
+                plugin_id: settings.plugin_id.clone(),
+                status,
+            });
+            if result.is_err() && settings.continue_on.contains(&status) {
+                continue;
+            }
+            decided = Some((settings.plugin_id, result));
+            break;
+        }
+
+        let (provider, result) = match decided {
+            Some((provider, result)) => (Some(provider), result),
+            None => (None, Err(401)),
+        };
+        if let (Some(key), Some(provider), Ok(user)) = (cache_key, &provider, &result) {
+            let consulted = attempts.iter().map(|a| a.plugin_id.clone()).collect();
+            self.auth_cache.insert(key, provider, user, consulted);
+        }
+        self.auth_traces.push(AuthTrace {
+            request_id: request_id.to_string(),
+            provider,
+            status: *result.as_ref().err().unwrap_or(&200),
+            user_id: result.as_ref().ok().map(|user| user.user_id.clone()),
+            cached: false,
+            attempts,
+            timestamp_ms: now_ms(),
+        });
+        result
+    }
+
+    /// 本次鉴权要询问的插件；配置的插件不可用或未声明鉴权能力时为 `None`
+    fn auth_chain(&self) -> Vec<(AuthProviderSettings, Option<Arc<PluginRuntime>>)> {
+        let plugins = self.plugins.read().unwrap();
+        if !self.auth_providers.is_empty() {
+            return self
+                .auth_providers
+                .iter()
+                .map(|settings| {
+                    let runtime = plugins
+                        .get(&settings.plugin_id)
+                        .filter(|r| {
+                            !self.is_disabled(&r.id) && (settings.legacy || declares_auth(r))
+                        })
+                        .cloned();
+                    (settings.clone(), runtime)
+                })
+                .collect();
+        }
+
+        let mut discovered: Vec<Arc<PluginRuntime>> = plugins
+            .values()
+            .filter(|r| !self.is_disabled(&r.id) && granted_auth(r))
+            .cloned()
+            .collect();
+        discovered.sort_by(|a, b| a.id.cmp(&b.id));
+        discovered
+            .into_iter()
+            .map(|runtime| {
+                let settings = AuthProviderSettings {
+                    plugin_id: runtime.id.clone(),
+                    continue_on: vec![401],
+                    cache_headers: Vec::new(),
+                    legacy: false,
+                };
+                (settings, Some(runtime))
+            })
+            .collect()
+    }
+
+    /// 是否为配置的鉴权插件（禁止停用与卸载）
+    pub(super) fn is_auth_provider(&self, plugin_id: &str) -> bool {
+        self.auth_providers.iter().any(|p| p.plugin_id == plugin_id)
+    }
+
+    /// 启动时确认配置的鉴权插件均已加载、启用并声明了鉴权能力
+    pub(super) fn verify_auth_providers(&self) -> anyhow::Result<()> {
+        for settings in self.auth_providers.iter() {
+            let auth_id = &settings.plugin_id;
+            if self.is_disabled(auth_id) {
+                warn!(
+                    "[Auth] auth provider '{}' was disabled; re-enabling it.",
+                    auth_id
+                );
+                self.set_enabled(auth_id, true)?;
+            }
+            let plugins = self.plugins.read().unwrap();
+            let Some(runtime) = plugins.get(auth_id) else {
+                error!(
+                    "[Fatal] Configured auth provider '{}' not found in loaded plugins!",
+                    auth_id
+                );
+                return Err(anyhow::anyhow!(
+                    "Critical: Configured auth provider '{}' is missing. Startup aborted.",
+                    auth_id
+                ));
+            };
+            if !declares_auth(runtime) {
+                if settings.legacy {
+                    warn!(
+                        "[Auth] auth_provider '{}' does not declare the '{}' capability; \
+                         keeping it as the legacy provider. Declare the capability or move it to auth_providers.",
+                        auth_id, AUTH_CAPABILITY
+                    );
+                    continue;
+                }
+                return Err(anyhow::anyhow!(
+                    "Critical: Configured auth provider '{}' does not declare the '{}' capability. Startup aborted.",
+                    auth_id,
+                    AUTH_CAPABILITY
+                ));
+            }
+            info!("[Auth] Verified auth provider '{}' is active.", auth_id);
+        }
+        if self.auth_providers.is_empty() {
+            warn!(
+                "[Auth] No auth_providers configured; plugins granted '{}' are asked in plugin ID order",
+                AUTH_CAPABILITY
+            );
+        }
+        Ok(())
+    }
+
+    /// 按请求 ID 查询鉴权记录
+    pub fn auth_trace(&self, request_id: &str) -> Option<AuthTrace> {
+        self.auth_traces.get(request_id)
+    }
+
+    /// 清空鉴权缓存，返回移除的条目数
+    pub fn flush_auth_cache(&self) -> usize {
+        let flushed = self.auth_cache.clear();
+        info!("[Auth] Flushed {} cached identities", flushed);
+        flushed
+    }
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
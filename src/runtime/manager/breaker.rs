@@ -0,0 +1,231 @@
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+use crate::config::BreakerSettings;
+
+/// 半开状态下探测请求进行中时，其余请求的重试间隔
+const PROBE_RETRY: Duration = Duration::from_secs(1);
+
+/// 熔断器状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BreakerState {
+    Closed,
+    Open,
+    HalfOpen,
+}
+
+/// 对外展示的熔断器状态
+#[derive(Debug, Clone, Serialize)]
+pub struct BreakerStatus {
+    pub state: BreakerState,
+    /// 当前窗口内的失败次数
+    pub failures: usize,
+    /// 熔断打开时距离允许探测的剩余秒数
+    pub retry_after_secs: Option<u64>,
+}
+
+struct Breaker {
+    state: BreakerState,
+    failures: VecDeque<Instant>,
+    open_until: Option<Instant>,
+    probing: bool,
+}
+
+impl Breaker {
+    fn new() -> Self {
+        Self {
+            state: BreakerState::Closed,
+            failures: VecDeque::new(),
+            open_until: None,
+            probing: false,
+        }
+    }
+}
+
+/// 熔断器放行的一次调用
+///
+/// 调用结束时通过 `record` 提交结果。半开状态下的探测调用若未提交就被丢弃
+/// （客户端断开、提前返回或 panic），按失败处理以释放探测名额。
+#[must_use = "call result must be recorded"]
+pub struct CallPermit<'a> {
+    breakers: &'a CircuitBreakers,
+    plugin_id: String,
+    probe: bool,
+    recorded: bool,
+}
+
+impl CallPermit<'_> {
+    /// 是否为半开状态下的探测调用
+    pub fn is_probe(&self) -> bool {
+        self.probe
+    }
+
+    pub fn record(mut self, ok: bool) {
+        self.recorded = true;
+        if ok {
+            self.breakers.record_success(&self.plugin_id);
+        } else {
+            self.breakers.record_failure(&self.plugin_id);
+        }
+    }
+}
+
+impl Drop for CallPermit<'_> {
+    fn drop(&mut self) {
+        if self.is_probe() && !self.recorded {
+            warn!(
+                "[Breaker] Probe of '{}' abandoned without a result",
+                self.plugin_id
+            );
+            self.breakers.record_failure(&self.plugin_id);
+        }
+    }
+}
+
+/// 按插件划分的熔断器
+///
+/// 窗口内连续失败达到阈值后打开，打开期间拒绝调用；到期后进入半开状态，
+/// 只放行一个探测调用，成功则关闭，失败则重新打开。
+pub struct CircuitBreakers {
+    settings: BreakerSettings,
+    breakers: Mutex<HashMap<String, Breaker>>,
+}
+
+impl CircuitBreakers {
+    pub fn new(settings: BreakerSettings) -> Self {
+        Self {
+            settings,
+            breakers: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn enabled(&self) -> bool {
+        self.settings.failure_threshold > 0
+    }
+
+    /// 是否放行本次调用；拒绝时返回建议的重试间隔
+    pub fn admit(&self, plugin_id: &str) -> Result<CallPermit<'_>, Duration> {
+        let permit = |probe| CallPermit {
+            breakers: self,
+            plugin_id: plugin_id.to_string(),
+            probe,
+            recorded: false,
+        };
+        if !self.enabled() {
+            return Ok(permit(false));
+        }
+        let mut breakers = self.breakers.lock().unwrap();
+        let Some(breaker) = breakers.get_mut(plugin_id) else {
+            return Ok(permit(false));
+        };
+        let now = Instant::now();
+        match breaker.state {
+            BreakerState::Closed => Ok(permit(false)),
+            BreakerState::Open => {
+                let until = breaker.open_until.unwrap_or(now);
+                if now < until {
+                    return Err(until - now);
+                }
+                info!("[Breaker] '{}' half-open, probing", plugin_id);
+                breaker.state = BreakerState::HalfOpen;
+                breaker.probing = true;
+                Ok(permit(true))
+            }
+            BreakerState::HalfOpen => {
+                if breaker.probing {
+                    Err(PROBE_RETRY)
+                } else {
+                    breaker.probing = true;
+                    Ok(permit(true))
+                }
+            }
+        }
+    }
+
+    pub fn record_success(&self, plugin_id: &str) {
+        if !self.enabled() {
+            return;
+        }
+        let mut breakers = self.breakers.lock().unwrap();
+        if let Some(breaker) = breakers.get_mut(plugin_id) {
+            if breaker.state == BreakerState::HalfOpen {
+                info!("[Breaker] '{}' recovered, closing circuit", plugin_id);
+                *breaker = Breaker::new();
+            }
+        }
+    }
+
+    pub fn record_failure(&self, plugin_id: &str) {
+        if !self.enabled() {
+            return;
+        }
+        let now = Instant::now();
+        let window = Duration::from_secs(self.settings.window_secs);
+        let mut breakers = self.breakers.lock().unwrap();
+        let breaker = breakers
+            .entry(plugin_id.to_string())
+            .or_insert_with(Breaker::new);
+
+        breaker.failures.push_back(now);
+        while breaker
+            .failures
+            .front()
+            .is_some_and(|at| now.duration_since(*at) > window)
+        {
+            breaker.failures.pop_front();
+        }
+
+        let trip = match breaker.state {
+            BreakerState::HalfOpen => true,
+            BreakerState::Closed => {
+                breaker.failures.len() >= self.settings.failure_threshold as usize
+            }
+            BreakerState::Open => false,
+        };
+        if trip {
+            warn!(
+                "[Breaker] '{}' failed {} times, opening circuit for {}s",
+                plugin_id,
+                breaker.failures.len(),
+                self.settings.open_secs
+            );
+            breaker.state = BreakerState::Open;
+            breaker.open_until = Some(now + Duration::from_secs(self.settings.open_secs));
+            breaker.probing = false;
+        }
+    }
+
+    pub fn status(&self, plugin_id: &str) -> BreakerStatus {
+        let breakers = self.breakers.lock().unwrap();
+        let Some(breaker) = breakers.get(plugin_id) else {
+            return BreakerStatus {
+                state: BreakerState::Closed,
+                failures: 0,
+                retry_after_secs: None,
+            };
+        };
+        let retry_after_secs = match (breaker.state, breaker.open_until) {
+            (BreakerState::Open, Some(until)) => Some(
+                until
+                    .saturating_duration_since(Instant::now())
+                    .as_secs_f64()
+                    .ceil() as u64,
+            ),
+            _ => None,
+        };
+        BreakerStatus {
+            state: breaker.state,
+            failures: breaker.failures.len(),
+            retry_after_secs,
+        }
+    }
+
+    /// 关闭熔断并清空失败记录
+    pub fn reset(&self, plugin_id: &str) {
+        self.breakers.lock().unwrap().remove(plugin_id);
+    }
+}
@@ -0,0 +1,270 @@
+use hmac::{Hmac, Mac};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::hash::{Hash, Hasher};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use tracing::{debug, info, warn};
+use wasmtime::component::Component;
+use wasmtime::Engine;
+
+const CACHE_EXTENSION: &str = "cwasm";
+const DIGEST_LEN: usize = 32;
+const KEY_LEN: usize = 32;
+/// 签名密钥文件后缀，位于缓存目录旁（例如 `cache/components.key`）
+const KEY_EXTENSION: &str = "key";
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// 编译产物持久化缓存
+///
+/// 键为组件内容哈希与引擎编译配置哈希；文件格式为 `hmac_sha256(key, payload) || payload`。
+/// HMAC 密钥按安装随机生成，保存在缓存目录之外、仅当前用户可读的文件中；
+/// 反序列化前先校验 HMAC，损坏、伪造或不兼容的条目会被删除并重新编译。
+pub struct ComponentCache {
+    dir: Option<PathBuf>,
+    key: [u8; KEY_LEN],
+    engine_key: String,
+    hits: AtomicU64,
+    compiles: AtomicU64,
+    invalidated: AtomicU64,
+}
+
+/// 缓存统计信息
+#[derive(Debug, Clone, Serialize)]
+pub struct ComponentCacheStats {
+    pub enabled: bool,
+    pub hits: u64,
+    pub compiles: u64,
+    pub invalidated: u64,
+    pub entries: usize,
+    pub bytes: u64,
+}
+
+impl ComponentCache {
+    /// `dir` 为 `None` 时缓存关闭，每次都重新编译
+    pub fn new(engine: &Engine, dir: Option<PathBuf>) -> anyhow::Result<Self> {
+        let key = match &dir {
+            Some(dir) => {
+                std::fs::create_dir_all(dir)?;
+                load_or_create_key(&key_path(dir))?
+            }
+            None => [0; KEY_LEN],
+        };
+        let mut hasher = DigestHasher(Sha256::new());
+        engine.precompile_compatibility_hash().hash(&mut hasher);
+        Ok(Self {
+            dir,
+            key,
+            engine_key: hex::encode(hasher.0.finalize()),
+            hits: AtomicU64::new(0),
+            compiles: AtomicU64::new(0),
+            invalidated: AtomicU64::new(0),
+        })
+    }
+
+    /// 命中缓存则反序列化，否则编译并写回缓存
+    pub fn load_or_compile(
+        &self,
+        engine: &Engine,
+        component_bytes: &[u8],
+    ) -> anyhow::Result<Component> {
+        let Some(path) = self.entry_path(component_bytes) else {
+            self.compiles.fetch_add(1, Ordering::Relaxed);
+            return Component::new(engine, component_bytes);
+        };
+
+        if let Some(component) = self.try_load(engine, &path) {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            debug!("[ComponentCache] Hit: {}", path.display());
+            return Ok(component);
+        }
+
+        let component = Component::new(engine, component_bytes)?;
+        self.compiles.fetch_add(1, Ordering::Relaxed);
+        if let Err(e) = self.store(&component, &path) {
+            warn!("[ComponentCache] Failed to write {}: {}", path.display(), e);
+        }
+        Ok(component)
+    }
+
+    /// 清空缓存目录中的所有条目，返回删除的数量
+    pub fn purge(&self) -> anyhow::Result<usize> {
+        let Some(dir) = &self.dir else {
+            return Ok(0);
+        };
+        let mut removed = 0;
+        for path in cache_entries(dir)? {
+            std::fs::remove_file(&path)?;
+            removed += 1;
+        }
+        info!(
+            "[ComponentCache] Purged {} entries from {}",
+            removed,
+            dir.display()
+        );
+        Ok(removed)
+    }
+
+    pub fn stats(&self) -> ComponentCacheStats {
+        let (entries, bytes) = match &self.dir {
+            Some(dir) => cache_entries(dir)
+                .map(|paths| {
+                    let bytes = paths
+                        .iter()
+                        .filter_map(|p| std::fs::metadata(p).ok())
+                        .map(|m| m.len())
+                        .sum();
+                    (paths.len(), bytes)
+                })
+                .unwrap_or((0, 0)),
+            None => (0, 0),
+        };
+        ComponentCacheStats {
+            enabled: self.dir.is_some(),
+            hits: self.hits.load(Ordering::Relaxed),
+            compiles: self.compiles.load(Ordering::Relaxed),
+            invalidated: self.invalidated.load(Ordering::Relaxed),
+            entries,
+            bytes,
+        }
+    }
+
+    fn entry_path(&self, component_bytes: &[u8]) -> Option<PathBuf> {
+        let dir = self.dir.as_ref()?;
+        let mut hasher = Sha256::new();
+        hasher.update(self.engine_key.as_bytes());
+        hasher.update(component_bytes);
+        let key = hex::encode(hasher.finalize());
+        Some(dir.join(format!("{}.{}", key, CACHE_EXTENSION)))
+    }
+
+    fn try_load(&self, engine: &Engine, path: &Path) -> Option<Component> {
+        let data = std::fs::read(path).ok()?;
+        let verified = data.len() > DIGEST_LEN
+            && self
+                .mac(&data[DIGEST_LEN..])
+                .verify_slice(&data[..DIGEST_LEN])
+                .is_ok();
+        if !verified {
+            self.invalidate(path, "signature mismatch");
+            return None;
+        }
+
+        // SAFETY: 反序列化会执行条目中的本机代码。条目已通过 HMAC 校验，只能由持有本安装
+        // 密钥的一方写入；密钥位于缓存目录之外且仅当前用户可读，因此能写缓存目录但读不到
+        // 密钥的一方无法伪造条目。wasmtime 仍会校验引擎版本与编译配置的兼容性。
+        match unsafe { Component::deserialize(engine, &data[DIGEST_LEN..]) } {
+            Ok(component) => Some(component),
+            Err(e) => {
+                self.invalidate(path, &e.to_string());
+                None
+            }
+        }
+    }
+
+    fn store(&self, component: &Component, path: &Path) -> anyhow::Result<()> {
+        let payload = component.serialize()?;
+        let mut data = Vec::with_capacity(DIGEST_LEN + payload.len());
+        data.extend_from_slice(&self.mac(&payload).finalize().into_bytes());
+        data.extend_from_slice(&payload);
+
+        let tmp = path.with_extension(format!("{}.tmp", uuid::Uuid::new_v4()));
+        std::fs::write(&tmp, &data)?;
+        if let Err(e) = std::fs::rename(&tmp, path) {
+            let _ = std::fs::remove_file(&tmp);
+            return Err(e.into());
+        }
+        Ok(())
+    }
+
+    fn mac(&self, payload: &[u8]) -> HmacSha256 {
+        let mut mac = HmacSha256::new_from_slice(&self.key).expect("HMAC accepts any key length");
+        mac.update(payload);
+        mac
+    }
+
+    fn invalidate(&self, path: &Path, reason: &str) {
+        self.invalidated.fetch_add(1, Ordering::Relaxed);
+        warn!("[ComponentCache] Discarding {}: {}", path.display(), reason);
+        let _ = std::fs::remove_file(path);
+    }
+}
+
+fn key_path(dir: &Path) -> PathBuf {
+    let mut name = dir
+        .file_name()
+        .map(|name| name.to_os_string())
+        .unwrap_or_else(|| "components".into());
+    name.push(format!(".{}", KEY_EXTENSION));
+    dir.with_file_name(name)
+}
+
+/// 读取缓存签名密钥，不存在或格式不对时生成新密钥（旧条目随之失效）
+fn load_or_create_key(path: &Path) -> anyhow::Result<[u8; KEY_LEN]> {
+    if let Ok(existing) = std::fs::read(path) {
+        if let Ok(key) = <[u8; KEY_LEN]>::try_from(existing.as_slice()) {
+            restrict_permissions(path)?;
+            return Ok(key);
+        }
+        warn!(
+            "[ComponentCache] Invalid key file {}, regenerating",
+            path.display()
+        );
+        std::fs::remove_file(path)?;
+    }
+
+    let mut key = [0u8; KEY_LEN];
+    getrandom::getrandom(&mut key)
+        .map_err(|e| anyhow::anyhow!("Failed to generate cache key: {}", e))?;
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options.open(path)?.write_all(&key)?;
+    info!("[ComponentCache] Created cache key {}", path.display());
+    Ok(key)
+}
+
+/// 旧版本创建的密钥文件可能使用默认权限，收紧为仅当前用户可读写
+fn restrict_permissions(path: &Path) -> anyhow::Result<()> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(path)?.permissions().mode() & 0o777;
+        if mode != 0o600 {
+            std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = path;
+    Ok(())
+}
+
+fn cache_entries(dir: &Path) -> anyhow::Result<Vec<PathBuf>> {
+    let mut paths = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().and_then(|e| e.to_str()) == Some(CACHE_EXTENSION) {
+            paths.push(path);
+        }
+    }
+    Ok(paths)
+}
+
+/// 将 `Hash` 输出导入 SHA-256，保证键在不同进程间稳定
+struct DigestHasher(Sha256);
+
+impl Hasher for DigestHasher {
+    fn finish(&self) -> u64 {
+        0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        self.0.update(bytes);
+    }
+}
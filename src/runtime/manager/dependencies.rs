@@ -0,0 +1,289 @@
+use semver::{Version, VersionReq};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use tracing::{info, warn};
+
+use super::{PluginManager, PluginRejection, PluginStatus};
+
+/// 包元数据中声明的插件依赖（`"dependencies": { "<plugin-id>": "<semver range>" }`）
+#[derive(Debug, Clone, Serialize)]
+pub struct PluginDependency {
+    pub plugin_id: String,
+    pub requirement: VersionReq,
+}
+
+/// 未满足的依赖及原因
+#[derive(Debug, Clone, Serialize)]
+pub struct UnresolvedDependency {
+    pub plugin_id: String,
+    pub requirement: String,
+    pub reason: DependencyIssue,
+    /// 已加载的依赖版本（若存在）
+    pub found: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DependencyIssue {
+    /// 依赖插件未加载
+    Missing,
+    /// 依赖插件已停用
+    Disabled,
+    /// 依赖插件自身仍在等待依赖
+    Pending,
+    /// 依赖插件版本不满足要求
+    Incompatible,
+}
+
+/// 因依赖未满足而暂缓激活的插件
+pub(super) struct PendingPlugin {
+    pub(super) uri: String,
+    pub(super) dependencies: Vec<PluginDependency>,
+    pub(super) status: PluginStatus,
+}
+
+/// 从包元数据中解析依赖声明
+pub fn parse_dependencies(metadata: Option<&[u8]>) -> Result<Vec<PluginDependency>, String> {
+    #[derive(serde::Deserialize)]
+    struct Declared {
+        #[serde(default)]
+        dependencies: BTreeMap<String, String>,
+    }
+
+    let Some(bytes) = metadata else {
+        return Ok(Vec::new());
+    };
+    let Ok(declared) = serde_json::from_slice::<Declared>(bytes) else {
+        return Ok(Vec::new());
+    };
+    declared
+        .dependencies
+        .into_iter()
+        .map(|(plugin_id, requirement)| {
+            VersionReq::parse(&requirement)
+                .map(|requirement| PluginDependency {
+                    plugin_id: plugin_id.clone(),
+                    requirement,
+                })
+                .map_err(|e| {
+                    format!(
+                        "invalid version requirement '{}' for dependency '{}': {}",
+                        requirement, plugin_id, e
+                    )
+                })
+        })
+        .collect()
+}
+
+/// 按依赖关系拓扑排序，返回加载顺序（下标）
+///
+/// 依赖不在集合内的插件视为无约束；成环的插件排在最后，由依赖检查拒绝激活。
+pub fn load_order(nodes: &[(String, Vec<String>)]) -> Vec<usize> {
+    let index: HashMap<&str, usize> = nodes
+        .iter()
+        .enumerate()
+        .map(|(i, (id, _))| (id.as_str(), i))
+        .collect();
+
+    let mut in_degree = vec![0usize; nodes.len()];
+    let mut dependents: Vec<Vec<usize>> = vec![Vec::new(); nodes.len()];
+    for (i, (_, deps)) in nodes.iter().enumerate() {
+        let unique: HashSet<&str> = deps.iter().map(String::as_str).collect();
+        for dep in unique {
+            if let Some(&j) = index.get(dep) {
+                if j != i {
+                    in_degree[i] += 1;
+                    dependents[j].push(i);
+                }
+            }
+        }
+    }
+
+    let mut ready: Vec<usize> = (0..nodes.len()).filter(|&i| in_degree[i] == 0).collect();
+    let mut order = Vec::with_capacity(nodes.len());
+    while !ready.is_empty() {
+        ready.sort_by(|a, b| nodes[*b].0.cmp(&nodes[*a].0));
+        let next = ready.pop().unwrap();
+        order.push(next);
+        for &dependent in &dependents[next] {
+            in_degree[dependent] -= 1;
+            if in_degree[dependent] == 0 {
+                ready.push(dependent);
+            }
+        }
+    }
+
+    if order.len() < nodes.len() {
+        let mut cyclic: Vec<usize> = (0..nodes.len()).filter(|i| !order.contains(i)).collect();
+        cyclic.sort_by(|a, b| nodes[*a].0.cmp(&nodes[*b].0));
+        warn!(
+            "[Dependencies] Dependency cycle among: {:?}",
+            cyclic.iter().map(|&i| &nodes[i].0).collect::<Vec<_>>()
+        );
+        order.extend(cyclic);
+    }
+    order
+}
+
+impl PluginManager {
+    /// 对照当前已激活的插件检查依赖
+    pub(super) fn unresolved_dependencies(
+        &self,
+        dependencies: &[PluginDependency],
+    ) -> Vec<UnresolvedDependency> {
+        let plugins = self.plugins.read().unwrap();
+        let pending = self.pending.read().unwrap();
+        dependencies
+            .iter()
+            .filter_map(|dep| {
+                let unresolved = |reason, found: Option<&str>| UnresolvedDependency {
+                    plugin_id: dep.plugin_id.clone(),
+                    requirement: dep.requirement.to_string(),
+                    reason,
+                    found: found.map(str::to_string),
+                };
+                let Some(runtime) = plugins.get(&dep.plugin_id) else {
+                    let reason = if pending.contains_key(&dep.plugin_id) {
+                        DependencyIssue::Pending
+                    } else {
+                        DependencyIssue::Missing
+                    };
+                    return Some(unresolved(reason, None));
+                };
+                let version = runtime.manifest.version.as_str();
+                if self.is_disabled(&dep.plugin_id) {
+                    return Some(unresolved(DependencyIssue::Disabled, Some(version)));
+                }
+                let compatible = Version::parse(version)
+                    .map(|v| dep.requirement.matches(&v))
+                    .unwrap_or(false);
+                if !compatible {
+                    return Some(unresolved(DependencyIssue::Incompatible, Some(version)));
+                }
+                None
+            })
+            .collect()
+    }
+
+    /// 检查依赖是否满足，不满足时返回 `PluginRejection::DependencyUnresolved`
+    pub(super) fn check_dependencies(
+        &self,
+        plugin_id: &str,
+        dependencies: &[PluginDependency],
+    ) -> Result<(), PluginRejection> {
+        let unresolved = self.unresolved_dependencies(dependencies);
+        if unresolved.is_empty() {
+            return Ok(());
+        }
+        Err(PluginRejection::DependencyUnresolved {
+            plugin_id: plugin_id.to_string(),
+            unresolved,
+        })
+    }
+
+    /// 记录暂缓激活的插件；已有旧版本在服务时不做处理
+    pub(super) fn park_pending(&self, plugin_id: &str, pending: PendingPlugin) {
+        if self.plugins.read().unwrap().contains_key(plugin_id) {
+            return;
+        }
+        info!(
+            "[Dependencies] Plugin '{}' is waiting for dependencies",
+            plugin_id
+        );
+        self.pending
+            .write()
+            .unwrap()
+            .insert(plugin_id.to_string(), pending);
+    }
+
+    /// 停用依赖已失效的插件（级联），被停用的插件转入等待列表
+    pub(super) fn cascade_unresolved(&self) {
+        loop {
+            let active: Vec<_> = self.routes.read().unwrap().clone();
+            let broken: Vec<_> = active
+                .into_iter()
+                .filter_map(|runtime| {
+                    let unresolved = self.unresolved_dependencies(&runtime.dependencies);
+                    (!unresolved.is_empty()).then_some((runtime, unresolved))
+                })
+                .collect();
+            if broken.is_empty() {
+                return;
+            }
+
+            for (runtime, unresolved) in broken {
+                warn!(
+                    "[Dependencies] Deactivating '{}': dependencies no longer satisfied",
+                    runtime.id
+                );
+                {
+                    let mut plugins = self.plugins.write().unwrap();
+                    let mut routes = self.routes.write().unwrap();
+                    plugins.remove(&runtime.id);
+                    routes.retain(|p| p.id != runtime.id);
+                }
+                let mut status = PluginStatus::from_runtime(&runtime, false);
+                status.unresolved_dependencies = unresolved;
+                self.pending.write().unwrap().insert(
+                    runtime.id.clone(),
+                    PendingPlugin {
+                        uri: runtime.source_uri.clone(),
+                        dependencies: runtime.dependencies.clone(),
+                        status,
+                    },
+                );
+
+                let bus = self.event_bus.clone();
+                let plugin_id = runtime.id.clone();
+                tokio::spawn(async move {
+                    bus.unregister_plugin(&plugin_id).await;
+                });
+            }
+        }
+    }
+
+    /// 级联处理依赖变化：先停用失效的插件，再重新加载依赖已满足的等待插件
+    pub(super) async fn reconcile_dependencies(&self) {
+        let mut attempted = HashSet::new();
+        loop {
+            self.cascade_unresolved();
+
+            let candidates: Vec<(String, String, Vec<PluginDependency>)> = {
+                let pending = self.pending.read().unwrap();
+                pending
+                    .iter()
+                    .filter(|(id, _)| !attempted.contains(*id))
+                    .map(|(id, p)| (id.clone(), p.uri.clone(), p.dependencies.clone()))
+                    .collect()
+            };
+            let ready: Vec<(String, String)> = candidates
+                .into_iter()
+                .filter(|(_, _, deps)| self.unresolved_dependencies(deps).is_empty())
+                .map(|(id, uri, _)| (id, uri))
+                .collect();
+            if ready.is_empty() {
+                return;
+            }
+
+            for (plugin_id, uri) in ready {
+                attempted.insert(plugin_id.clone());
+                self.pending.write().unwrap().remove(&plugin_id);
+                if let Err(e) = self.load_package(&uri).await {
+                    warn!(
+                        "[Dependencies] Failed to activate '{}' after dependencies resolved: {}",
+                        plugin_id, e
+                    );
+                }
+            }
+        }
+    }
+
+    /// 在同步上下文中触发依赖级联处理
+    pub(super) fn spawn_reconcile(&self) {
+        self.cascade_unresolved();
+        let manager = self.clone();
+        tokio::spawn(async move {
+            manager.reconcile_dependencies().await;
+        });
+    }
+}
@@ -0,0 +1,325 @@
+use bytes::Bytes;
+use serde::Serialize;
+use tracing::{info, warn};
+use url::Url;
+
+use super::dependencies::UnresolvedDependency;
+use super::signature::DETACHED_SIGNATURE_SUFFIX;
+use super::{is_vtx_path, loader, PluginManager, PluginRuntime, PluginStatus};
+
+/// 插件安装被拒绝的结构化原因
+///
+/// 由加载流程以 `anyhow::Error` 形式抛出，管理接口通过 `downcast_ref` 还原。
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "reason", rename_all = "snake_case")]
+pub enum PluginRejection {
+    InvalidPackage {
+        message: String,
+    },
+    RouteConflict {
+        plugin_id: String,
+        entrypoint: String,
+        owner: String,
+    },
+    InstallationLocked {
+        plugin_id: String,
+        registered_uri: String,
+        attempted_uri: String,
+    },
+    MigrationRejected {
+        index: usize,
+        statement: String,
+        message: String,
+    },
+    MigrationFailed {
+        index: usize,
+        statement: String,
+        message: String,
+    },
+    SignatureRejected {
+        message: String,
+    },
+    DependencyUnresolved {
+        plugin_id: String,
+        unresolved: Vec<UnresolvedDependency>,
+    },
+    Downgrade {
+        plugin_id: String,
+        installed_version: String,
+        attempted_version: String,
+    },
+    MigrationsBehind {
+        plugin_id: String,
+        applied: usize,
+        declared: usize,
+    },
+    NoWritableRoot,
+}
+
+impl std::fmt::Display for PluginRejection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidPackage { message } => write!(f, "{}", message),
+            Self::RouteConflict {
+                plugin_id,
+                entrypoint,
+                owner,
+            } => write!(
+                f,
+                "Route conflict: '{}' is already owned by plugin '{}'. Installation of '{}' aborted.",
+                entrypoint, owner, plugin_id
+            ),
+            Self::InstallationLocked { plugin_id, .. } => write!(
+                f,
+                "Plugin ID '{}' is already registered with a different path. Installation aborted.",
+                plugin_id
+            ),
+            Self::MigrationRejected { message, .. } => {
+                write!(f, "Migration rejected: {}", message)
+            }
+            Self::MigrationFailed { message, .. } => write!(f, "Migration failed: {}", message),
+            Self::SignatureRejected { message } => {
+                write!(f, "Signature verification failed: {}", message)
+            }
+            Self::DependencyUnresolved {
+                plugin_id,
+                unresolved,
+            } => {
+                let names: Vec<String> = unresolved
+                    .iter()
+                    .map(|d| format!("{} {}", d.plugin_id, d.requirement))
+                    .collect();
+                write!(
+                    f,
+                    "Plugin '{}' has unresolved dependencies: {}",
+                    plugin_id,
+                    names.join(", ")
+                )
+            }
+            Self::Downgrade {
+                plugin_id,
+                installed_version,
+                attempted_version,
+            } => write!(
+                f,
+                "Refusing to downgrade '{}' from v{} to v{} without force",
+                plugin_id, installed_version, attempted_version
+            ),
+            Self::MigrationsBehind {
+                plugin_id,
+                applied,
+                declared,
+            } => write!(
+                f,
+                "Package of '{}' declares {} migration(s) but {} are already applied; refusing without force",
+                plugin_id, declared, applied
+            ),
+            Self::NoWritableRoot => write!(f, "No writable plugin root is configured"),
+        }
+    }
+}
+
+impl std::error::Error for PluginRejection {}
+
+impl PluginManager {
+    /// 通过上传的包内容安装或更新插件
+    ///
+    /// 流程：签名与解析校验 -> 路由冲突与依赖检查 -> 编译并冒烟验证 -> 锁定路径并迁移 -> 写入插件根目录 -> 注册。
+    /// 包写入优先级最高的可写根目录，热重载监听器不会再次加载这次写入。
+    ///
+    /// 提供独立签名时，签名文件会与包一起写入，供后续热重载与重启时校验。
+    /// 低于已安装版本的包只有在 `force` 时才会被接受。
+    pub async fn install_package(
+        &self,
+        file_name: Option<&str>,
+        bytes: Bytes,
+        detached_signature: Option<Bytes>,
+        force: bool,
+    ) -> anyhow::Result<PluginStatus> {
+        self.install_root()?;
+        let load_result = loader::inspect_package(
+            &self.engine,
+            &self.component_cache,
+            &self.signature_verifier,
+            &self.registry,
+            &self.linker,
+            &self.deadlines,
+            self.max_memory_bytes,
+            loader::PackageSource {
+                bytes: &bytes,
+                detached_signature: detached_signature.as_deref(),
+                source_hint: file_name.unwrap_or("upload.vtx"),
+            },
+            self.vtx_ffmpeg.clone(),
+            self.vfs.clone(),
+            self.event_bus.clone(),
+        )
+        .await?;
+
+        let uri = self.resolve_install_uri(&load_result.plugin_id, file_name)?;
+        {
+            let routes = self.routes.read().unwrap();
+            if let Some(rejection) = find_route_conflict(
+                &routes,
+                &load_result.plugin_id,
+                &load_result.manifest.entrypoint,
+            ) {
+                return Err(rejection.into());
+            }
+        }
+        if self.registry.is_plugin_enabled(&load_result.plugin_id)? {
+            self.check_dependencies(&load_result.plugin_id, &load_result.dependencies)?;
+        }
+        let previous_installation = self
+            .claim_installation(&load_result.plugin_id, &uri)
+            .await?;
+
+        let plugin_id = load_result.plugin_id.clone();
+        let version = load_result.manifest.version.clone();
+        let outcome = async {
+            let runtime = self
+                .prepare_runtime(load_result, uri.clone(), bytes.clone(), force)
+                .await?;
+            self.write_signed_package(&uri, bytes.clone(), detached_signature)
+                .await?;
+            info!("[Install] Package for '{}' written to {}", plugin_id, uri);
+            self.activate_runtime(runtime.clone())?;
+            Ok::<_, anyhow::Error>(runtime)
+        }
+        .await;
+        if let (Err(_), Some(previous)) = (&outcome, &previous_installation) {
+            self.restore_installation(&plugin_id, previous);
+        }
+        self.record_reload(
+            Some(&plugin_id),
+            &uri,
+            Some(&version),
+            outcome.as_ref().err(),
+        );
+        self.record_load(&plugin_id, &version, &bytes, &outcome, force);
+
+        let runtime = outcome?;
+        self.reconcile_dependencies().await;
+        Ok(PluginStatus::from_runtime(
+            &runtime,
+            !self.is_disabled(&runtime.id),
+        ))
+    }
+
+    /// 写入独立签名与包；包写入失败时恢复原有签名文件，避免新签名残留在旧包旁
+    async fn write_signed_package(
+        &self,
+        uri: &str,
+        package: Bytes,
+        signature: Option<Bytes>,
+    ) -> anyhow::Result<()> {
+        let Some(signature) = signature else {
+            return self.write_package(uri, package).await;
+        };
+        let sig_uri = format!("{}{}", uri, DETACHED_SIGNATURE_SUFFIX);
+        let previous = loader::read_detached_signature(&self.vfs, uri).await;
+        self.vfs
+            .put_object(&sig_uri, signature)
+            .await
+            .map_err(|e| {
+                anyhow::anyhow!("Failed to write plugin signature to {}: {}", sig_uri, e)
+            })?;
+
+        let result = self.write_package(uri, package).await;
+        if result.is_err() {
+            let restored = match previous {
+                Some(previous) => self.vfs.put_object(&sig_uri, previous).await,
+                None => self.vfs.delete_object(&sig_uri).await,
+            };
+            if let Err(e) = restored {
+                warn!("[Install] Failed to restore signature {}: {}", sig_uri, e);
+            }
+        }
+        result
+    }
+
+    /// 将包写入插件根目录，并让监听器跳过这次写入触发的重载
+    pub(super) async fn write_package(&self, uri: &str, package: Bytes) -> anyhow::Result<()> {
+        let normalized = self.vfs.normalize_uri(uri)?;
+        self.self_writes.mark(&normalized, &package);
+        if let Err(e) = self.vfs.put_object(uri, package).await {
+            self.self_writes.unmark(&normalized);
+            return Err(anyhow::anyhow!(
+                "Failed to write plugin package to {}: {}",
+                uri,
+                e
+            ));
+        }
+        Ok(())
+    }
+
+    fn resolve_install_uri(
+        &self,
+        plugin_id: &str,
+        file_name: Option<&str>,
+    ) -> anyhow::Result<String> {
+        let root = self.install_root()?;
+        if let Some(name) = file_name {
+            if !is_safe_file_name(name) || !is_vtx_path(name) {
+                return Err(PluginRejection::InvalidPackage {
+                    message: format!("Invalid plugin file name: {}", name),
+                }
+                .into());
+            }
+            return Ok(Url::parse(&root.uri)?.join(name)?.to_string());
+        }
+
+        if let Some(existing) = self.registry.get_installation(plugin_id)? {
+            if existing.starts_with(&root.uri) {
+                return Ok(existing);
+            }
+        }
+
+        let name = format!("{}.vtx", plugin_id);
+        if !is_safe_file_name(&name) {
+            return Err(PluginRejection::InvalidPackage {
+                message: format!("Plugin ID '{}' cannot be used as a file name", plugin_id),
+            }
+            .into());
+        }
+        let uri = Url::parse(&root.uri)?.join(&name)?;
+        Ok(uri.to_string())
+    }
+}
+
+pub(super) fn find_route_conflict(
+    routes: &[std::sync::Arc<PluginRuntime>],
+    plugin_id: &str,
+    entrypoint: &str,
+) -> Option<PluginRejection> {
+    routes
+        .iter()
+        .find(|existing| existing.manifest.entrypoint == entrypoint && existing.id != plugin_id)
+        .map(|existing| PluginRejection::RouteConflict {
+            plugin_id: plugin_id.to_string(),
+            entrypoint: entrypoint.to_string(),
+            owner: existing.id.clone(),
+        })
+}
+
+fn is_safe_file_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with('.')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '.')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn safe_file_name_rejects_traversal() {
+        assert!(is_safe_file_name("auth-basic_1.0.vtx"));
+        assert!(!is_safe_file_name("../auth.vtx"));
+        assert!(!is_safe_file_name("dir/auth.vtx"));
+        assert!(!is_safe_file_name(".hidden.vtx"));
+        assert!(!is_safe_file_name(""));
+    }
+}
@@ -0,0 +1,244 @@
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Instant;
+use tracing::{debug, warn};
+use wasmtime::component::InstancePre;
+use wasmtime::{Engine, Store};
+
+use crate::runtime::context::{InvocationScope, StreamContext};
+use crate::runtime::deadline::NO_DEADLINE;
+use crate::runtime::fuel;
+use crate::runtime::vtx_host_impl::VtxPlugin;
+
+/// 已实例化的插件，调用结束后交还 `InstancePool`
+pub struct PluginInstance {
+    pub store: Store<StreamContext>,
+    pub plugin: VtxPlugin,
+    /// 创建时的池代数，早于当前代数的实例以旧环境或旧授权构建
+    generation: u64,
+}
+
+/// 单个插件版本的预实例化实例池
+///
+/// 容量为 0 时不复用实例，每次调用都冷启动；统计信息始终记录，便于对比两种路径的延迟。
+/// 调用期间发生 trap、遗留宿主资源或创建于 `clear` 之前的实例不会归还。
+pub struct InstancePool {
+    capacity: usize,
+    idle: Mutex<Vec<PluginInstance>>,
+    generation: AtomicU64,
+    pooled_checkouts: AtomicU64,
+    pooled_nanos: AtomicU64,
+    cold_instantiations: AtomicU64,
+    cold_nanos: AtomicU64,
+    evictions: AtomicU64,
+}
+
+/// 实例池统计信息
+#[derive(Debug, Clone, Serialize)]
+pub struct InstancePoolStats {
+    pub plugin_id: String,
+    pub enabled: bool,
+    pub capacity: usize,
+    pub idle: usize,
+    pub pooled_checkouts: u64,
+    pub cold_instantiations: u64,
+    pub evictions: u64,
+    /// 从池中取出实例的平均耗时（微秒）
+    pub avg_pooled_us: Option<u64>,
+    /// 冷实例化的平均耗时（微秒）
+    pub avg_cold_us: Option<u64>,
+}
+
+impl InstancePool {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            idle: Mutex::new(Vec::with_capacity(capacity)),
+            generation: AtomicU64::new(0),
+            pooled_checkouts: AtomicU64::new(0),
+            pooled_nanos: AtomicU64::new(0),
+            cold_instantiations: AtomicU64::new(0),
+            cold_nanos: AtomicU64::new(0),
+            evictions: AtomicU64::new(0),
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.capacity > 0
+    }
+
+    /// 取出空闲实例并切换到本次调用的上下文；池为空时冷实例化
+    pub async fn checkout(
+        &self,
+        engine: &Engine,
+        instance_pre: &InstancePre<StreamContext>,
+        scope: InvocationScope,
+        make_context: impl FnOnce(InvocationScope) -> StreamContext,
+    ) -> anyhow::Result<PluginInstance> {
+        let started = Instant::now();
+        let idle = self.idle.lock().unwrap().pop();
+        if let Some(mut instance) = idle {
+            instance.store.data_mut().enter_scope(scope);
+            self.pooled_checkouts.fetch_add(1, Ordering::Relaxed);
+            self.pooled_nanos
+                .fetch_add(elapsed_nanos(started), Ordering::Relaxed);
+            return Ok(instance);
+        }
+
+        let generation = self.generation.load(Ordering::Acquire);
+        let instance = instantiate(engine, instance_pre, make_context(scope), generation).await?;
+        self.cold_instantiations.fetch_add(1, Ordering::Relaxed);
+        self.cold_nanos
+            .fetch_add(elapsed_nanos(started), Ordering::Relaxed);
+        Ok(instance)
+    }
+
+    /// 归还实例；`healthy` 为 false（调用中发生 trap）时直接丢弃
+    ///
+    /// 资源表非空（插件遗留了宿主资源句柄）或实例早于最近一次 `clear` 创建时同样丢弃。
+    pub fn checkin(&self, mut instance: PluginInstance, healthy: bool) {
+        if !self.enabled() {
+            return;
+        }
+        let plugin_id = instance.store.data().plugin_id.clone();
+        let plugin_id = plugin_id.as_deref().unwrap_or("?");
+        if !healthy {
+            self.evictions.fetch_add(1, Ordering::Relaxed);
+            warn!(
+                "[InstancePool] Evicted instance of '{}' after a failed call",
+                plugin_id
+            );
+            return;
+        }
+        if !instance.store.data().table.is_empty() {
+            self.evictions.fetch_add(1, Ordering::Relaxed);
+            warn!(
+                "[InstancePool] Evicted instance of '{}' holding leaked resources",
+                plugin_id
+            );
+            return;
+        }
+        instance.store.data_mut().leave_scope();
+        let mut idle = self.idle.lock().unwrap();
+        // 在持有锁时比较代数，与 `clear` 串行
+        if instance.generation != self.generation.load(Ordering::Acquire) {
+            debug!(
+                "[InstancePool] Dropped stale instance of '{}' (pool cleared)",
+                plugin_id
+            );
+            return;
+        }
+        if idle.len() < self.capacity {
+            idle.push(instance);
+        }
+    }
+
+    /// 预先填满实例池
+    pub async fn warm(
+        &self,
+        engine: &Engine,
+        instance_pre: &InstancePre<StreamContext>,
+        make_context: impl Fn() -> StreamContext,
+    ) -> anyhow::Result<()> {
+        let missing = self
+            .capacity
+            .saturating_sub(self.idle.lock().unwrap().len());
+        for _ in 0..missing {
+            let started = Instant::now();
+            let generation = self.generation.load(Ordering::Acquire);
+            let mut instance =
+                instantiate(engine, instance_pre, make_context(), generation).await?;
+            self.cold_instantiations.fetch_add(1, Ordering::Relaxed);
+            self.cold_nanos
+                .fetch_add(elapsed_nanos(started), Ordering::Relaxed);
+            instance.store.data_mut().leave_scope();
+            let mut idle = self.idle.lock().unwrap();
+            if generation != self.generation.load(Ordering::Acquire) {
+                // 预热期间池被清空，由新的预热任务重新填充
+                break;
+            }
+            if idle.len() < self.capacity {
+                idle.push(instance);
+            }
+        }
+        debug!("[InstancePool] Warmed {} instances", missing);
+        Ok(())
+    }
+
+    /// 丢弃全部空闲实例（插件版本、配置或授权变化时调用）
+    ///
+    /// 代数递增后，正在执行或实例化中的旧实例在归还时也会被丢弃。
+    pub fn clear(&self) {
+        let mut idle = self.idle.lock().unwrap();
+        self.generation.fetch_add(1, Ordering::AcqRel);
+        idle.clear();
+    }
+
+    pub fn stats(&self, plugin_id: &str) -> InstancePoolStats {
+        let pooled_checkouts = self.pooled_checkouts.load(Ordering::Relaxed);
+        let cold_instantiations = self.cold_instantiations.load(Ordering::Relaxed);
+        InstancePoolStats {
+            plugin_id: plugin_id.to_string(),
+            enabled: self.enabled(),
+            capacity: self.capacity,
+            idle: self.idle.lock().unwrap().len(),
+            pooled_checkouts,
+            cold_instantiations,
+            evictions: self.evictions.load(Ordering::Relaxed),
+            avg_pooled_us: average_micros(
+                self.pooled_nanos.load(Ordering::Relaxed),
+                pooled_checkouts,
+            ),
+            avg_cold_us: average_micros(
+                self.cold_nanos.load(Ordering::Relaxed),
+                cold_instantiations,
+            ),
+        }
+    }
+}
+
+/// 包元数据中是否声明插件无状态（`"stateless": true`），无状态插件可复用实例
+pub fn parse_stateless(metadata: Option<&[u8]>) -> bool {
+    #[derive(serde::Deserialize)]
+    struct Declared {
+        #[serde(default)]
+        stateless: bool,
+    }
+
+    metadata
+        .and_then(|bytes| serde_json::from_slice::<Declared>(bytes).ok())
+        .map(|declared| declared.stateless)
+        .unwrap_or(false)
+}
+
+async fn instantiate(
+    engine: &Engine,
+    instance_pre: &InstancePre<StreamContext>,
+    ctx: StreamContext,
+    generation: u64,
+) -> anyhow::Result<PluginInstance> {
+    let mut store = Store::new(engine, ctx);
+    store.limiter(|s| &mut s.limiter);
+    store.set_epoch_deadline(NO_DEADLINE);
+    fuel::prime(&mut store);
+    let instance = instance_pre
+        .instantiate_async(&mut store)
+        .await
+        .map_err(|e| anyhow::anyhow!("Fast instantiation failed: {}", e))?;
+    let plugin = VtxPlugin::new(&mut store, &instance)
+        .map_err(|e| anyhow::anyhow!("Plugin binding failed: {}", e))?;
+    Ok(PluginInstance {
+        store,
+        plugin,
+        generation,
+    })
+}
+
+fn elapsed_nanos(started: Instant) -> u64 {
+    u64::try_from(started.elapsed().as_nanos()).unwrap_or(u64::MAX)
+}
+
+fn average_micros(total_nanos: u64, count: u64) -> Option<u64> {
+    (count > 0).then(|| total_nanos / count / 1000)
+}
@@ -1,7 +1,17 @@
+use crate::config::DeadlineSettings;
 use crate::runtime::{
     context::{SecurityPolicy, StreamContext, StreamContextConfig},
+    deadline::{self, PluginCall},
     ffmpeg::VtxFfmpegManager,
-    manager::migration_policy,
+    fuel,
+    manager::{
+        component_cache::ComponentCache,
+        dependencies::PluginDependency,
+        migration_policy,
+        route_policy::RoutePolicy,
+        signature::{SignatureVerifier, DETACHED_SIGNATURE_SUFFIX},
+        PluginRejection,
+    },
     vtx_host_impl::VtxPlugin,
 };
 use crate::storage::VtxVideoRegistry;
@@ -21,26 +31,70 @@ pub struct LoadResult {
     pub policy: super::PluginPolicy,
     pub vtx_meta: Option<super::VtxPackageMetadata>,
     pub component: Component,
+    /// 规范化后的声明资源（表名）
+    pub resources: Vec<String>,
+    /// 经过策略校验与改写后的迁移语句
+    pub migrations: Vec<String>,
+    /// 签名校验通过的发布者
+    pub publisher: Option<String>,
+    /// 元数据中声明的插件依赖
+    pub dependencies: Vec<PluginDependency>,
+    /// 元数据中声明为无状态，可复用实例
+    pub stateless: bool,
+    /// 元数据中声明的路由访问策略
+    pub route_policy: Option<RoutePolicy>,
 }
 
-pub async fn load_and_migrate(
+/// 待检查的插件包内容
+pub struct PackageSource<'a> {
+    pub bytes: &'a [u8],
+    /// 独立签名文件内容（若存在）
+    pub detached_signature: Option<&'a [u8]>,
+    /// 用于日志与错误信息的来源描述
+    pub source_hint: &'a str,
+}
+
+/// 读取插件包原始内容（仅允许 `.vtx`）
+pub async fn read_package(vfs: &VtxVfsManager, vtx_uri: &str) -> anyhow::Result<bytes::Bytes> {
+    enforce_vtx_only(vtx_uri)?;
+    read_vtx_bytes(vfs, vtx_uri).await
+}
+
+/// 解析并校验插件包，不产生任何持久化副作用
+///
+/// 职责：编译组件、读取 manifest / capabilities / resources，并校验改写全部迁移语句。
+/// 检查期间的实例与冒烟验证一样受执行期限和 `max_memory_bytes` 内存上限约束。
+#[allow(clippy::too_many_arguments)]
+pub async fn inspect_package(
     engine: &Engine,
+    cache: &ComponentCache,
+    verifier: &SignatureVerifier,
     registry: &VtxVideoRegistry,
     linker: &Linker<StreamContext>,
-    vtx_uri: &str,
+    deadlines: &DeadlineSettings,
+    max_memory_bytes: usize,
+    source: PackageSource<'_>,
     vtx_ffmpeg: Arc<VtxFfmpegManager>,
     vfs: Arc<VtxVfsManager>,
     event_bus: Arc<crate::runtime::bus::EventBus>,
 ) -> anyhow::Result<LoadResult> {
-    enforce_vtx_only(vtx_uri)?;
-
-    let (component, vtx_meta) = load_component_from_vtx(engine, &vfs, vtx_uri).await?;
+    let CompiledPackage {
+        component,
+        vtx_meta,
+        publisher,
+        dependencies,
+        stateless,
+        route_policy,
+    } = compile_vtx_bytes(engine, cache, verifier, &source)?;
 
     let ctx = StreamContext::new_secure(StreamContextConfig {
         registry: registry.clone(),
         vtx_ffmpeg,
         vfs,
-        limiter: wasmtime::StoreLimitsBuilder::new().build(),
+        limiter: wasmtime::StoreLimitsBuilder::new()
+            .instances(1)
+            .memory_size(max_memory_bytes)
+            .build(),
         policy: SecurityPolicy::Root,
         plugin_id: None,
         max_buffer_read_bytes: 0,
@@ -48,30 +102,129 @@ pub async fn load_and_migrate(
         event_bus,
         permissions: std::collections::HashSet::new(),
         http_allowlist: Vec::new(),
+        env: Vec::new(),
+        scratch_dir: None,
+        logs: None,
+        request_id: None,
+        request: None,
     });
     let mut store = wasmtime::Store::new(engine, ctx);
+    store.limiter(|s| &mut s.limiter);
+    let limit_ms = deadline::limit_ms(deadlines, "", PluginCall::Inspect);
+    deadline::arm(&mut store, deadlines, limit_ms);
+    fuel::prime(&mut store);
+    let timed = |e| inspect_error(e, source.source_hint, limit_ms);
 
-    let instance = linker.instantiate_async(&mut store, &component).await?;
+    let instance = linker
+        .instantiate_async(&mut store, &component)
+        .await
+        .map_err(timed)?;
     let plugin = VtxPlugin::new(&mut store, &instance)?;
 
-    let manifest = plugin.call_get_manifest(&mut store).await?;
+    let manifest = plugin.call_get_manifest(&mut store).await.map_err(timed)?;
     let plugin_id = manifest.id.clone();
-    let capabilities = plugin.call_get_capabilities(&mut store).await?;
+    let capabilities = plugin
+        .call_get_capabilities(&mut store)
+        .await
+        .map_err(timed)?;
     let policy = super::PluginPolicy {
         subscriptions: capabilities.subscriptions,
         permissions: capabilities.permissions,
         http: capabilities.http.unwrap_or_default(),
     };
 
-    if !registry.verify_installation(&plugin_id, vtx_uri)? {
-        return Err(anyhow::anyhow!(
-            "Plugin ID '{}' is already registered with a different path. Installation aborted.",
-            plugin_id
-        ));
+    let declared_resources = plugin.call_get_resources(&mut store).await.map_err(timed)?;
+    let normalized_resources =
+        migration_policy::normalize_declared_resources(&plugin_id, declared_resources)
+            .map_err(|e| anyhow::anyhow!("Invalid resource declaration: {}", e))?;
+    let declared_set = normalized_resources
+        .iter()
+        .cloned()
+        .collect::<std::collections::HashSet<_>>();
+    let migrations = plugin
+        .call_get_migrations(&mut store)
+        .await
+        .map_err(timed)?;
+    let mut rewritten_migrations = Vec::with_capacity(migrations.len());
+    for (index, sql) in migrations.into_iter().enumerate() {
+        let rewritten =
+            migration_policy::validate_and_rewrite_migration(&plugin_id, &declared_set, &sql)
+                .map_err(|message| PluginRejection::MigrationRejected {
+                    index,
+                    statement: sql.clone(),
+                    message,
+                })?;
+        rewritten_migrations.push(rewritten);
+    }
+
+    Ok(LoadResult {
+        plugin_id,
+        manifest,
+        policy,
+        vtx_meta,
+        component,
+        resources: normalized_resources,
+        migrations: rewritten_migrations,
+        publisher,
+        dependencies,
+        stateless,
+        route_policy,
+    })
+}
+
+/// 检查阶段的期限中断转换为可读错误
+pub fn inspect_error(err: anyhow::Error, source: &str, limit_ms: u64) -> anyhow::Error {
+    if deadline::is_deadline_exceeded(&err) {
+        anyhow::anyhow!(
+            "{}: plugin inspection exceeded its deadline of {}ms",
+            source,
+            limit_ms
+        )
+    } else {
+        err
+    }
+}
+
+/// 锁定安装路径、记录元数据并执行尚未应用的迁移
+pub fn apply_migrations(
+    registry: &VtxVideoRegistry,
+    load_result: &LoadResult,
+    vtx_uri: &str,
+    snapshot_retention: usize,
+) -> anyhow::Result<()> {
+    let plugin_id = &load_result.plugin_id;
+    let manifest = &load_result.manifest;
+
+    if !registry.verify_installation(plugin_id, vtx_uri)? {
+        return Err(PluginRejection::InstallationLocked {
+            plugin_id: plugin_id.clone(),
+            registered_uri: registry.get_installation(plugin_id)?.unwrap_or_default(),
+            attempted_uri: vtx_uri.to_string(),
+        }
+        .into());
+    }
+
+    let publisher = load_result.publisher.as_deref();
+    if let Ok(Some(previous)) = registry.get_plugin_publisher(plugin_id) {
+        if publisher.is_some_and(|p| p != previous) {
+            tracing::warn!(
+                "[plugin/signature] Publisher of {} changed: '{}' -> '{}'",
+                plugin_id,
+                previous,
+                publisher.unwrap_or_default()
+            );
+        }
+    }
+    if let Err(e) = registry.set_plugin_publisher(plugin_id, publisher) {
+        tracing::warn!(
+            "[plugin/meta] Failed to persist publisher for {}: {}",
+            plugin_id,
+            e
+        );
     }
 
-    if let Some(meta) = vtx_meta.as_ref() {
-        if let Err(e) = registry.set_plugin_metadata(&plugin_id, meta) {
+    if let Some(meta) = load_result.vtx_meta.as_ref() {
+        if let Err(e) = registry.set_plugin_metadata(plugin_id, meta) {
             tracing::warn!(
                 "[plugin/meta] Failed to persist metadata for {}: {}",
                 plugin_id,
@@ -82,35 +235,27 @@ pub async fn load_and_migrate(
 
     info!(
         "[plugin/init] Plugin loaded: {} (v{}) - {}",
-        &plugin_id, manifest.version, manifest.name
+        plugin_id, manifest.version, manifest.name
     );
 
-    let declared_resources = plugin.call_get_resources(&mut store).await?;
-    let normalized_resources =
-        migration_policy::normalize_declared_resources(&plugin_id, declared_resources)
-            .map_err(|e| anyhow::anyhow!("Invalid resource declaration: {}", e))?;
-    let declared_set = normalized_resources
-        .iter()
-        .cloned()
-        .collect::<std::collections::HashSet<_>>();
-    let migrations = plugin.call_get_migrations(&mut store).await?;
-    let mut rewritten_migrations = Vec::with_capacity(migrations.len());
-    for sql in migrations {
-        let rewritten =
-            migration_policy::validate_and_rewrite_migration(&plugin_id, &declared_set, &sql)
-                .map_err(|e| anyhow::anyhow!("Migration rejected: {}", e))?;
-        rewritten_migrations.push(rewritten);
-    }
-    let current_ver = registry.get_plugin_version(&plugin_id);
+    let rewritten_migrations = &load_result.migrations;
+    let current_ver = registry.get_plugin_version(plugin_id);
 
     if rewritten_migrations.len() > current_ver {
         info!(
             "[plugin/migration] Starting DB migration: {} (v{} -> v{})",
-            &plugin_id,
+            plugin_id,
             current_ver,
             rewritten_migrations.len()
         );
 
+        // 保留数为 0 时不创建快照
+        if snapshot_retention > 0 {
+            registry
+                .create_plugin_snapshot(plugin_id)
+                .context("Failed to snapshot plugin tables before migration")?;
+        }
+
         let mut conn = registry
             .pool
             .get()
@@ -123,7 +268,7 @@ pub async fn load_and_migrate(
             debug!(
                 "[plugin/migration] Executing migration #{} for {}",
                 idx + 1,
-                &plugin_id
+                plugin_id
             );
 
             if let Err(e) = tx.execute(sql, []) {
@@ -133,41 +278,55 @@ pub async fn load_and_migrate(
                     e
                 );
 
-                return Err(anyhow::anyhow!("Migration failed: {}", e));
+                return Err(PluginRejection::MigrationFailed {
+                    index: idx,
+                    statement: sql.clone(),
+                    message: e.to_string(),
+                }
+                .into());
             }
         }
 
         tx.commit()
             .context("Failed to commit migration transaction")?;
 
-        registry.set_plugin_version(&plugin_id, rewritten_migrations.len());
+        registry.set_plugin_version(plugin_id, rewritten_migrations.len());
 
-        for table_name in normalized_resources {
-            registry.register_resource(&plugin_id, "TABLE", &table_name);
+        for table_name in &load_result.resources {
+            registry.register_resource(plugin_id, "TABLE", table_name);
             info!(
                 "[plugin/resource] Registered table resource: {}",
                 table_name
             );
         }
 
+        if snapshot_retention > 0 {
+            match registry.prune_plugin_snapshots(plugin_id, snapshot_retention) {
+                Ok(0) => {}
+                Ok(pruned) => debug!(
+                    "[plugin/migration] Pruned {} old snapshots for {}",
+                    pruned, plugin_id
+                ),
+                Err(e) => tracing::warn!(
+                    "[plugin/migration] Failed to prune snapshots for {}: {}",
+                    plugin_id,
+                    e
+                ),
+            }
+        }
+
         info!(
             "[plugin/migration] Migration complete for plugin: {}",
-            &plugin_id
+            plugin_id
         );
     } else {
         info!(
             "[plugin/migration] No migration needed. Plugin '{}' database is up to date.",
-            &plugin_id
+            plugin_id
         );
     }
 
-    Ok(LoadResult {
-        plugin_id,
-        manifest,
-        policy,
-        vtx_meta,
-        component,
-    })
+    Ok(())
 }
 
 fn enforce_vtx_only(uri: &str) -> anyhow::Result<()> {
@@ -178,35 +337,92 @@ fn enforce_vtx_only(uri: &str) -> anyhow::Result<()> {
     Ok(())
 }
 
-async fn load_component_from_vtx(
-    engine: &Engine,
-    vfs: &VtxVfsManager,
-    uri: &str,
-) -> anyhow::Result<(Component, Option<super::VtxPackageMetadata>)> {
+/// 读取包旁的独立签名文件，不存在时返回 `None`
+pub async fn read_detached_signature(vfs: &VtxVfsManager, vtx_uri: &str) -> Option<bytes::Bytes> {
+    let sig_uri = format!("{}{}", vtx_uri, DETACHED_SIGNATURE_SUFFIX);
+    let meta = vfs.head(&sig_uri).await.ok()?;
+    match vfs.read_range(&sig_uri, 0, meta.size).await {
+        Ok(bytes) => Some(bytes),
+        Err(e) => {
+            debug!("[plugin/signature] Failed to read {}: {}", sig_uri, e);
+            None
+        }
+    }
+}
+
+async fn read_vtx_bytes(vfs: &VtxVfsManager, uri: &str) -> anyhow::Result<bytes::Bytes> {
     let meta = vfs
         .head(uri)
         .await
         .with_context(|| format!("failed to read plugin package metadata: {}", uri))?;
-    let bytes = vfs
-        .read_range(uri, 0, meta.size)
+    vfs.read_range(uri, 0, meta.size)
         .await
-        .with_context(|| format!("failed to read plugin package: {}", uri))?;
+        .with_context(|| format!("failed to read plugin package: {}", uri))
+}
 
-    let decoded = vtx_format::decode_with_metadata(&bytes)
-        .with_context(|| format!("invalid vtx package: {}", uri))?;
+struct CompiledPackage {
+    component: Component,
+    vtx_meta: Option<super::VtxPackageMetadata>,
+    publisher: Option<String>,
+    dependencies: Vec<PluginDependency>,
+    stateless: bool,
+    route_policy: Option<RoutePolicy>,
+}
+
+/// 解码、校验签名并编译组件
+///
+/// 签名校验发生在编译与实例化之前，未通过的包不会执行任何代码。
+fn compile_vtx_bytes(
+    engine: &Engine,
+    cache: &ComponentCache,
+    verifier: &SignatureVerifier,
+    source: &PackageSource<'_>,
+) -> anyhow::Result<CompiledPackage> {
+    let uri = source.source_hint;
+    let decoded = vtx_format::decode_with_metadata(source.bytes).map_err(|e| {
+        PluginRejection::InvalidPackage {
+            message: format!("invalid vtx package: {}: {}", uri, e),
+        }
+    })?;
 
     let version = decoded.version;
-    let vtx_meta = decoded.metadata.and_then(parse_vtx_metadata_json);
     let component_bytes = decoded.component;
+    let publisher = verifier.verify(
+        component_bytes,
+        decoded.metadata,
+        source.detached_signature,
+        uri,
+    )?;
+    let vtx_meta = decoded.metadata.and_then(parse_vtx_metadata_json);
+    let dependencies = super::dependencies::parse_dependencies(decoded.metadata).map_err(|e| {
+        PluginRejection::InvalidPackage {
+            message: format!("{}: {}", uri, e),
+        }
+    })?;
+
+    let stateless = super::instance_pool::parse_stateless(decoded.metadata);
+    let route_policy = super::route_policy::parse_route_policy(decoded.metadata).map_err(|e| {
+        PluginRejection::InvalidPackage {
+            message: format!("{}: {}", uri, e),
+        }
+    })?;
 
-    Component::new(engine, component_bytes)
-        .with_context(|| {
-            format!(
-                "failed to compile component from vtx (version {}): {}",
-                version, uri
-            )
-        })
-        .map(|c| (c, vtx_meta))
+    let component = cache
+        .load_or_compile(engine, component_bytes)
+        .map_err(|e| PluginRejection::InvalidPackage {
+            message: format!(
+                "failed to compile component from vtx (version {}): {}: {}",
+                version, uri, e
+            ),
+        })?;
+    Ok(CompiledPackage {
+        component,
+        vtx_meta,
+        publisher,
+        dependencies,
+        stateless,
+        route_policy,
+    })
 }
 
 fn parse_vtx_metadata_json(bytes: &[u8]) -> Option<super::VtxPackageMetadata> {
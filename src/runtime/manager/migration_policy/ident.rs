@@ -1,4 +1,5 @@
-pub(super) fn plugin_prefix(plugin_id: &str) -> String {
+/// 插件数据库对象的命名前缀
+pub(crate) fn plugin_prefix(plugin_id: &str) -> String {
     format!("vtx_plugin_{}_", plugin_id)
 }
 
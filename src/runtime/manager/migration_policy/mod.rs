@@ -4,10 +4,15 @@ mod ident;
 mod rewrite;
 mod token;
 
-use ident::{normalize_name, plugin_prefix};
-use rewrite::{apply_replacements, rewrite_ident, rewrite_index_ident};
+use ident::normalize_name;
+pub(crate) use ident::plugin_prefix;
+use rewrite::{
+    apply_replacements, rewrite_body_references, rewrite_fts5_content, rewrite_ident,
+    rewrite_index_ident,
+};
 use token::{
-    ensure_single_statement, next_identifier, next_table_ident, next_word, tokenize, TokenKind,
+    ensure_single_statement, ensure_single_trigger, is_qualifier, next_identifier,
+    next_table_ident, next_word, tokenize, Token, TokenKind,
 };
 
 pub fn normalize_declared_resources(
@@ -28,106 +33,212 @@ pub fn normalize_declared_resources(
     Ok(out)
 }
 
+/// 校验单条迁移语句并将表名改写到插件命名空间
+///
+/// 允许的语句：
+/// - `CREATE TABLE` / `DROP TABLE` / `ALTER TABLE`（含 `RENAME TO`），表名须已声明
+/// - `CREATE [UNIQUE] INDEX`、`CREATE VIEW`、`CREATE TRIGGER` 及对应的 `DROP`，对象名自动加前缀
+/// - `CREATE VIRTUAL TABLE ... USING fts5(...)`
+///
+/// 视图与触发器主体中引用的表同样须已声明。
 pub fn validate_and_rewrite_migration(
     plugin_id: &str,
     declared_tables: &HashSet<String>,
     statement: &str,
 ) -> Result<String, String> {
-    ensure_single_statement(statement)?;
-
     let tokens = tokenize(statement)?;
     if tokens.is_empty() {
         return Err("Migration SQL is empty".into());
     }
 
-    let first = tokens
+    let first_idx = tokens
         .iter()
-        .find(|t| t.kind == TokenKind::Word)
-        .map(|t| t.value.to_ascii_lowercase())
+        .position(|t| t.kind == TokenKind::Word)
         .ok_or("Migration SQL is empty")?;
 
-    let allowed = ["create", "alter", "drop"];
-    if !allowed.contains(&first.as_str()) {
-        return Err("Migration SQL not allowed".into());
+    let is_trigger = tokens[first_idx].value.eq_ignore_ascii_case("create")
+        && next_word(&tokens, first_idx + 1).is_ok_and(|(_, word)| word == "trigger");
+    if is_trigger {
+        ensure_single_trigger(&tokens)?;
+    } else {
+        ensure_single_statement(statement)?;
     }
 
     let prefix = plugin_prefix(plugin_id);
-    let mut replacements: Vec<(usize, usize, String)> = Vec::new();
-
-    let mut i = 0;
-    while i < tokens.len() {
-        let tok = &tokens[i];
-        if tok.kind != TokenKind::Word {
-            i += 1;
-            continue;
-        }
-        let word = tok.value.to_ascii_lowercase();
-
-        if word == "create" {
-            let (kind_idx, kind_word) = next_word(&tokens, i + 1)?;
-            if kind_word == "table" {
-                let table_idx = next_table_ident(&tokens, kind_idx + 1)?;
-                let replacement = rewrite_ident(&tokens[table_idx], &prefix, declared_tables)?;
-                replacements.push(replacement);
-                i = table_idx + 1;
-                continue;
-            }
-            if kind_word == "index" || kind_word == "unique" {
-                let mut idx = kind_idx;
-                if kind_word == "unique" {
-                    let (next_idx, next_word) = next_word(&tokens, idx + 1)?;
-                    if next_word != "index" {
-                        return Err("Migration SQL not allowed".into());
-                    }
-                    idx = next_idx;
-                }
-                let index_idx = next_identifier(&tokens, idx + 1)?;
-                let replacement = rewrite_index_ident(&tokens[index_idx], &prefix)?;
-                replacements.push(replacement);
+    let replacements = match tokens[first_idx].value.to_ascii_lowercase().as_str() {
+        "create" => rewrite_create(statement, &tokens, first_idx, &prefix, declared_tables)?,
+        "alter" => rewrite_alter(&tokens, first_idx, &prefix, declared_tables)?,
+        "drop" => rewrite_drop(&tokens, first_idx, &prefix, declared_tables)?,
+        _ => return Err("Migration SQL not allowed".into()),
+    };
+
+    if replacements.is_empty() {
+        return Err("Migration SQL not allowed".into());
+    }
+
+    Ok(apply_replacements(statement, &replacements))
+}
 
-                let (on_idx, on_word) = next_word(&tokens, index_idx + 1)?;
-                if on_word != "on" {
+fn rewrite_create(
+    statement: &str,
+    tokens: &[Token],
+    create_idx: usize,
+    prefix: &str,
+    declared_tables: &HashSet<String>,
+) -> Result<Vec<(usize, usize, String)>, String> {
+    let (kind_idx, kind_word) = next_word(tokens, create_idx + 1)?;
+    let mut replacements = Vec::new();
+
+    match kind_word.as_str() {
+        "table" => {
+            let table_idx = next_table_ident(tokens, kind_idx + 1)?;
+            replacements.push(rewrite_ident(&tokens[table_idx], prefix, declared_tables)?);
+        }
+        "index" | "unique" => {
+            let mut idx = kind_idx;
+            if kind_word == "unique" {
+                let (next_idx, next_word) = next_word(tokens, idx + 1)?;
+                if next_word != "index" {
                     return Err("Migration SQL not allowed".into());
                 }
-                let table_idx = next_identifier(&tokens, on_idx + 1)?;
-                let replacement = rewrite_ident(&tokens[table_idx], &prefix, declared_tables)?;
-                replacements.push(replacement);
-                i = table_idx + 1;
-                continue;
+                idx = next_idx;
             }
-            return Err("Migration SQL not allowed".into());
-        }
+            let index_idx = next_identifier(tokens, idx + 1)?;
+            replacements.push(rewrite_index_ident(&tokens[index_idx], prefix)?);
 
-        if word == "alter" {
-            let (table_idx, table_word) = next_word(&tokens, i + 1)?;
-            if table_word != "table" {
+            let (on_idx, on_word) = next_word(tokens, index_idx + 1)?;
+            if on_word != "on" {
                 return Err("Migration SQL not allowed".into());
             }
-            let ident_idx = next_identifier(&tokens, table_idx + 1)?;
-            let replacement = rewrite_ident(&tokens[ident_idx], &prefix, declared_tables)?;
-            replacements.push(replacement);
-            i = ident_idx + 1;
-            continue;
+            let table_idx = next_identifier(tokens, on_idx + 1)?;
+            replacements.push(rewrite_ident(&tokens[table_idx], prefix, declared_tables)?);
         }
-
-        if word == "drop" {
-            let (kind_idx, kind_word) = next_word(&tokens, i + 1)?;
-            if kind_word != "index" {
+        "view" => {
+            reject_temporary(tokens, create_idx, kind_idx)?;
+            let view_idx = next_table_ident(tokens, kind_idx + 1)?;
+            if is_qualifier(tokens, view_idx) {
+                return Err("Migration SQL not allowed".into());
+            }
+            replacements.push(rewrite_index_ident(&tokens[view_idx], prefix)?);
+            replacements.extend(rewrite_body_references(
+                tokens,
+                view_idx + 1,
+                prefix,
+                declared_tables,
+            )?);
+        }
+        "trigger" => {
+            reject_temporary(tokens, create_idx, kind_idx)?;
+            let trigger_idx = next_table_ident(tokens, kind_idx + 1)?;
+            if is_qualifier(tokens, trigger_idx) {
+                return Err("Migration SQL not allowed".into());
+            }
+            replacements.push(rewrite_index_ident(&tokens[trigger_idx], prefix)?);
+
+            let on_idx = tokens
+                .iter()
+                .enumerate()
+                .skip(trigger_idx + 1)
+                .find(|(_, t)| {
+                    t.kind == TokenKind::Word
+                        && t.quote.is_none()
+                        && t.value.eq_ignore_ascii_case("on")
+                })
+                .map(|(i, _)| i)
+                .ok_or("Migration SQL not allowed")?;
+            let table_idx = next_identifier(tokens, on_idx + 1)?;
+            if is_qualifier(tokens, table_idx) {
                 return Err("Migration SQL not allowed".into());
             }
-            let index_idx = next_identifier(&tokens, kind_idx + 1)?;
-            let replacement = rewrite_index_ident(&tokens[index_idx], &prefix)?;
-            replacements.push(replacement);
-            i = index_idx + 1;
-            continue;
+            replacements.push(rewrite_ident(&tokens[table_idx], prefix, declared_tables)?);
+            replacements.extend(rewrite_body_references(
+                tokens,
+                table_idx + 1,
+                prefix,
+                declared_tables,
+            )?);
         }
+        "virtual" => {
+            let (table_kw_idx, table_word) = next_word(tokens, kind_idx + 1)?;
+            if table_word != "table" {
+                return Err("Migration SQL not allowed".into());
+            }
+            let table_idx = next_table_ident(tokens, table_kw_idx + 1)?;
+            if is_qualifier(tokens, table_idx) {
+                return Err("Migration SQL not allowed".into());
+            }
+            replacements.push(rewrite_ident(&tokens[table_idx], prefix, declared_tables)?);
 
-        i += 1;
+            let (using_idx, using_word) = next_word(tokens, table_idx + 1)?;
+            let (module_idx, module) = next_word(tokens, using_idx + 1)?;
+            if using_word != "using" || module != "fts5" {
+                return Err("Migration SQL not allowed".into());
+            }
+            replacements.extend(rewrite_fts5_content(
+                statement,
+                tokens,
+                module_idx + 1,
+                prefix,
+                declared_tables,
+            )?);
+        }
+        _ => return Err("Migration SQL not allowed".into()),
     }
 
-    if replacements.is_empty() {
+    Ok(replacements)
+}
+
+fn rewrite_alter(
+    tokens: &[Token],
+    alter_idx: usize,
+    prefix: &str,
+    declared_tables: &HashSet<String>,
+) -> Result<Vec<(usize, usize, String)>, String> {
+    let (table_idx, table_word) = next_word(tokens, alter_idx + 1)?;
+    if table_word != "table" {
         return Err("Migration SQL not allowed".into());
     }
+    let ident_idx = next_identifier(tokens, table_idx + 1)?;
+    let mut replacements = vec![rewrite_ident(&tokens[ident_idx], prefix, declared_tables)?];
+
+    // `RENAME TO` 的新表名同样须已声明；`RENAME [COLUMN] a TO b` 不涉及表名
+    if let Ok((rename_idx, word)) = next_word(tokens, ident_idx + 1) {
+        if word == "rename" {
+            let (to_idx, to_word) = next_word(tokens, rename_idx + 1)?;
+            if to_word == "to" {
+                let new_idx = next_identifier(tokens, to_idx + 1)?;
+                replacements.push(rewrite_ident(&tokens[new_idx], prefix, declared_tables)?);
+            }
+        }
+    }
 
-    Ok(apply_replacements(statement, &replacements))
+    Ok(replacements)
+}
+
+fn rewrite_drop(
+    tokens: &[Token],
+    drop_idx: usize,
+    prefix: &str,
+    declared_tables: &HashSet<String>,
+) -> Result<Vec<(usize, usize, String)>, String> {
+    let (kind_idx, kind_word) = next_word(tokens, drop_idx + 1)?;
+    let ident_idx = next_table_ident(tokens, kind_idx + 1)?;
+    let replacement = match kind_word.as_str() {
+        "table" => rewrite_ident(&tokens[ident_idx], prefix, declared_tables)?,
+        "index" | "view" | "trigger" => rewrite_index_ident(&tokens[ident_idx], prefix)?,
+        _ => return Err("Migration SQL not allowed".into()),
+    };
+    Ok(vec![replacement])
+}
+
+/// 临时视图 / 触发器仅存在于单个连接，不允许用于迁移
+fn reject_temporary(tokens: &[Token], create_idx: usize, kind_idx: usize) -> Result<(), String> {
+    let temporary = tokens[create_idx + 1..kind_idx].iter().any(|t| {
+        t.kind == TokenKind::Word
+            && (t.value.eq_ignore_ascii_case("temp") || t.value.eq_ignore_ascii_case("temporary"))
+    });
+    if temporary {
+        return Err("Migration SQL not allowed".into());
+    }
+    Ok(())
 }
@@ -1,7 +1,30 @@
 use std::collections::HashSet;
 
 use super::ident::normalize_name;
-use super::token::Token;
+use super::token::{is_punct, is_qualifier, Token, TokenKind};
+
+/// 结束 FROM 子句的关键字
+const CLAUSE_KEYWORDS: &[&str] = &[
+    "where",
+    "group",
+    "order",
+    "having",
+    "limit",
+    "window",
+    "union",
+    "except",
+    "intersect",
+    "set",
+    "values",
+    "select",
+    "returning",
+    "begin",
+    "end",
+    "when",
+    "then",
+    "else",
+    "do",
+];
 
 pub(super) fn rewrite_ident(
     token: &Token,
@@ -25,6 +48,187 @@ pub(super) fn rewrite_index_ident(
     Ok((token.start, token.end, replacement))
 }
 
+/// 改写视图 / 触发器主体中引用的表名
+///
+/// FROM / JOIN / INTO / UPDATE / IN 之后的表名必须是已声明的表，且不允许带 schema 限定或
+/// 使用表值函数；作为列限定符（`items.id`）出现的已声明表名同步改写。
+pub(super) fn rewrite_body_references(
+    tokens: &[Token],
+    start: usize,
+    prefix: &str,
+    declared_tables: &HashSet<String>,
+) -> Result<Vec<(usize, usize, String)>, String> {
+    #[derive(Default, Clone, Copy)]
+    struct Scope {
+        in_from: bool,
+        expect_table: bool,
+    }
+
+    let mut scopes = vec![Scope::default()];
+    let mut replacements = Vec::new();
+    let mut skip_words = 0;
+
+    for i in start..tokens.len() {
+        let token = &tokens[i];
+        if token.kind == TokenKind::Other {
+            match token.value.as_str() {
+                "(" => {
+                    // `FROM (a JOIN b)` 形式的括号内仍处于 FROM 子句
+                    let outer = *scopes.last().unwrap();
+                    scopes.push(Scope {
+                        in_from: outer.expect_table,
+                        expect_table: outer.expect_table,
+                    });
+                }
+                ")" => {
+                    if scopes.len() > 1 {
+                        scopes.pop();
+                    }
+                    scopes.last_mut().unwrap().expect_table = false;
+                }
+                "," => {
+                    let scope = scopes.last_mut().unwrap();
+                    if scope.in_from {
+                        scope.expect_table = true;
+                    }
+                }
+                ";" => *scopes.last_mut().unwrap() = Scope::default(),
+                _ => {}
+            }
+            continue;
+        }
+
+        // 限定符之后的列名
+        if i > 0 && is_punct(tokens.get(i - 1), ".") {
+            continue;
+        }
+        if skip_words > 0 {
+            skip_words -= 1;
+            continue;
+        }
+
+        let scope = scopes.last_mut().unwrap();
+        let keyword = token
+            .quote
+            .is_none()
+            .then(|| token.value.to_ascii_lowercase());
+
+        if is_qualifier(tokens, i) {
+            if scope.expect_table {
+                return Err("Migration SQL not allowed".into());
+            }
+            if let Ok(normalized) = normalize_name(prefix, &token.value) {
+                if declared_tables.contains(&normalized) {
+                    replacements.push((token.start, token.end, token.wrap(&normalized)));
+                }
+            }
+            continue;
+        }
+
+        if scope.expect_table {
+            if matches!(keyword.as_deref(), Some("select" | "values" | "with")) {
+                *scope = Scope::default();
+                continue;
+            }
+            // FROM 中的表值函数；`INSERT INTO t (...)` 的括号为列清单
+            if scope.in_from && is_punct(tokens.get(i + 1), "(") {
+                return Err("Migration SQL not allowed".into());
+            }
+            replacements.push(rewrite_ident(token, prefix, declared_tables)?);
+            scope.expect_table = false;
+            continue;
+        }
+
+        let next_word = tokens
+            .get(i + 1)
+            .filter(|t| t.kind == TokenKind::Word && t.quote.is_none())
+            .map(|t| t.value.to_ascii_lowercase());
+        match keyword.as_deref() {
+            Some("from" | "join") => {
+                scope.in_from = true;
+                scope.expect_table = true;
+            }
+            Some("into") => {
+                scope.in_from = false;
+                scope.expect_table = true;
+            }
+            // `UPDATE OR <冲突处理> t`；`DO UPDATE SET` 不引用表
+            Some("update") => match next_word.as_deref() {
+                Some("set") => {}
+                Some("or") => {
+                    skip_words = 2;
+                    scope.expect_table = true;
+                }
+                _ => scope.expect_table = true,
+            },
+            // `x IN table`
+            Some("in") if tokens.get(i + 1).is_some_and(|t| t.kind == TokenKind::Word) => {
+                scope.expect_table = true;
+            }
+            Some("on" | "using") => scope.expect_table = false,
+            Some(k) if CLAUSE_KEYWORDS.contains(&k) => *scope = Scope::default(),
+            _ => {}
+        }
+    }
+
+    Ok(replacements)
+}
+
+/// 校验 fts5 选项中的外部内容表（`content=items` / `content='items'`）
+pub(super) fn rewrite_fts5_content(
+    statement: &str,
+    tokens: &[Token],
+    start: usize,
+    prefix: &str,
+    declared_tables: &HashSet<String>,
+) -> Result<Vec<(usize, usize, String)>, String> {
+    let mut replacements = Vec::new();
+    for i in start..tokens.len() {
+        let token = &tokens[i];
+        let is_content = token.kind == TokenKind::Word
+            && token.quote.is_none()
+            && token.value.eq_ignore_ascii_case("content");
+        if !is_content || !is_punct(tokens.get(i + 1), "=") {
+            continue;
+        }
+
+        let eq_end = tokens[i + 1].end;
+        let gap_end = tokens.get(i + 2).map_or(statement.len(), |t| t.start);
+        let gap = &statement[eq_end..gap_end];
+        let literal = gap.trim();
+        if literal.is_empty() {
+            // 标识符形式
+            let value = tokens
+                .get(i + 2)
+                .filter(|t| t.kind == TokenKind::Word)
+                .ok_or("Migration SQL not allowed")?;
+            replacements.push(rewrite_ident(value, prefix, declared_tables)?);
+            continue;
+        }
+
+        // 字符串形式；`content=''` 表示无内容表
+        let inner = literal
+            .strip_prefix('\'')
+            .and_then(|l| l.strip_suffix('\''))
+            .filter(|l| !l.contains('\''))
+            .ok_or("Migration SQL not allowed")?;
+        if inner.is_empty() {
+            continue;
+        }
+        let normalized = normalize_name(prefix, inner)?;
+        if !declared_tables.contains(&normalized) {
+            return Err("Migration SQL not allowed".into());
+        }
+        let literal_start = eq_end + (gap.len() - gap.trim_start().len());
+        replacements.push((
+            literal_start,
+            literal_start + literal.len(),
+            format!("'{}'", normalized),
+        ));
+    }
+    Ok(replacements)
+}
+
 pub(super) fn apply_replacements(
     statement: &str,
     replacements: &[(usize, usize, String)],
@@ -159,6 +159,16 @@ pub(super) fn next_table_ident(tokens: &[Token], start: usize) -> Result<usize,
     Err("Migration SQL not allowed".into())
 }
 
+/// 判断 token 是否为指定的单字符标点
+pub(super) fn is_punct(token: Option<&Token>, punct: &str) -> bool {
+    token.is_some_and(|t| t.kind == TokenKind::Other && t.value == punct)
+}
+
+/// 判断标识符后是否紧跟 `.`（即 `schema.table` 或 `table.column` 的限定符）
+pub(super) fn is_qualifier(tokens: &[Token], idx: usize) -> bool {
+    is_punct(tokens.get(idx + 1), ".")
+}
+
 pub(super) fn ensure_single_statement(statement: &str) -> Result<(), String> {
     let bytes = statement.as_bytes();
     let mut i = 0;
@@ -241,6 +251,27 @@ pub(super) fn ensure_single_statement(statement: &str) -> Result<(), String> {
     Ok(())
 }
 
+/// 触发器主体 `BEGIN ... END` 内允许多条语句，主体之外仍只允许单条语句
+pub(super) fn ensure_single_trigger(tokens: &[Token]) -> Result<(), String> {
+    let is_keyword = |t: &Token, word: &str| {
+        t.kind == TokenKind::Word && t.quote.is_none() && t.value.eq_ignore_ascii_case(word)
+    };
+    let begin = tokens
+        .iter()
+        .position(|t| is_keyword(t, "begin"))
+        .ok_or("Migration SQL not allowed")?;
+
+    let mut end = tokens.len();
+    if is_punct(tokens.last(), ";") {
+        end -= 1;
+    }
+    let closed = tokens[..end].last().is_some_and(|t| is_keyword(t, "end"));
+    if !closed || tokens[..begin].iter().any(|t| is_punct(Some(t), ";")) {
+        return Err("Migration SQL not allowed".into());
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1,28 +1,69 @@
+pub mod auth_cache;
+pub mod auth_chain;
+pub mod breaker;
+pub mod component_cache;
+pub mod dependencies;
+mod install;
+pub mod instance_pool;
 pub mod loader;
 pub mod migration_policy;
+pub mod permissions;
+pub mod plugin_config;
+mod reload;
+pub mod roots;
+pub mod route_policy;
+pub mod scratch;
+pub mod signature;
+pub mod validate;
+pub mod versions;
 pub mod watcher;
 
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
 use std::sync::{Arc, RwLock};
-use tracing::{error, info, warn};
+use std::time::Duration;
+use tracing::{debug, error, info, warn};
 use wasmtime::component::{Component, InstancePre, Linker};
 use wasmtime::Engine;
 
+use crate::config::{
+    AuthCacheSettings, AuthProviderSettings, BreakerSettings, DeadlineSettings, FuelSettings,
+    PluginRootSettings, PoolSettings, ScratchSettings, SignatureSettings, WatchSettings,
+};
 use crate::runtime::bus::EventBus;
-use crate::runtime::context::{SecurityPolicy, StreamContext, StreamContextConfig};
-use crate::runtime::executor::{EventDispatchContext, VtxPluginExecutor};
+use crate::runtime::context::{
+    InvocationScope, SecurityPolicy, StreamContext, StreamContextConfig,
+};
+use crate::runtime::deadline::{self, PluginCall};
+use crate::runtime::executor::{EventDispatchContext, ExecutionError, VtxPluginExecutor};
 use crate::runtime::ffmpeg::VtxFfmpegManager;
+use crate::runtime::fuel::{FuelMeter, FuelUsage};
 use crate::runtime::vtx_host_impl::api::vtx_auth_types::UserContext;
 use crate::runtime::vtx_host_impl::api::vtx_types::{HttpAllowRule, Manifest};
-use crate::runtime::vtx_host_impl::VtxPlugin;
 use crate::storage::VtxVideoRegistry;
 use crate::vtx_vfs::VtxVfsManager;
 use anyhow::Context;
+use bytes::Bytes;
 use futures_util::StreamExt;
 use url::Url;
 
+use crate::runtime::plugin_logs::{PluginLogLine, PluginLogs};
+use auth_cache::AuthCache;
+use auth_chain::AuthTraces;
+use breaker::{BreakerStatus, CallPermit, CircuitBreakers};
+use component_cache::{ComponentCache, ComponentCacheStats};
+use dependencies::{PendingPlugin, PluginDependency, UnresolvedDependency};
+pub use install::PluginRejection;
+use instance_pool::{InstancePool, InstancePoolStats};
+use permissions::PluginGrants;
+use plugin_config::PluginConfigStore;
+use roots::PluginRoot;
+use route_policy::RoutePolicy;
+use scratch::{ScratchMount, ScratchSpace};
+use signature::SignatureVerifier;
+use watcher::SelfWrites;
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct VtxAuthor {
     pub name: Option<String>,
@@ -55,9 +96,20 @@ pub struct PluginRuntime {
     #[allow(dead_code)]
     pub component: Component,
     pub source_uri: String,
+    /// 原始包内容，用于回滚与变更判定
+    pub package: Bytes,
+    /// 签名校验通过的发布者
+    pub publisher: Option<String>,
+    pub dependencies: Vec<PluginDependency>,
+    /// 预实例化实例池（未启用时容量为 0）
+    pub instances: InstancePool,
+    /// 已批准的权限，审批后即时更新
+    pub grants: RwLock<PluginGrants>,
+    /// 包元数据声明的路由访问策略
+    pub route_policy: Option<RoutePolicy>,
 }
 
-#[derive(Serialize)]
+#[derive(Clone, Serialize)]
 pub struct PluginStatus {
     pub id: String,
     pub name: String,
@@ -65,6 +117,62 @@ pub struct PluginStatus {
     pub entrypoint: String,
     pub source_path: String,
     pub vtx_meta: Option<VtxPackageMetadata>,
+    pub publisher: Option<String>,
+    pub enabled: bool,
+    pub dependencies: Vec<PluginDependency>,
+    /// 未满足的依赖（非空时插件未激活）
+    pub unresolved_dependencies: Vec<UnresolvedDependency>,
+    /// 熔断器状态（仅在插件列表中填充）
+    pub breaker: Option<BreakerStatus>,
+    /// 生效的路由访问策略（仅在插件列表中填充）
+    pub route_policy: Option<RoutePolicy>,
+}
+
+impl PluginStatus {
+    fn from_runtime(runtime: &PluginRuntime, enabled: bool) -> Self {
+        Self {
+            id: runtime.id.clone(),
+            name: runtime.manifest.name.clone(),
+            version: runtime.manifest.version.clone(),
+            entrypoint: runtime.manifest.entrypoint.clone(),
+            source_path: runtime.source_uri.clone(),
+            vtx_meta: runtime.vtx_meta.clone(),
+            publisher: runtime.publisher.clone(),
+            enabled,
+            dependencies: runtime.dependencies.clone(),
+            unresolved_dependencies: Vec::new(),
+            breaker: None,
+            route_policy: None,
+        }
+    }
+
+    fn pending(
+        load_result: &loader::LoadResult,
+        uri: &str,
+        unresolved: Vec<UnresolvedDependency>,
+    ) -> Self {
+        Self {
+            id: load_result.plugin_id.clone(),
+            name: load_result.manifest.name.clone(),
+            version: load_result.manifest.version.clone(),
+            entrypoint: load_result.manifest.entrypoint.clone(),
+            source_path: uri.to_string(),
+            vtx_meta: load_result.vtx_meta.clone(),
+            publisher: load_result.publisher.clone(),
+            enabled: false,
+            dependencies: load_result.dependencies.clone(),
+            unresolved_dependencies: unresolved,
+            breaker: None,
+            route_policy: None,
+        }
+    }
+}
+
+/// 已读取并解析、尚未激活的插件包
+struct StagedPackage {
+    uri: String,
+    package: Bytes,
+    load_result: loader::LoadResult,
 }
 
 #[derive(Debug, Clone, Default)]
@@ -78,12 +186,38 @@ pub struct PluginPolicy {
 pub struct PluginManager {
     engine: Engine,
     linker: Linker<StreamContext>,
-    pub plugin_root: String,
+    /// 插件根目录，按优先级从高到低排列
+    roots: Arc<Vec<PluginRoot>>,
+    /// 被更高优先级副本遮蔽的包：plugin_id -> uri 列表
+    shadowed: Arc<RwLock<HashMap<String, Vec<String>>>>,
     registry: VtxVideoRegistry,
     plugins: Arc<RwLock<HashMap<String, Arc<PluginRuntime>>>>,
     routes: Arc<RwLock<Vec<Arc<PluginRuntime>>>>,
+    /// 已停用插件：保留安装锁与数据，但不参与路由、鉴权与事件订阅
+    disabled: Arc<RwLock<HashSet<String>>>,
+    /// 每个插件上一个成功激活的版本，用于回滚
+    previous: Arc<RwLock<HashMap<String, Arc<PluginRuntime>>>>,
+    component_cache: Arc<ComponentCache>,
+    signature_verifier: Arc<SignatureVerifier>,
+    snapshot_retention: usize,
+    pool: Arc<PoolSettings>,
+    deadlines: Arc<DeadlineSettings>,
+    fuel: Arc<FuelMeter>,
+    breakers: Arc<CircuitBreakers>,
+    config: Arc<PluginConfigStore>,
+    scratch: Arc<ScratchSpace>,
+    logs: Arc<PluginLogs>,
+    /// 依赖未满足、暂缓激活的插件
+    pending: Arc<RwLock<HashMap<String, PendingPlugin>>>,
 
-    auth_provider: Option<String>,
+    /// 按顺序调用的鉴权插件链，为空时按能力发现
+    auth_providers: Arc<Vec<AuthProviderSettings>>,
+    auth_traces: Arc<AuthTraces>,
+    auth_cache: Arc<AuthCache>,
+    /// 自身写入插件根目录的包，监听器不重复加载
+    self_writes: Arc<SelfWrites>,
+    /// 管理员配置的路由访问策略覆盖
+    route_policies: Arc<HashMap<String, RoutePolicy>>,
 
     pub vtx_ffmpeg: Arc<VtxFfmpegManager>,
     pub vfs: Arc<VtxVfsManager>,
@@ -94,57 +228,128 @@ pub struct PluginManager {
 
 pub struct PluginManagerConfig {
     pub engine: Engine,
-    pub plugin_root: String,
+    pub plugin_roots: Vec<PluginRootSettings>,
     pub registry: VtxVideoRegistry,
     pub linker: Linker<StreamContext>,
-    pub auth_provider: Option<String>,
+    pub auth_providers: Vec<AuthProviderSettings>,
+    pub auth_cache: AuthCacheSettings,
+    /// 路由访问策略覆盖：plugin_id -> 策略字符串
+    pub route_policies: HashMap<String, String>,
     pub vtx_ffmpeg: Arc<VtxFfmpegManager>,
     pub vfs: Arc<VtxVfsManager>,
     pub max_buffer_read_bytes: u64,
     pub max_memory_bytes: usize,
     pub event_bus: Arc<EventBus>,
+    /// 编译产物缓存目录，`None` 表示关闭缓存
+    pub component_cache_dir: Option<PathBuf>,
+    pub signature: SignatureSettings,
+    /// 每个插件保留的迁移前快照数量，0 表示关闭
+    pub snapshot_retention: usize,
+    pub watch: WatchSettings,
+    pub pool: PoolSettings,
+    pub deadlines: DeadlineSettings,
+    pub fuel: FuelSettings,
+    pub breaker: BreakerSettings,
+    pub scratch: ScratchSettings,
+    /// secret 加密主密钥，`None` 时只能写入明文配置
+    pub secrets_key: Option<String>,
+    /// 每个插件保留的 stdout / stderr 行数
+    pub log_buffer_lines: usize,
 }
 
 impl PluginManager {
     pub async fn new(config: PluginManagerConfig) -> anyhow::Result<Self> {
         let PluginManagerConfig {
             engine,
-            plugin_root,
+            plugin_roots,
             registry,
             linker,
-            auth_provider,
+            auth_providers,
+            auth_cache,
+            route_policies,
             vtx_ffmpeg,
             vfs,
             max_buffer_read_bytes,
             max_memory_bytes,
             event_bus,
+            component_cache_dir,
+            signature,
+            snapshot_retention,
+            watch,
+            pool,
+            deadlines,
+            fuel,
+            breaker,
+            scratch,
+            secrets_key,
+            log_buffer_lines,
         } = config;
 
-        let plugin_root = normalize_plugin_root(&vfs, &plugin_root)?;
-        info!(
-            "[PluginManager] Initializing plugin manager at: {}",
-            plugin_root
-        );
+        if plugin_roots.is_empty() {
+            return Err(anyhow::anyhow!("No plugin roots configured"));
+        }
+        let mut roots = Vec::with_capacity(plugin_roots.len());
+        for settings in &plugin_roots {
+            let uri = normalize_plugin_root(&vfs, &settings.location)?;
+            info!(
+                "[PluginManager] Initializing plugin root: {} (priority {}{})",
+                uri,
+                settings.priority,
+                if settings.read_only {
+                    ", read-only"
+                } else {
+                    ""
+                }
+            );
 
-        if let Ok(url) = Url::parse(&plugin_root) {
-            if url.scheme() == "file" {
-                let path = url
-                    .to_file_path()
-                    .map_err(|_| anyhow::anyhow!("Invalid plugin root URI"))?;
-                if !path.exists() {
-                    std::fs::create_dir_all(&path)?;
+            if let Ok(url) = Url::parse(&uri) {
+                if url.scheme() == "file" {
+                    let path = url
+                        .to_file_path()
+                        .map_err(|_| anyhow::anyhow!("Invalid plugin root URI"))?;
+                    if !path.exists() {
+                        std::fs::create_dir_all(&path)?;
+                    }
                 }
             }
+            roots.push(PluginRoot {
+                uri,
+                priority: settings.priority,
+                read_only: settings.read_only,
+            });
         }
+        roots.sort_by_key(|root| std::cmp::Reverse(root.priority));
+
+        let component_cache = Arc::new(ComponentCache::new(&engine, component_cache_dir)?);
+        let signature_verifier = Arc::new(SignatureVerifier::new(&signature)?);
+        let config_store = PluginConfigStore::new(registry.clone(), secrets_key.as_deref());
 
         let manager = Self {
             engine: engine.clone(),
             linker,
-            plugin_root: plugin_root.clone(),
+            roots: Arc::new(roots),
+            shadowed: Arc::new(RwLock::new(HashMap::new())),
             registry,
             plugins: Arc::new(RwLock::new(HashMap::new())),
             routes: Arc::new(RwLock::new(Vec::new())),
-            auth_provider,
+            disabled: Arc::new(RwLock::new(HashSet::new())),
+            previous: Arc::new(RwLock::new(HashMap::new())),
+            component_cache,
+            signature_verifier,
+            snapshot_retention,
+            pool: Arc::new(pool),
+            deadlines: Arc::new(deadlines),
+            fuel: Arc::new(FuelMeter::new(&fuel)),
+            breakers: Arc::new(CircuitBreakers::new(breaker)),
+            config: Arc::new(config_store),
+            scratch: Arc::new(ScratchSpace::new(&scratch)?),
+            logs: Arc::new(PluginLogs::new(log_buffer_lines)),
+            pending: Arc::new(RwLock::new(HashMap::new())),
+            auth_providers: Arc::new(auth_providers),
+            auth_traces: Arc::new(AuthTraces::new()),
+            auth_cache: Arc::new(AuthCache::new(&auth_cache)),
+            self_writes: Arc::new(SelfWrites::for_settings(&watch)),
+            route_policies: Arc::new(route_policy::parse_overrides(&route_policies)?),
             vtx_ffmpeg,
             vfs,
             max_buffer_read_bytes,
@@ -154,50 +359,88 @@ impl PluginManager {
 
         manager.load_all_plugins().await?;
 
-        if let Some(auth_id) = &manager.auth_provider {
-            let plugins = manager.plugins.read().unwrap();
-            if !plugins.contains_key(auth_id) {
-                error!(
-                    "[Fatal] Configured auth_provider '{}' not found in loaded plugins!",
-                    auth_id
-                );
+        manager.verify_auth_providers()?;
 
-                return Err(anyhow::anyhow!(
-                    "Critical: Configured auth_provider '{}' is missing. Startup aborted.",
-                    auth_id
-                ));
-            }
-            info!("[Auth] Verified auth_provider '{}' is active.", auth_id);
+        for root in manager.roots() {
+            watcher::spawn_watcher(
+                manager.clone(),
+                root.uri.clone(),
+                watch.clone(),
+                tokio::runtime::Handle::current(),
+            );
         }
 
-        watcher::spawn_watcher(manager.clone(), tokio::runtime::Handle::current());
-
         Ok(manager)
     }
 
+    /// 扫描所有插件根目录并按依赖关系顺序加载
+    ///
+    /// 同一插件 ID 出现在多个根目录时，优先级高者生效；优先级相同则保留已登记的安装路径，
+    /// 其余副本记为被遮蔽的候选。
     async fn load_all_plugins(&self) -> anyhow::Result<()> {
-        info!("[PluginManager] Scanning plugins in: {}", self.plugin_root);
-        let mut entries = self.vfs.list_objects(&self.plugin_root).await?;
-        let mut loaded_count = 0;
-        let mut errors = 0usize;
-
-        while let Some(item) = entries.next().await {
-            match item {
-                Ok(obj) => {
-                    if is_vtx_uri(&obj.uri) {
-                        match self.load_one(&obj.uri).await {
-                            Ok(_) => loaded_count += 1,
-                            Err(e) => error!("[PluginManager] Failed to load {}: {}", obj.uri, e),
+        let mut staged: Vec<StagedPackage> = Vec::new();
+        let mut errors = Vec::new();
+
+        for root in self.roots() {
+            info!("[PluginManager] Scanning plugins in: {}", root.uri);
+            let mut entries = match self.vfs.list_objects(&root.uri).await {
+                Ok(entries) => entries,
+                Err(e) => {
+                    error!("[PluginManager] Failed to list {}: {}", root.uri, e);
+                    errors.push(root.uri.clone());
+                    continue;
+                }
+            };
+
+            while let Some(item) = entries.next().await {
+                match item {
+                    Ok(obj) => {
+                        if is_vtx_uri(&obj.uri) {
+                            match self.stage_package(&obj.uri).await {
+                                Ok(Some(package)) => self.stage_unique(&mut staged, package),
+                                Ok(None) => {}
+                                Err(e) => {
+                                    error!("[PluginManager] Failed to load {}: {}", obj.uri, e)
+                                }
+                            }
                         }
                     }
+                    Err(e) => {
+                        errors.push(root.uri.clone());
+                        error!("[PluginManager] Failed to list plugin object: {}", e);
+                    }
                 }
-                Err(e) => {
-                    errors += 1;
-                    error!("[PluginManager] Failed to list plugin object: {}", e);
-                }
             }
         }
 
+        let nodes: Vec<(String, Vec<String>)> = staged
+            .iter()
+            .map(|p| {
+                let deps = p
+                    .load_result
+                    .dependencies
+                    .iter()
+                    .map(|d| d.plugin_id.clone())
+                    .collect();
+                (p.load_result.plugin_id.clone(), deps)
+            })
+            .collect();
+        let order = dependencies::load_order(&nodes);
+        let mut staged: Vec<Option<StagedPackage>> = staged.into_iter().map(Some).collect();
+
+        let mut loaded_count = 0;
+        for index in order {
+            let Some(package) = staged[index].take() else {
+                continue;
+            };
+            let uri = package.uri.clone();
+            match self.commit_package(package).await {
+                Ok(_) => loaded_count += 1,
+                Err(e) => error!("[PluginManager] Failed to load {}: {}", uri, e),
+            }
+        }
+        self.reconcile_dependencies().await;
+
         if loaded_count == 0 {
             warn!("[PluginManager] No .vtx plugins found in directory.");
         } else {
@@ -207,72 +450,274 @@ impl PluginManager {
             );
         }
 
-        if errors > 0 {
+        if !errors.is_empty() {
+            errors.dedup();
             return Err(anyhow::anyhow!(
-                "Failed to list {} plugin objects under {}",
-                errors,
-                self.plugin_root
+                "Failed to list plugin objects under {}",
+                errors.join(", ")
             ));
         }
 
         Ok(())
     }
 
+    /// 将解析后的包加入启动加载列表，同一插件 ID 只保留一个副本
+    fn stage_unique(&self, staged: &mut Vec<StagedPackage>, package: StagedPackage) {
+        let plugin_id = package.load_result.plugin_id.clone();
+        let Some(existing) = staged
+            .iter_mut()
+            .find(|p| p.load_result.plugin_id == plugin_id)
+        else {
+            staged.push(package);
+            return;
+        };
+
+        let registered = self.registry.get_installation(&plugin_id).ok().flatten();
+        let replaces = self.outranks(&package.uri, &existing.uri)
+            || (!self.outranks(&existing.uri, &package.uri)
+                && registered.as_deref() == Some(package.uri.as_str()));
+        if replaces {
+            let shadowed = std::mem::replace(existing, package);
+            self.add_shadowed(&plugin_id, &shadowed.uri);
+        } else {
+            self.add_shadowed(&plugin_id, &package.uri);
+        }
+    }
+
+    /// 加载或热重载单个插件包
+    ///
+    /// 新版本在完成编译、迁移与冒烟验证之前不会替换正在服务的旧版本；每次尝试都会记录结果。
+    /// 完成后会级联处理依赖于该插件的其他插件。
     pub async fn load_one(&self, uri: &str) -> anyhow::Result<()> {
+        let result = self.load_package(uri).await;
+        self.reconcile_dependencies().await;
+        result
+    }
+
+    async fn load_package(&self, uri: &str) -> anyhow::Result<()> {
+        match self.stage_package(uri).await? {
+            Some(package) => self.commit_package(package).await,
+            None => Ok(()),
+        }
+    }
+
+    /// 读取并解析插件包；包内容未变化时返回 `Ok(None)`
+    async fn stage_package(&self, uri: &str) -> anyhow::Result<Option<StagedPackage>> {
         let uri = self.vfs.normalize_uri(uri)?;
-        let load_result = loader::load_and_migrate(
+        let package = match loader::read_package(&self.vfs, &uri).await {
+            Ok(package) => package,
+            Err(e) => {
+                self.record_reload(
+                    self.plugin_id_for_uri(&uri).as_deref(),
+                    &uri,
+                    None,
+                    Some(&e),
+                );
+                return Err(e);
+            }
+        };
+
+        if self.self_writes.take(&uri, &package) {
+            debug!(
+                "[PluginManager] Package was written by the manager itself, skipping reload: {}",
+                uri
+            );
+            return Ok(None);
+        }
+
+        let unchanged = {
+            let plugins = self.plugins.read().unwrap();
+            plugins
+                .values()
+                .any(|p| p.source_uri == uri && p.package == package)
+        };
+        if unchanged {
+            debug!(
+                "[PluginManager] Package unchanged, skipping reload: {}",
+                uri
+            );
+            return Ok(None);
+        }
+
+        let detached_signature = loader::read_detached_signature(&self.vfs, &uri).await;
+        let load_result = match loader::inspect_package(
             &self.engine,
+            &self.component_cache,
+            &self.signature_verifier,
             &self.registry,
             &self.linker,
-            &uri,
+            &self.deadlines,
+            self.max_memory_bytes,
+            loader::PackageSource {
+                bytes: &package,
+                detached_signature: detached_signature.as_deref(),
+                source_hint: &uri,
+            },
             self.vtx_ffmpeg.clone(),
             self.vfs.clone(),
             self.event_bus.clone(),
         )
-        .await?;
+        .await
+        {
+            Ok(load_result) => load_result,
+            Err(e) => {
+                self.record_reload(
+                    self.plugin_id_for_uri(&uri).as_deref(),
+                    &uri,
+                    None,
+                    Some(&e),
+                );
+                return Err(e);
+            }
+        };
+
+        Ok(Some(StagedPackage {
+            uri,
+            package,
+            load_result,
+        }))
+    }
+
+    /// 检查依赖后迁移、验证并激活已解析的插件包
+    ///
+    /// 依赖未满足且没有旧版本在服务时，插件进入等待列表，依赖就绪后自动重试。
+    async fn commit_package(&self, staged: StagedPackage) -> anyhow::Result<()> {
+        let StagedPackage {
+            uri,
+            package,
+            load_result,
+        } = staged;
+        let plugin_id = load_result.plugin_id.clone();
+        let version = load_result.manifest.version.clone();
+
+        let previous_installation = match self.claim_installation(&plugin_id, &uri).await {
+            Ok(previous) => previous,
+            Err(e) => {
+                self.record_reload(Some(&plugin_id), &uri, Some(&version), Some(&e));
+                return Err(e);
+            }
+        };
+
+        if self.registry.is_plugin_enabled(&plugin_id).unwrap_or(true) {
+            if let Err(rejection) = self.check_dependencies(&plugin_id, &load_result.dependencies) {
+                if let PluginRejection::DependencyUnresolved { unresolved, .. } = &rejection {
+                    self.park_pending(
+                        &plugin_id,
+                        PendingPlugin {
+                            uri: uri.clone(),
+                            dependencies: load_result.dependencies.clone(),
+                            status: PluginStatus::pending(&load_result, &uri, unresolved.clone()),
+                        },
+                    );
+                }
+                if let Some(previous) = &previous_installation {
+                    self.restore_installation(&plugin_id, previous);
+                }
+                let e = anyhow::Error::from(rejection);
+                self.record_reload(Some(&plugin_id), &uri, Some(&version), Some(&e));
+                return Err(e);
+            }
+        }
+
+        let outcome = match self
+            .prepare_runtime(load_result, uri.clone(), package.clone(), false)
+            .await
+        {
+            Ok(runtime) => self.activate_runtime(runtime),
+            Err(e) => Err(e),
+        };
+        if let (Err(_), Some(previous)) = (&outcome, &previous_installation) {
+            self.restore_installation(&plugin_id, previous);
+        }
+        self.record_reload(
+            Some(&plugin_id),
+            &uri,
+            Some(&version),
+            outcome.as_ref().err(),
+        );
+        self.record_load(&plugin_id, &version, &package, &outcome, false);
+        outcome
+    }
+
+    fn build_runtime(
+        &self,
+        load_result: &loader::LoadResult,
+        uri: String,
+        package: Bytes,
+    ) -> anyhow::Result<Arc<PluginRuntime>> {
         let instance_pre = self.linker.instantiate_pre(&load_result.component)?;
+        let pooled = load_result.stateless || self.pool.plugins.contains(&load_result.plugin_id);
+        let capacity = if pooled { self.pool.size } else { 0 };
+        let grants = self.sync_grants(&load_result.plugin_id, &load_result.policy)?;
 
-        let runtime = Arc::new(PluginRuntime {
+        Ok(Arc::new(PluginRuntime {
             id: load_result.plugin_id.clone(),
             manifest: load_result.manifest.clone(),
             policy: load_result.policy.clone(),
             vtx_meta: load_result.vtx_meta.clone(),
             instance_pre,
-            component: load_result.component,
+            component: load_result.component.clone(),
             source_uri: uri,
-        });
-
-        self.register_plugin(runtime)
+            package,
+            publisher: load_result.publisher.clone(),
+            dependencies: load_result.dependencies.clone(),
+            instances: InstancePool::new(capacity),
+            grants: RwLock::new(grants),
+            route_policy: load_result.route_policy.clone(),
+        }))
     }
 
     fn register_plugin(&self, runtime: Arc<PluginRuntime>) -> anyhow::Result<()> {
-        let mut plugins_lock = self.plugins.write().unwrap();
-        let mut routes_lock = self.routes.write().unwrap();
-
         let new_entrypoint = &runtime.manifest.entrypoint;
         let new_id = &runtime.id;
+        let enabled = self.registry.is_plugin_enabled(new_id).unwrap_or_else(|e| {
+            error!(
+                "[Register] Failed to read state of '{}', assuming enabled: {}",
+                new_id, e
+            );
+            true
+        });
 
-        for existing in routes_lock.iter() {
-            if existing.manifest.entrypoint == *new_entrypoint && existing.id != *new_id {
-                return Err(anyhow::anyhow!(
-                    "Route conflict: '{}' is already owned by plugin '{}'. Installation of '{}' aborted.",
-                    new_entrypoint,
-                    existing.id,
-                    new_id
-                ));
+        {
+            let mut plugins_lock = self.plugins.write().unwrap();
+            let mut routes_lock = self.routes.write().unwrap();
+
+            if enabled {
+                if let Some(rejection) =
+                    install::find_route_conflict(&routes_lock, new_id, new_entrypoint)
+                {
+                    return Err(rejection.into());
+                }
             }
-        }
 
-        plugins_lock.insert(new_id.clone(), runtime.clone());
+            plugins_lock.insert(new_id.clone(), runtime.clone());
+            routes_lock.retain(|p| p.id != *new_id);
+            // 鉴权插件重载、启用或停用后，缓存的结论不再可信
+            self.auth_cache.invalidate_plugin(new_id);
 
-        routes_lock.retain(|p| p.id != *new_id);
-        routes_lock.push(runtime.clone());
-        routes_lock.sort_by(|a, b| {
-            b.manifest
-                .entrypoint
-                .len()
-                .cmp(&a.manifest.entrypoint.len())
-        });
+            if enabled {
+                routes_lock.push(runtime.clone());
+                routes_lock.sort_by(|a, b| {
+                    b.manifest
+                        .entrypoint
+                        .len()
+                        .cmp(&a.manifest.entrypoint.len())
+                });
+                self.disabled.write().unwrap().remove(new_id);
+            } else {
+                self.disabled.write().unwrap().insert(new_id.clone());
+            }
+        }
+
+        if !enabled {
+            info!("[Register] Plugin '{}' registered (disabled)", new_id);
+            let bus = self.event_bus.clone();
+            let plugin_id = new_id.clone();
+            tokio::spawn(async move {
+                bus.unregister_plugin(&plugin_id).await;
+            });
+            return Ok(());
+        }
 
         info!(
             "[Register] Plugin '{}' registered at route '{}'",
@@ -289,13 +734,26 @@ impl PluginManager {
             let vfs = self.vfs.clone();
             let max_buffer = self.max_buffer_read_bytes;
             let max_memory = self.max_memory_bytes;
+            let deadlines = self.deadlines.clone();
+            let fuel = self.fuel.clone();
+            let breakers = self.breakers.clone();
+            let config = self.config.clone();
+            let scratch = self.scratch.clone();
+            let logs = self.logs.clone();
 
             tokio::spawn(async move {
                 let mut rx = bus
                     .register_plugin(&runtime.id, &topics, &runtime.policy.subscriptions)
                     .await;
                 while let Some(event) = rx.recv().await {
-                    if let Err(e) = VtxPluginExecutor::dispatch_event_with(
+                    // 熔断期间暂停消费，事件留在队列中等待恢复
+                    let permit = loop {
+                        match breakers.admit(&runtime.id) {
+                            Ok(permit) => break permit,
+                            Err(retry_after) => tokio::time::sleep(retry_after).await,
+                        }
+                    };
+                    let dispatched = VtxPluginExecutor::dispatch_event_with(
                         EventDispatchContext {
                             engine: engine.clone(),
                             registry: registry.clone(),
@@ -304,13 +762,33 @@ impl PluginManager {
                             event_bus: bus.clone(),
                             max_memory_bytes: max_memory,
                             max_buffer_read_bytes: max_buffer,
+                            deadlines: deadlines.clone(),
+                            fuel: fuel.clone(),
+                            config: config.clone(),
+                            scratch: scratch.clone(),
+                            logs: logs.clone(),
                         },
                         runtime.clone(),
                         event,
                     )
-                    .await
-                    {
-                        tracing::error!("[EventBus] Dispatch failed for '{}': {}", runtime.id, e);
+                    .await;
+                    permit.record(matches!(
+                        dispatched,
+                        Ok(()) | Err(ExecutionError::Rejected(_))
+                    ));
+                    if let Err(e) = dispatched {
+                        match e {
+                            ExecutionError::DeadlineExceeded { .. } => {
+                                tracing::error!("[EventBus] Handler timed out: {}", e)
+                            }
+                            ExecutionError::Failed(_) | ExecutionError::Rejected(_) => {
+                                tracing::error!(
+                                    "[EventBus] Dispatch failed for '{}': {}",
+                                    runtime.id,
+                                    e
+                                )
+                            }
+                        }
                     }
                 }
             });
@@ -319,6 +797,51 @@ impl PluginManager {
         Ok(())
     }
 
+    /// 切换插件启用状态并持久化
+    ///
+    /// 返回 `Ok(None)` 表示插件未加载。
+    pub fn set_enabled(
+        &self,
+        plugin_id: &str,
+        enabled: bool,
+    ) -> anyhow::Result<Option<PluginStatus>> {
+        let runtime = {
+            let plugins = self.plugins.read().unwrap();
+            plugins.get(plugin_id).cloned()
+        };
+        let Some(runtime) = runtime else {
+            return Ok(None);
+        };
+
+        if !enabled && self.is_auth_provider(plugin_id) {
+            warn!(
+                "[Protection] Disable blocked for auth provider '{}'.",
+                plugin_id
+            );
+            return Err(anyhow::anyhow!(
+                "Operation denied: Cannot disable a configured auth provider."
+            ));
+        }
+
+        self.registry.set_plugin_enabled(plugin_id, enabled)?;
+        if let Err(e) = self.register_plugin(runtime.clone()) {
+            self.registry.set_plugin_enabled(plugin_id, !enabled)?;
+            return Err(e);
+        }
+
+        info!(
+            "[PluginManager] Plugin '{}' {}.",
+            plugin_id,
+            if enabled { "enabled" } else { "disabled" }
+        );
+        self.spawn_reconcile();
+        Ok(Some(PluginStatus::from_runtime(&runtime, enabled)))
+    }
+
+    fn is_disabled(&self, plugin_id: &str) -> bool {
+        self.disabled.read().unwrap().contains(plugin_id)
+    }
+
     pub fn match_route(&self, path: &str) -> Option<(Arc<PluginRuntime>, String)> {
         let routes = self.routes.read().unwrap();
         for plugin in routes.iter() {
@@ -334,13 +857,13 @@ impl PluginManager {
     }
 
     pub fn uninstall_by_uri(&self, uri: &str) {
-        let target_id = {
-            let plugins = self.plugins.read().unwrap();
-            plugins
-                .values()
-                .find(|p| p.source_uri == uri)
-                .map(|p| p.id.clone())
-        };
+        let target_id = self.plugin_id_for_uri(uri).or_else(|| {
+            let pending = self.pending.read().unwrap();
+            pending
+                .iter()
+                .find(|(_, p)| p.uri == uri)
+                .map(|(id, _)| id.clone())
+        });
 
         if let Some(id) = target_id {
             info!(
@@ -349,36 +872,65 @@ impl PluginManager {
             );
             if let Err(e) = self.uninstall(&id, true) {
                 error!("[Watcher] Failed to uninstall plugin '{}': {}", id, e);
+                return;
+            }
+
+            // 由被遮蔽的副本按优先级接替
+            let candidates = self.take_shadowed(&id);
+            if !candidates.is_empty() {
+                let manager = self.clone();
+                tokio::spawn(async move {
+                    for uri in candidates {
+                        match manager.load_one(&uri).await {
+                            Ok(()) => {
+                                info!("[PluginRoots] Plugin '{}' now served from {}", id, uri);
+                                break;
+                            }
+                            Err(e) => {
+                                warn!("[PluginRoots] Fallback {} for '{}' failed: {}", uri, id, e)
+                            }
+                        }
+                    }
+                });
             }
         }
     }
 
     pub fn uninstall(&self, plugin_id: &str, keep_data: bool) -> anyhow::Result<()> {
-        if let Some(auth_id) = &self.auth_provider {
-            if auth_id == plugin_id {
-                warn!(
-                    "[Protection] Uninstall blocked for auth_provider '{}'. \
-                     System requires this plugin to remain active. \
-                     Use file replacement (Atomic Move/Copy) to update it.",
-                    plugin_id
-                );
-                return Err(anyhow::anyhow!(
-                    "Operation denied: Cannot uninstall the active auth_provider."
-                ));
-            }
+        if self.is_auth_provider(plugin_id) {
+            warn!(
+                "[Protection] Uninstall blocked for auth provider '{}'. \
+                 System requires this plugin to remain active. \
+                 Use file replacement (Atomic Move/Copy) to update it.",
+                plugin_id
+            );
+            return Err(anyhow::anyhow!(
+                "Operation denied: Cannot uninstall a configured auth provider."
+            ));
         }
 
+        let removed;
         {
+            let was_pending = self.pending.write().unwrap().remove(plugin_id).is_some();
             let mut plugins_lock = self.plugins.write().unwrap();
-            if plugins_lock.remove(plugin_id).is_none() {
+            removed = plugins_lock.remove(plugin_id);
+            if removed.is_none() && !was_pending {
                 return Err(anyhow::anyhow!("Plugin not found: {}", plugin_id));
             }
             let mut routes_lock = self.routes.write().unwrap();
             routes_lock.retain(|p| p.id != plugin_id);
+            self.disabled.write().unwrap().remove(plugin_id);
+            self.previous.write().unwrap().remove(plugin_id);
         }
+        self.breakers.reset(plugin_id);
+        self.auth_cache.invalidate_plugin(plugin_id);
 
         if !keep_data {
             self.registry.nuke_plugin(plugin_id)?;
+            self.config.invalidate(plugin_id);
+            self.scratch.remove(plugin_id)?;
+            self.logs.clear(plugin_id);
+            self.record_uninstalled(plugin_id, removed.as_deref());
             self.registry.release_installation(plugin_id)?;
         }
 
@@ -390,116 +942,215 @@ impl PluginManager {
         });
 
         info!("[Uninstall] Plugin '{}' uninstalled.", plugin_id_log);
+        self.spawn_reconcile();
         Ok(())
     }
 
-    pub fn list_plugins(&self) -> Vec<PluginStatus> {
-        let plugins = self.plugins.read().unwrap();
-        plugins
-            .values()
-            .map(|p| PluginStatus {
-                id: p.id.clone(),
-                name: p.manifest.name.clone(),
-                version: p.manifest.version.clone(),
-                entrypoint: p.manifest.entrypoint.clone(),
-                source_path: p.source_uri.clone(),
-                vtx_meta: p.vtx_meta.clone(),
-            })
-            .collect()
+    /// 编译缓存命中与编译次数统计
+    pub fn component_cache_stats(&self) -> ComponentCacheStats {
+        self.component_cache.stats()
     }
 
-    pub async fn verify_identity(
-        &self,
-        headers: &axum::http::HeaderMap,
-    ) -> Result<UserContext, u16> {
-        let wit_headers: Vec<(String, String)> = headers
-            .iter()
-            .map(|(k, v)| (k.to_string(), v.to_str().unwrap_or("").to_string()))
-            .collect();
+    /// 清空编译缓存，已加载的插件不受影响
+    pub fn purge_component_cache(&self) -> anyhow::Result<usize> {
+        self.component_cache.purge()
+    }
 
-        if let Some(provider_id) = &self.auth_provider {
-            let runtime = {
-                let plugins = self.plugins.read().unwrap();
-                plugins.get(provider_id).cloned()
-            };
-            if let Some(runtime) = runtime {
-                match self.invoke_authenticate(&runtime, &wit_headers).await {
-                    Ok(user) => return Ok(user),
-                    Err(code) => return Err(code),
-                }
-            } else {
-                error!(
-                    "[Auth] Critical: auth_provider '{}' missing at runtime!",
-                    provider_id
-                );
-                return Err(500);
-            }
-        } else {
-            let plugins: Vec<Arc<PluginRuntime>> =
-                { self.plugins.read().unwrap().values().cloned().collect() };
+    /// 各插件实例池的复用与冷实例化统计
+    pub fn instance_pool_stats(&self) -> Vec<InstancePoolStats> {
+        let plugins = self.plugins.read().unwrap();
+        let mut stats: Vec<InstancePoolStats> =
+            plugins.values().map(|p| p.instances.stats(&p.id)).collect();
+        stats.sort_by(|a, b| a.plugin_id.cmp(&b.plugin_id));
+        stats
+    }
 
-            for plugin_runtime in plugins {
-                match self
-                    .invoke_authenticate(&plugin_runtime, &wit_headers)
-                    .await
-                {
-                    Ok(user) => return Ok(user),
-                    Err(code) => {
-                        if code == 401 {
-                            continue;
-                        }
-                        return Err(code);
-                    }
-                }
-            }
+    /// 熔断检查：插件熔断期间返回建议的重试间隔
+    ///
+    /// 放行时返回的凭据须在调用结束后提交结果，用于熔断判定。
+    pub fn admit_call(&self, plugin_id: &str) -> Result<CallPermit<'_>, Duration> {
+        self.breakers.admit(plugin_id)
+    }
+
+    /// 手动关闭插件熔断；返回 `None` 表示插件未加载
+    pub fn reset_breaker(&self, plugin_id: &str) -> Option<BreakerStatus> {
+        if !self.plugins.read().unwrap().contains_key(plugin_id) {
+            return None;
         }
-        Err(401)
+        self.breakers.reset(plugin_id);
+        info!("[Breaker] '{}' reset by admin", plugin_id);
+        Some(self.breakers.status(plugin_id))
+    }
+
+    /// 燃料计量器（执行器在每次调用前后使用）
+    pub fn fuel_meter(&self) -> Arc<FuelMeter> {
+        self.fuel.clone()
+    }
+
+    /// 按插件与导出函数累计的燃料消耗；未启用计量时返回 `None`
+    pub fn fuel_usage(&self) -> Option<Vec<FuelUsage>> {
+        self.fuel.enabled().then(|| self.fuel.usage())
+    }
+
+    /// 列出已加载与等待依赖的插件
+    pub fn list_plugins(&self) -> Vec<PluginStatus> {
+        let mut statuses: Vec<PluginStatus> = {
+            let plugins = self.plugins.read().unwrap();
+            plugins
+                .values()
+                .map(|p| {
+                    let mut status = PluginStatus::from_runtime(p, !self.is_disabled(&p.id));
+                    status.breaker = Some(self.breakers.status(&p.id));
+                    status.route_policy = Some(self.route_policy(p));
+                    status
+                })
+                .collect()
+        };
+        let pending = self.pending.read().unwrap();
+        statuses.extend(pending.values().map(|p| p.status.clone()));
+        statuses
     }
 
     async fn invoke_authenticate(
         &self,
         runtime: &PluginRuntime,
         headers: &[(String, String)],
+        request_id: &str,
     ) -> Result<UserContext, u16> {
+        let grants = runtime.grants();
+        let scope = InvocationScope {
+            policy: SecurityPolicy::Restricted,
+            current_user: None,
+            permissions: grants.permissions,
+            http_allowlist: grants.http,
+            request_id: Some(request_id.to_string()),
+            request: None,
+        };
+        self.scratch.admit(runtime).await;
+        let mut instance = runtime
+            .instances
+            .checkout(&self.engine, &runtime.instance_pre, scope, |scope| {
+                self.instance_context(runtime, scope)
+            })
+            .await
+            .map_err(|e| {
+                error!("[Auth] Instantiation failed: {}", e);
+                500u16
+            })?;
+
+        let limit_ms = deadline::limit_ms(&self.deadlines, &runtime.id, PluginCall::Authenticate);
+        deadline::arm(&mut instance.store, &self.deadlines, limit_ms);
+        self.fuel.arm(&mut instance.store);
+
+        let result = instance
+            .plugin
+            .call_authenticate(&mut instance.store, headers)
+            .await;
+        self.fuel.record(
+            &instance.store,
+            &runtime.id,
+            PluginCall::Authenticate,
+            result.as_ref().err(),
+        );
+        runtime.instances.checkin(instance, result.is_ok());
+        result.map_err(|e| {
+            if deadline::is_deadline_exceeded(&e) {
+                error!(
+                    "[Auth] Plugin '{}' exceeded its {}ms deadline",
+                    runtime.id, limit_ms
+                );
+                return 504u16;
+            }
+            error!("[Auth] Call failed: {}", e);
+            500u16
+        })?
+    }
+
+    /// 为插件实例构建宿主上下文
+    fn instance_context(&self, runtime: &PluginRuntime, scope: InvocationScope) -> StreamContext {
         let limits = wasmtime::StoreLimitsBuilder::new()
-            .instances(1)
             .memory_size(self.max_memory_bytes)
+            .instances(5)
+            .tables(1000)
             .build();
 
-        let ctx = StreamContext::new_secure(StreamContextConfig {
+        StreamContext::new_secure(StreamContextConfig {
             registry: self.registry.clone(),
             vtx_ffmpeg: self.vtx_ffmpeg.clone(),
             vfs: self.vfs.clone(),
             limiter: limits,
-            policy: SecurityPolicy::Restricted,
+            policy: scope.policy,
             plugin_id: Some(runtime.id.clone()),
             max_buffer_read_bytes: self.max_buffer_read_bytes,
-            current_user: None,
+            current_user: scope.current_user,
             event_bus: self.event_bus.clone(),
-            permissions: runtime.policy.permissions.iter().cloned().collect(),
-            http_allowlist: runtime.policy.http.clone(),
-        });
-        let mut store = wasmtime::Store::new(&self.engine, ctx);
-        store.limiter(|s| &mut s.limiter);
+            permissions: scope.permissions,
+            http_allowlist: scope.http_allowlist,
+            env: self.plugin_env(&runtime.id),
+            scratch_dir: self.scratch_dir(runtime),
+            logs: Some(self.logs.clone()),
+            request_id: scope.request_id,
+            request: scope.request,
+        })
+    }
 
-        let instance = runtime
-            .instance_pre
-            .instantiate_async(&mut store)
-            .await
-            .map_err(|e| {
-                error!("[Auth] Instantiation failed: {}", e);
-                500u16
-            })?;
+    /// 插件被授予 `file:scratch` 时的临时目录
+    pub fn scratch_dir(&self, runtime: &PluginRuntime) -> Option<ScratchMount> {
+        self.scratch
+            .dir_for(&runtime.id, &runtime.grants().permissions)
+    }
 
-        let plugin = VtxPlugin::new(&mut store, &instance).map_err(|_| 500u16)?;
+    /// 临时目录（执行器在调用开始前检查配额）
+    pub fn scratch_space(&self) -> Arc<ScratchSpace> {
+        self.scratch.clone()
+    }
 
-        plugin
-            .call_authenticate(&mut store, headers)
-            .await
-            .map_err(|e| {
-                error!("[Auth] Call failed: {}", e);
-                500u16
-            })?
+    /// 插件 stdout / stderr 缓冲
+    pub fn plugin_logs(&self) -> Arc<PluginLogs> {
+        self.logs.clone()
+    }
+
+    /// 插件最近的输出；返回 `None` 表示插件未加载且没有缓冲的输出
+    pub fn recent_plugin_logs(
+        &self,
+        plugin_id: &str,
+        limit: usize,
+        after: Option<u64>,
+    ) -> Option<Vec<PluginLogLine>> {
+        let lines = self.logs.recent(plugin_id, limit, after);
+        (self.is_known_plugin(plugin_id) || !lines.is_empty()).then_some(lines)
+    }
+
+    /// 后台预热启用了实例池的插件
+    fn spawn_pool_warmup(&self, runtime: Arc<PluginRuntime>) {
+        if !runtime.instances.enabled() {
+            return;
+        }
+        let manager = self.clone();
+        tokio::spawn(async move {
+            let warmed = runtime
+                .instances
+                .warm(&manager.engine, &runtime.instance_pre, || {
+                    manager.instance_context(
+                        &runtime,
+                        InvocationScope {
+                            policy: SecurityPolicy::Restricted,
+                            current_user: None,
+                            permissions: HashSet::new(),
+                            http_allowlist: Vec::new(),
+                            request_id: None,
+                            request: None,
+                        },
+                    )
+                })
+                .await;
+            if let Err(e) = warmed {
+                warn!(
+                    "[InstancePool] Failed to warm instances of '{}': {}",
+                    runtime.id, e
+                );
+            }
+        });
     }
 }
 
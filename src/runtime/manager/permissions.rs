@@ -0,0 +1,171 @@
+use std::collections::HashSet;
+use tracing::{info, warn};
+
+use super::validate::HttpAllowRuleView;
+use super::{PluginManager, PluginPolicy, PluginRuntime};
+use crate::runtime::vtx_host_impl::api::vtx_types::HttpAllowRule;
+use crate::storage::plugin_permissions::{
+    PluginPermissionRecord, STATUS_DENIED, STATUS_GRANTED, STATUS_PENDING,
+};
+
+pub const KIND_PERMISSION: &str = "permission";
+pub const KIND_HTTP: &str = "http";
+
+/// 插件当前生效的授权：声明且已被批准的权限与 HTTP 规则
+#[derive(Debug, Clone, Default)]
+pub struct PluginGrants {
+    pub permissions: HashSet<String>,
+    pub http: Vec<HttpAllowRule>,
+}
+
+/// 管理员对待审批权限的处理；两个列表都为空时作用于全部 pending 条目
+#[derive(Debug, Clone, Default, serde::Deserialize)]
+pub struct PermissionDecision {
+    pub permissions: Option<Vec<String>>,
+    pub http: Option<Vec<String>>,
+}
+
+/// HTTP 规则的持久化名称（规则的完整 JSON 表示），规则任一字段变化都视为新的请求
+pub fn http_rule_name(rule: &HttpAllowRule) -> String {
+    serde_json::to_string(&HttpAllowRuleView::from(rule)).unwrap_or_else(|_| rule.host.clone())
+}
+
+/// 策略中声明的全部权限条目
+pub fn requested(policy: &PluginPolicy) -> Vec<(&'static str, String)> {
+    policy
+        .permissions
+        .iter()
+        .map(|p| (KIND_PERMISSION, p.clone()))
+        .chain(policy.http.iter().map(|r| (KIND_HTTP, http_rule_name(r))))
+        .collect()
+}
+
+/// 按授予记录筛选声明的权限；未声明的历史授予不会生效
+pub fn resolve(policy: &PluginPolicy, records: &[PluginPermissionRecord]) -> PluginGrants {
+    let granted = |kind: &str, name: &str| {
+        records
+            .iter()
+            .any(|r| r.kind == kind && r.name == name && r.status == STATUS_GRANTED)
+    };
+    PluginGrants {
+        permissions: policy
+            .permissions
+            .iter()
+            .filter(|p| granted(KIND_PERMISSION, p))
+            .cloned()
+            .collect(),
+        http: policy
+            .http
+            .iter()
+            .filter(|r| granted(KIND_HTTP, &http_rule_name(r)))
+            .cloned()
+            .collect(),
+    }
+}
+
+impl PluginRuntime {
+    /// 当前生效授权的副本
+    pub fn grants(&self) -> PluginGrants {
+        self.grants.read().unwrap().clone()
+    }
+}
+
+impl PluginManager {
+    /// 登记运行时声明的权限并刷新其生效授权
+    ///
+    /// 新增的声明一律进入 pending 状态，不会因插件更新而自动获得；
+    /// 启用权限审批前已安装的插件首次登记时保留原有权限。
+    pub(super) fn sync_grants(
+        &self,
+        plugin_id: &str,
+        policy: &PluginPolicy,
+    ) -> anyhow::Result<PluginGrants> {
+        let records = self
+            .registry
+            .sync_plugin_permissions(plugin_id, &requested(policy))?;
+        let pending = requested(policy)
+            .iter()
+            .filter(|(kind, name)| {
+                records
+                    .iter()
+                    .any(|r| r.kind == *kind && r.name == *name && r.status == STATUS_PENDING)
+            })
+            .count();
+        if pending > 0 {
+            warn!(
+                "[Permissions] Plugin '{}' has {} permission request(s) awaiting approval",
+                plugin_id, pending
+            );
+        }
+        Ok(resolve(policy, &records))
+    }
+
+    pub(super) fn refresh_grants(&self, runtime: &PluginRuntime) -> anyhow::Result<()> {
+        let grants = self.sync_grants(&runtime.id, &runtime.policy)?;
+        *runtime.grants.write().unwrap() = grants;
+        Ok(())
+    }
+
+    /// 插件的权限记录；返回 `Ok(None)` 表示插件未加载且没有记录
+    pub fn plugin_permissions(
+        &self,
+        plugin_id: &str,
+    ) -> anyhow::Result<Option<Vec<PluginPermissionRecord>>> {
+        let records = self.registry.list_plugin_permissions(plugin_id)?;
+        let loaded = self.plugins.read().unwrap().contains_key(plugin_id);
+        Ok((loaded || !records.is_empty()).then_some(records))
+    }
+
+    /// 批准或拒绝插件的权限请求，立即作用于正在服务的版本
+    ///
+    /// 拒绝同样可用于撤销已授予的权限。返回 `Ok(None)` 表示插件未加载且没有记录。
+    pub fn decide_permissions(
+        &self,
+        plugin_id: &str,
+        decision: PermissionDecision,
+        approve: bool,
+    ) -> anyhow::Result<Option<Vec<PluginPermissionRecord>>> {
+        if self.plugin_permissions(plugin_id)?.is_none() {
+            return Ok(None);
+        }
+        let status = if approve {
+            STATUS_GRANTED
+        } else {
+            STATUS_DENIED
+        };
+        let all_pending = decision.permissions.is_none() && decision.http.is_none();
+        let mut updated = 0;
+        for (kind, names) in [
+            (KIND_PERMISSION, decision.permissions),
+            (KIND_HTTP, decision.http),
+        ] {
+            if all_pending || names.is_some() {
+                updated += self.registry.decide_plugin_permissions(
+                    plugin_id,
+                    kind,
+                    names.as_deref(),
+                    status,
+                )?;
+            }
+        }
+        info!(
+            "[Permissions] {} {} permission(s) of '{}'",
+            status, updated, plugin_id
+        );
+
+        let runtimes: Vec<std::sync::Arc<PluginRuntime>> = [
+            self.plugins.read().unwrap().get(plugin_id).cloned(),
+            self.previous.read().unwrap().get(plugin_id).cloned(),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        let records = self.registry.list_plugin_permissions(plugin_id)?;
+        for runtime in runtimes {
+            *runtime.grants.write().unwrap() = resolve(&runtime.policy, &records);
+        }
+        // 临时目录以 preopen 形式固定在实例中，需要重建池化实例
+        self.refresh_instances(plugin_id);
+        Ok(Some(records))
+    }
+}
@@ -0,0 +1,314 @@
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use base64::Engine as _;
+use hkdf::Hkdf;
+use serde::Serialize;
+use sha2::Sha256;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use tracing::{info, warn};
+
+use super::PluginManager;
+use crate::storage::VtxVideoRegistry;
+
+/// 提供 secret 加密主密钥的环境变量
+pub const SECRETS_KEY_ENV: &str = "VTX_SECRETS_KEY";
+
+/// 密文格式版本前缀
+const CIPHERTEXT_PREFIX: &str = "v1:";
+const NONCE_LEN: usize = 12;
+/// HKDF 派生信息，与密文版本一同变更
+const KEY_INFO: &[u8] = b"vtx-plugin-config:v1";
+
+/// 注入 WASI 的环境变量列表
+pub type PluginEnv = Arc<Vec<(String, String)>>;
+
+/// 插件配置写入被拒绝的原因
+#[derive(Debug, Clone)]
+pub enum ConfigRejection {
+    InvalidKey { key: String },
+    SecretsUnavailable,
+}
+
+impl std::fmt::Display for ConfigRejection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidKey { key } => write!(
+                f,
+                "Invalid config key '{}': expected [A-Za-z_][A-Za-z0-9_]* without the reserved VTX_ prefix",
+                key
+            ),
+            Self::SecretsUnavailable => write!(
+                f,
+                "Secret values require the {} environment variable",
+                SECRETS_KEY_ENV
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ConfigRejection {}
+
+/// 对外展示的配置项，secret 条目不返回值
+#[derive(Debug, Clone, Serialize)]
+pub struct PluginConfigEntry {
+    pub key: String,
+    pub value: Option<String>,
+    pub secret: bool,
+    pub updated_at: String,
+}
+
+/// 使用主密钥派生的 AES-256-GCM 密钥加解密 secret
+///
+/// 密钥由 HKDF-SHA256 以固定的 `vtx-plugin-config:v1` 信息派生，与主密钥的其他用途隔离。
+/// 插件 ID 与配置键作为附加认证数据，密文不能被挪用到其他条目。
+pub struct SecretCipher {
+    cipher: Aes256Gcm,
+}
+
+impl SecretCipher {
+    pub fn new(master_key: &str) -> Self {
+        let mut key = Key::<Aes256Gcm>::default();
+        Hkdf::<Sha256>::new(None, master_key.as_bytes())
+            .expand(KEY_INFO, &mut key)
+            .expect("32 bytes is a valid HKDF-SHA256 output length");
+        Self {
+            cipher: Aes256Gcm::new(&key),
+        }
+    }
+
+    pub fn encrypt(&self, plugin_id: &str, key: &str, plaintext: &str) -> anyhow::Result<String> {
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let aad = associated_data(plugin_id, key);
+        let ciphertext = self
+            .cipher
+            .encrypt(
+                &nonce,
+                Payload {
+                    msg: plaintext.as_bytes(),
+                    aad: &aad,
+                },
+            )
+            .map_err(|_| anyhow::anyhow!("Secret encryption failed"))?;
+        let mut sealed = nonce.to_vec();
+        sealed.extend_from_slice(&ciphertext);
+        Ok(format!(
+            "{}{}",
+            CIPHERTEXT_PREFIX,
+            base64::engine::general_purpose::STANDARD.encode(sealed)
+        ))
+    }
+
+    pub fn decrypt(&self, plugin_id: &str, key: &str, sealed: &str) -> anyhow::Result<String> {
+        let encoded = sealed
+            .strip_prefix(CIPHERTEXT_PREFIX)
+            .ok_or_else(|| anyhow::anyhow!("Unknown secret format"))?;
+        let sealed = base64::engine::general_purpose::STANDARD.decode(encoded)?;
+        if sealed.len() < NONCE_LEN {
+            return Err(anyhow::anyhow!("Secret ciphertext truncated"));
+        }
+        let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+        let aad = associated_data(plugin_id, key);
+        let plaintext = self
+            .cipher
+            .decrypt(
+                Nonce::from_slice(nonce),
+                Payload {
+                    msg: ciphertext,
+                    aad: &aad,
+                },
+            )
+            .map_err(|_| anyhow::anyhow!("Secret decryption failed (wrong master key?)"))?;
+        Ok(String::from_utf8(plaintext)?)
+    }
+}
+
+fn associated_data(plugin_id: &str, key: &str) -> Vec<u8> {
+    format!("{}\0{}", plugin_id, key).into_bytes()
+}
+
+/// 配置键需可作为环境变量名，`VTX_` 前缀保留给宿主
+pub fn is_valid_key(key: &str) -> bool {
+    let mut chars = key.chars();
+    let Some(first) = chars.next() else {
+        return false;
+    };
+    key.len() <= 128
+        && (first.is_ascii_alphabetic() || first == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+        && !key.to_ascii_uppercase().starts_with("VTX_")
+}
+
+/// 插件配置与 secret 存储
+///
+/// 解密后的环境变量按插件缓存，写入或删除时失效。
+pub struct PluginConfigStore {
+    registry: VtxVideoRegistry,
+    cipher: Option<SecretCipher>,
+    cache: RwLock<HashMap<String, PluginEnv>>,
+}
+
+impl PluginConfigStore {
+    pub fn new(registry: VtxVideoRegistry, master_key: Option<&str>) -> Self {
+        Self {
+            registry,
+            cipher: master_key
+                .filter(|key| !key.is_empty())
+                .map(SecretCipher::new),
+            cache: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// 注入插件 WASI 环境的配置项；无法解密的 secret 会被跳过
+    pub fn env(&self, plugin_id: &str) -> PluginEnv {
+        if let Some(env) = self.cache.read().unwrap().get(plugin_id) {
+            return env.clone();
+        }
+
+        let rows = match self.registry.list_plugin_config(plugin_id) {
+            Ok(rows) => rows,
+            Err(e) => {
+                warn!(
+                    "[PluginConfig] Failed to read config of '{}': {}",
+                    plugin_id, e
+                );
+                return Arc::new(Vec::new());
+            }
+        };
+        let mut env = Vec::with_capacity(rows.len());
+        for row in rows {
+            if !row.secret {
+                env.push((row.key, row.value));
+                continue;
+            }
+            let decrypted = match &self.cipher {
+                Some(cipher) => cipher.decrypt(plugin_id, &row.key, &row.value),
+                None => Err(anyhow::anyhow!("{} is not set", SECRETS_KEY_ENV)),
+            };
+            match decrypted {
+                Ok(value) => env.push((row.key, value)),
+                Err(e) => warn!(
+                    "[PluginConfig] Skipping secret '{}' of '{}': {}",
+                    row.key, plugin_id, e
+                ),
+            }
+        }
+
+        let env = Arc::new(env);
+        self.cache
+            .write()
+            .unwrap()
+            .insert(plugin_id.to_string(), env.clone());
+        env
+    }
+
+    pub fn list(&self, plugin_id: &str) -> anyhow::Result<Vec<PluginConfigEntry>> {
+        let rows = self.registry.list_plugin_config(plugin_id)?;
+        Ok(rows
+            .into_iter()
+            .map(|row| PluginConfigEntry {
+                value: (!row.secret).then_some(row.value),
+                key: row.key,
+                secret: row.secret,
+                updated_at: row.updated_at,
+            })
+            .collect())
+    }
+
+    pub fn set(&self, plugin_id: &str, key: &str, value: &str, secret: bool) -> anyhow::Result<()> {
+        if !is_valid_key(key) {
+            return Err(ConfigRejection::InvalidKey {
+                key: key.to_string(),
+            }
+            .into());
+        }
+        let stored = if secret {
+            let cipher = self
+                .cipher
+                .as_ref()
+                .ok_or(ConfigRejection::SecretsUnavailable)?;
+            cipher.encrypt(plugin_id, key, value)?
+        } else {
+            value.to_string()
+        };
+        self.registry
+            .set_plugin_config(plugin_id, key, &stored, secret)?;
+        self.invalidate(plugin_id);
+        Ok(())
+    }
+
+    /// 删除配置项，返回是否存在
+    pub fn remove(&self, plugin_id: &str, key: &str) -> anyhow::Result<bool> {
+        let removed = self.registry.delete_plugin_config(plugin_id, key)?;
+        self.invalidate(plugin_id);
+        Ok(removed)
+    }
+
+    pub fn invalidate(&self, plugin_id: &str) {
+        self.cache.write().unwrap().remove(plugin_id);
+    }
+}
+
+impl PluginManager {
+    /// 注入插件 WASI 环境的配置项
+    pub fn plugin_env(&self, plugin_id: &str) -> Vec<(String, String)> {
+        self.config.env(plugin_id).as_ref().clone()
+    }
+
+    /// 配置存储（事件分发在每次冷实例化时读取）
+    pub fn config_store(&self) -> Arc<PluginConfigStore> {
+        self.config.clone()
+    }
+
+    pub(super) fn is_known_plugin(&self, plugin_id: &str) -> bool {
+        self.plugins.read().unwrap().contains_key(plugin_id)
+            || self.pending.read().unwrap().contains_key(plugin_id)
+    }
+
+    /// 列出插件配置；返回 `Ok(None)` 表示插件未加载
+    pub fn plugin_config(&self, plugin_id: &str) -> anyhow::Result<Option<Vec<PluginConfigEntry>>> {
+        if !self.is_known_plugin(plugin_id) {
+            return Ok(None);
+        }
+        self.config.list(plugin_id).map(Some)
+    }
+
+    /// 写入配置项；返回 `Ok(None)` 表示插件未加载
+    pub fn set_plugin_config(
+        &self,
+        plugin_id: &str,
+        key: &str,
+        value: &str,
+        secret: bool,
+    ) -> anyhow::Result<Option<()>> {
+        if !self.is_known_plugin(plugin_id) {
+            return Ok(None);
+        }
+        self.config.set(plugin_id, key, value, secret)?;
+        info!("[PluginConfig] '{}' updated key '{}'", plugin_id, key);
+        self.refresh_instances(plugin_id);
+        Ok(Some(()))
+    }
+
+    /// 删除配置项；返回 `Ok(None)` 表示插件未加载，`Ok(Some(false))` 表示配置项不存在
+    pub fn remove_plugin_config(&self, plugin_id: &str, key: &str) -> anyhow::Result<Option<bool>> {
+        if !self.is_known_plugin(plugin_id) {
+            return Ok(None);
+        }
+        let removed = self.config.remove(plugin_id, key)?;
+        if removed {
+            info!("[PluginConfig] '{}' removed key '{}'", plugin_id, key);
+            self.refresh_instances(plugin_id);
+        }
+        Ok(Some(removed))
+    }
+
+    /// 丢弃以旧环境或旧授权创建的池化实例，下一次调用即使用新的 WASI 上下文
+    pub(super) fn refresh_instances(&self, plugin_id: &str) {
+        let runtime = self.plugins.read().unwrap().get(plugin_id).cloned();
+        if let Some(runtime) = runtime {
+            runtime.instances.clear();
+            self.spawn_pool_warmup(runtime);
+        }
+    }
+}
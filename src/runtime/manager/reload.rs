@@ -0,0 +1,249 @@
+use bytes::Bytes;
+use std::sync::Arc;
+use tracing::{info, warn};
+
+use super::{loader, PluginManager, PluginRuntime, PluginStatus};
+use crate::runtime::context::{SecurityPolicy, StreamContext, StreamContextConfig};
+use crate::runtime::deadline::{self, PluginCall};
+use crate::runtime::fuel;
+use crate::runtime::vtx_host_impl::VtxPlugin;
+use crate::storage::plugin_history::{OUTCOME_ACTIVATED, OUTCOME_REJECTED, OUTCOME_ROLLED_BACK};
+use crate::storage::plugin_reloads::PluginReloadRecord;
+use crate::storage::plugin_snapshots::PluginSnapshot;
+
+impl PluginManager {
+    /// 检查降级、编译并冒烟验证新版本，全部通过后再执行迁移
+    ///
+    /// 迁移语句来自包元数据，不依赖实例，因此放在冒烟验证之后：验证失败时数据库保持原样，
+    /// 旧版本可以继续服务。在返回成功之前，旧的 `PluginRuntime` 仍保留在路由表中继续服务。
+    pub(super) async fn prepare_runtime(
+        &self,
+        load_result: loader::LoadResult,
+        uri: String,
+        package: Bytes,
+        force: bool,
+    ) -> anyhow::Result<Arc<PluginRuntime>> {
+        self.guard_downgrade(&load_result, &package, force)?;
+        let runtime = self.build_runtime(&load_result, uri.clone(), package)?;
+        self.smoke_test(&runtime).await?;
+        loader::apply_migrations(&self.registry, &load_result, &uri, self.snapshot_retention)?;
+        Ok(runtime)
+    }
+
+    /// 原子切换到新实例，并保留旧实例用于回滚
+    ///
+    /// 已经持有旧实例引用的请求会在旧实例上执行完毕后自然释放。
+    pub(super) fn activate_runtime(&self, runtime: Arc<PluginRuntime>) -> anyhow::Result<()> {
+        let previous = {
+            let plugins = self.plugins.read().unwrap();
+            plugins.get(&runtime.id).cloned()
+        };
+        self.register_plugin(runtime.clone())?;
+        self.pending.write().unwrap().remove(&runtime.id);
+        if let Some(uris) = self.shadowed.write().unwrap().get_mut(&runtime.id) {
+            uris.retain(|uri| uri != &runtime.source_uri);
+        }
+
+        self.spawn_pool_warmup(runtime.clone());
+        // 新版本不继承旧版本的失败记录
+        self.breakers.reset(&runtime.id);
+
+        if let Some(previous) = previous {
+            previous.instances.clear();
+            if previous.package != runtime.package {
+                self.previous
+                    .write()
+                    .unwrap()
+                    .insert(runtime.id.clone(), previous);
+            }
+        }
+        Ok(())
+    }
+
+    async fn smoke_test(&self, runtime: &PluginRuntime) -> anyhow::Result<()> {
+        let limits = wasmtime::StoreLimitsBuilder::new()
+            .instances(1)
+            .memory_size(self.max_memory_bytes)
+            .build();
+        let ctx = StreamContext::new_secure(StreamContextConfig {
+            registry: self.registry.clone(),
+            vtx_ffmpeg: self.vtx_ffmpeg.clone(),
+            vfs: self.vfs.clone(),
+            limiter: limits,
+            policy: SecurityPolicy::Restricted,
+            plugin_id: Some(runtime.id.clone()),
+            max_buffer_read_bytes: self.max_buffer_read_bytes,
+            current_user: None,
+            event_bus: self.event_bus.clone(),
+            permissions: std::collections::HashSet::new(),
+            http_allowlist: Vec::new(),
+            env: Vec::new(),
+            scratch_dir: None,
+            logs: None,
+            request_id: None,
+            request: None,
+        });
+        let mut store = wasmtime::Store::new(&self.engine, ctx);
+        store.limiter(|s| &mut s.limiter);
+        let limit_ms = deadline::limit_ms(&self.deadlines, &runtime.id, PluginCall::Inspect);
+        deadline::arm(&mut store, &self.deadlines, limit_ms);
+        fuel::prime(&mut store);
+
+        let instance = runtime
+            .instance_pre
+            .instantiate_async(&mut store)
+            .await
+            .map_err(|e| loader::inspect_error(e, &runtime.source_uri, limit_ms))
+            .map_err(|e| anyhow::anyhow!("Smoke test instantiation failed: {}", e))?;
+        let plugin = VtxPlugin::new(&mut store, &instance)?;
+        let manifest = plugin
+            .call_get_manifest(&mut store)
+            .await
+            .map_err(|e| loader::inspect_error(e, &runtime.source_uri, limit_ms))
+            .map_err(|e| anyhow::anyhow!("Smoke test get_manifest failed: {}", e))?;
+
+        if manifest.id != runtime.manifest.id || manifest.entrypoint != runtime.manifest.entrypoint
+        {
+            return Err(anyhow::anyhow!(
+                "Smoke test manifest mismatch for '{}': got id '{}' at '{}'",
+                runtime.id,
+                manifest.id,
+                manifest.entrypoint
+            ));
+        }
+        Ok(())
+    }
+
+    pub(super) fn record_reload(
+        &self,
+        plugin_id: Option<&str>,
+        uri: &str,
+        version: Option<&str>,
+        failure: Option<&anyhow::Error>,
+    ) {
+        let (label, error) = match failure {
+            None => (OUTCOME_ACTIVATED, None),
+            Some(e) => {
+                warn!(
+                    "[HotReload] Refused package {} (plugin {:?}), previous version stays active: {}",
+                    uri, plugin_id, e
+                );
+                (OUTCOME_REJECTED, Some(e.to_string()))
+            }
+        };
+        if let Err(e) =
+            self.registry
+                .record_plugin_reload(plugin_id, uri, version, label, error.as_deref())
+        {
+            warn!("[HotReload] Failed to record reload attempt: {}", e);
+        }
+    }
+
+    pub(super) fn plugin_id_for_uri(&self, uri: &str) -> Option<String> {
+        let plugins = self.plugins.read().unwrap();
+        plugins
+            .values()
+            .find(|p| p.source_uri == uri)
+            .map(|p| p.id.clone())
+    }
+
+    /// 回滚到上一个成功激活的包
+    ///
+    /// 旧包内容会写回源路径，以便重启与热重载后保持一致。新版本已应用的迁移不会撤销，
+    /// 重载时按内容哈希识别为已安装的包（见 `versions::check_reload`）。返回 `Ok(None)` 表示没有可回滚的版本。
+    pub async fn rollback(&self, plugin_id: &str) -> anyhow::Result<Option<PluginStatus>> {
+        let previous = self.previous.write().unwrap().remove(plugin_id);
+        let Some(previous) = previous else {
+            return Ok(None);
+        };
+        let current = {
+            let plugins = self.plugins.read().unwrap();
+            plugins.get(plugin_id).cloned()
+        };
+
+        let restored = async {
+            self.write_package(&previous.source_uri, previous.package.clone())
+                .await?;
+            self.refresh_grants(&previous)?;
+            self.register_plugin(previous.clone())
+        }
+        .await;
+        if let Err(e) = restored {
+            self.previous
+                .write()
+                .unwrap()
+                .insert(plugin_id.to_string(), previous);
+            return Err(e);
+        }
+
+        if let Some(current) = current {
+            self.previous
+                .write()
+                .unwrap()
+                .insert(plugin_id.to_string(), current);
+        }
+
+        if let Err(e) = self.registry.record_plugin_reload(
+            Some(plugin_id),
+            &previous.source_uri,
+            Some(&previous.manifest.version),
+            OUTCOME_ROLLED_BACK,
+            None,
+        ) {
+            warn!("[HotReload] Failed to record rollback: {}", e);
+        }
+        self.record_version(
+            plugin_id,
+            &previous.manifest.version,
+            &previous.package,
+            Ok(OUTCOME_ROLLED_BACK),
+            false,
+        );
+        info!(
+            "[HotReload] Plugin '{}' rolled back to v{}",
+            plugin_id, previous.manifest.version
+        );
+        self.reconcile_dependencies().await;
+
+        Ok(Some(PluginStatus::from_runtime(
+            &previous,
+            !self.is_disabled(plugin_id),
+        )))
+    }
+
+    pub fn list_reloads(
+        &self,
+        plugin_id: &str,
+        limit: i64,
+    ) -> anyhow::Result<Vec<PluginReloadRecord>> {
+        self.registry.list_plugin_reloads(plugin_id, limit)
+    }
+
+    pub fn list_snapshots(&self, plugin_id: &str) -> anyhow::Result<Vec<PluginSnapshot>> {
+        self.registry.list_plugin_snapshots(plugin_id)
+    }
+
+    /// 将插件的数据表与迁移版本恢复到指定快照
+    ///
+    /// 仅恢复数据库状态；若当前包的迁移多于快照版本，下次加载时会重新执行这些迁移，
+    /// 通常应先回滚包再恢复快照。返回 `Ok(None)` 表示快照不存在。
+    pub fn restore_snapshot(
+        &self,
+        plugin_id: &str,
+        snapshot_id: i64,
+    ) -> anyhow::Result<Option<i64>> {
+        let restored = self
+            .registry
+            .restore_plugin_snapshot(plugin_id, snapshot_id)?;
+        if let Some(version) = restored {
+            let loaded = self.plugins.read().unwrap().contains_key(plugin_id);
+            if loaded {
+                info!(
+                    "[Snapshot] Plugin '{}' keeps running against schema version {}",
+                    plugin_id, version
+                );
+            }
+        }
+        Ok(restored)
+    }
+}
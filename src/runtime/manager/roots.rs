@@ -0,0 +1,148 @@
+use serde::Serialize;
+use tracing::{info, warn};
+
+use super::{PluginManager, PluginRejection};
+
+/// 已规范化的插件根目录
+#[derive(Debug, Clone, Serialize)]
+pub struct PluginRoot {
+    pub uri: String,
+    pub priority: i32,
+    pub read_only: bool,
+}
+
+impl PluginManager {
+    /// 已配置的插件根目录（按优先级从高到低）
+    pub fn roots(&self) -> &[PluginRoot] {
+        &self.roots
+    }
+
+    /// 包含该 URI 的根目录（最长前缀匹配）
+    pub(super) fn root_for_uri(&self, uri: &str) -> Option<&PluginRoot> {
+        self.roots
+            .iter()
+            .filter(|root| uri.starts_with(&root.uri))
+            .max_by_key(|root| root.uri.len())
+    }
+
+    /// 上传安装的目标根目录：优先级最高的可写根目录
+    pub(super) fn install_root(&self) -> Result<&PluginRoot, PluginRejection> {
+        self.roots
+            .iter()
+            .find(|root| !root.read_only)
+            .ok_or(PluginRejection::NoWritableRoot)
+    }
+
+    /// `attempted` 所在根目录的优先级是否高于已登记的 `registered`
+    ///
+    /// 已登记路径不属于任何已配置的根目录（例如根目录已从配置中移除）时视为可接管。
+    pub(super) fn outranks(&self, attempted: &str, registered: &str) -> bool {
+        match (self.root_for_uri(attempted), self.root_for_uri(registered)) {
+            (Some(new), Some(old)) => new.priority > old.priority,
+            (Some(_), None) => true,
+            (None, _) => false,
+        }
+    }
+
+    /// 同一插件 ID 出现在多个位置时决定安装锁的归属
+    ///
+    /// 新路径所在根目录优先级更高，或已登记的包已不存在时，释放原路径的安装锁并返回原路径，
+    /// 新路径在迁移时登记；新版本未能激活时调用方需通过 [`Self::restore_installation`] 交还。
+    /// 否则返回 `PluginRejection::InstallationLocked`，新路径记为被遮蔽的候选。
+    pub(super) async fn claim_installation(
+        &self,
+        plugin_id: &str,
+        uri: &str,
+    ) -> anyhow::Result<Option<String>> {
+        let Some(registered) = self.registry.get_installation(plugin_id)? else {
+            return Ok(None);
+        };
+        if registered == uri {
+            return Ok(None);
+        }
+
+        let registered_present = self.vfs.head(&registered).await.is_ok();
+        if registered_present && !self.outranks(uri, &registered) {
+            self.add_shadowed(plugin_id, uri);
+            return Err(PluginRejection::InstallationLocked {
+                plugin_id: plugin_id.to_string(),
+                registered_uri: registered,
+                attempted_uri: uri.to_string(),
+            }
+            .into());
+        }
+
+        warn!(
+            "[PluginRoots] Moving plugin '{}' from {} to {} ({})",
+            plugin_id,
+            registered,
+            uri,
+            if registered_present {
+                "higher-priority root"
+            } else {
+                "previous package removed"
+            }
+        );
+        self.registry.release_installation(plugin_id)?;
+        if registered_present {
+            self.add_shadowed(plugin_id, &registered);
+        }
+        Ok(Some(registered))
+    }
+
+    /// 新路径的包未能激活时，把安装锁交还给 `claim_installation` 释放的原路径
+    ///
+    /// 旧版本仍在服务，锁不交还的话其他根目录或路径可以抢占该插件 ID。
+    pub(super) fn restore_installation(&self, plugin_id: &str, previous: &str) {
+        let restored = self
+            .registry
+            .release_installation(plugin_id)
+            .and_then(|_| self.registry.verify_installation(plugin_id, previous));
+        match restored {
+            Ok(true) => {
+                if let Some(uris) = self.shadowed.write().unwrap().get_mut(plugin_id) {
+                    uris.retain(|uri| uri != previous);
+                }
+                info!(
+                    "[PluginRoots] Restored installation of '{}' to {}",
+                    plugin_id, previous
+                );
+            }
+            Ok(false) => warn!(
+                "[PluginRoots] Installation of '{}' was claimed by another path; not restoring {}",
+                plugin_id, previous
+            ),
+            Err(e) => warn!(
+                "[PluginRoots] Failed to restore installation of '{}' to {}: {}",
+                plugin_id, previous, e
+            ),
+        }
+    }
+
+    /// 记录被更高优先级根目录遮蔽的包，拥有者移除后按优先级接替
+    pub(super) fn add_shadowed(&self, plugin_id: &str, uri: &str) {
+        info!(
+            "[PluginRoots] {} is shadowed by another copy of plugin '{}'",
+            uri, plugin_id
+        );
+        let mut shadowed = self.shadowed.write().unwrap();
+        let uris = shadowed.entry(plugin_id.to_string()).or_default();
+        if !uris.iter().any(|u| u == uri) {
+            uris.push(uri.to_string());
+        }
+    }
+
+    /// 取出插件被遮蔽的候选包（优先级从高到低）
+    pub(super) fn take_shadowed(&self, plugin_id: &str) -> Vec<String> {
+        let mut uris = self
+            .shadowed
+            .write()
+            .unwrap()
+            .remove(plugin_id)
+            .unwrap_or_default();
+        uris.sort_by_key(|uri| {
+            std::cmp::Reverse(self.root_for_uri(uri).map_or(i32::MIN, |r| r.priority))
+        });
+        uris
+    }
+}
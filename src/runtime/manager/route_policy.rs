@@ -0,0 +1,99 @@
+use serde::{Serialize, Serializer};
+use std::collections::HashMap;
+
+use super::{PluginManager, PluginRuntime};
+
+/// 插件路由的访问策略
+///
+/// 由包元数据 `"route_auth"` 声明，管理员可在 `plugins.route_policies` 中覆盖；均未设置时为 `public`。
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum RoutePolicy {
+    /// 不鉴权，插件拿不到当前用户
+    #[default]
+    Public,
+    /// 尝试鉴权，未通过（401）时以匿名身份继续
+    OptionalAuth,
+    RequiredAuth,
+    /// 需要鉴权且用户属于指定组
+    RequiredGroup(String),
+}
+
+impl std::str::FromStr for RoutePolicy {
+    type Err = String;
+
+    fn from_str(raw: &str) -> Result<Self, Self::Err> {
+        match raw.trim() {
+            "public" => Ok(Self::Public),
+            "optional-auth" => Ok(Self::OptionalAuth),
+            "required-auth" => Ok(Self::RequiredAuth),
+            other => match other.strip_prefix("required-group:") {
+                Some(group) if !group.trim().is_empty() => {
+                    Ok(Self::RequiredGroup(group.trim().to_string()))
+                }
+                _ => Err(format!(
+                    "invalid route policy '{}': expected public, optional-auth, required-auth or required-group:<group>",
+                    raw
+                )),
+            },
+        }
+    }
+}
+
+impl std::fmt::Display for RoutePolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Public => f.write_str("public"),
+            Self::OptionalAuth => f.write_str("optional-auth"),
+            Self::RequiredAuth => f.write_str("required-auth"),
+            Self::RequiredGroup(group) => write!(f, "required-group:{}", group),
+        }
+    }
+}
+
+impl Serialize for RoutePolicy {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// 从包元数据中解析路由策略声明（`"route_auth"`）
+///
+/// 元数据为空、不是 JSON 对象或未声明该字段时返回 `Ok(None)`（即 `public`，与旧版包一致）；
+/// 只有声明了 `route_auth` 但取值无效时才返回错误，避免鉴权声明因格式问题退化为 `public`。
+pub fn parse_route_policy(metadata: Option<&[u8]>) -> Result<Option<RoutePolicy>, String> {
+    let Some(serde_json::Value::Object(fields)) =
+        metadata.and_then(|bytes| serde_json::from_slice::<serde_json::Value>(bytes).ok())
+    else {
+        return Ok(None);
+    };
+    match fields.get("route_auth") {
+        None | Some(serde_json::Value::Null) => Ok(None),
+        Some(serde_json::Value::String(raw)) => raw.parse::<RoutePolicy>().map(Some),
+        Some(other) => Err(format!("invalid route_auth {}: expected a string", other)),
+    }
+}
+
+/// 解析管理员配置的路由策略覆盖
+pub fn parse_overrides(
+    raw: &HashMap<String, String>,
+) -> anyhow::Result<HashMap<String, RoutePolicy>> {
+    raw.iter()
+        .map(|(plugin_id, policy)| {
+            policy
+                .parse::<RoutePolicy>()
+                .map(|policy| (plugin_id.clone(), policy))
+                .map_err(|e| anyhow::anyhow!("plugins.route_policies.{}: {}", plugin_id, e))
+        })
+        .collect()
+}
+
+impl PluginManager {
+    /// 插件路由生效的访问策略：管理员覆盖优先于插件声明
+    pub fn route_policy(&self, runtime: &PluginRuntime) -> RoutePolicy {
+        self.route_policies
+            .get(&runtime.id)
+            .or(runtime.route_policy.as_ref())
+            .cloned()
+            .unwrap_or_default()
+    }
+}
@@ -0,0 +1,166 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tracing::{info, warn};
+
+use super::PluginRuntime;
+use crate::config::ScratchSettings;
+
+/// 使用临时目录所需的权限
+pub const SCRATCH_PERMISSION: &str = "file:scratch";
+/// 临时目录在插件内的挂载路径
+pub const SCRATCH_GUEST_PATH: &str = "/scratch";
+
+/// 插件临时目录
+///
+/// 每个插件在数据根目录下拥有独立子目录，以 WASI preopen 挂载到 `/scratch`。
+/// 配额在每次调用开始前检查：超出配额时新实例以只读文件权限挂载目录（仍可列出、读取、
+/// 删除与重命名），直到占用回落到配额以内。宿主不会删除插件的文件。
+///
+/// 配额不是硬上限：检查只在调用之间进行，单次调用（以及同一插件并发执行中的调用）
+/// 写入的数据不受限制，目录占用可能超出配额，超出量取决于调用期间写入的数据量。
+pub struct ScratchSpace {
+    root: Option<PathBuf>,
+    quota_bytes: u64,
+    over_quota: Mutex<HashSet<String>>,
+}
+
+/// 挂载给插件实例的临时目录
+#[derive(Debug, Clone)]
+pub struct ScratchMount {
+    pub path: PathBuf,
+    /// 超出配额时为 `false`，实例不能写入文件内容
+    pub writable: bool,
+}
+
+impl ScratchSpace {
+    pub fn new(settings: &ScratchSettings) -> anyhow::Result<Self> {
+        if let Some(root) = &settings.root {
+            std::fs::create_dir_all(root)?;
+            info!(
+                "[Scratch] Plugin scratch root: {} (quota {}MB)",
+                root.display(),
+                settings.quota_mb
+            );
+        }
+        Ok(Self {
+            root: settings.root.clone(),
+            quota_bytes: settings.quota_mb * 1024 * 1024,
+            over_quota: Mutex::new(HashSet::new()),
+        })
+    }
+
+    fn path_for(&self, plugin_id: &str) -> Option<PathBuf> {
+        let root = self.root.as_ref()?;
+        let name: String = plugin_id
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        Some(root.join(name))
+    }
+
+    /// 插件持有 `file:scratch` 权限时返回（并创建）其临时目录
+    pub fn dir_for(&self, plugin_id: &str, permissions: &HashSet<String>) -> Option<ScratchMount> {
+        if !permissions.contains(SCRATCH_PERMISSION) {
+            return None;
+        }
+        let dir = self.path_for(plugin_id)?;
+        if let Err(e) = std::fs::create_dir_all(&dir) {
+            warn!(
+                "[Scratch] Failed to create scratch dir for '{}': {}",
+                plugin_id, e
+            );
+            return None;
+        }
+        Some(ScratchMount {
+            path: dir,
+            writable: !self.is_over_quota(plugin_id),
+        })
+    }
+
+    /// 插件最近一次检查时是否超出配额
+    pub fn is_over_quota(&self, plugin_id: &str) -> bool {
+        self.over_quota.lock().unwrap().contains(plugin_id)
+    }
+
+    /// 调用开始前在阻塞线程池上检查配额
+    ///
+    /// 超额状态变化时清空插件的实例池，之后的实例按新的挂载权限创建；
+    /// 已在执行中的实例在归还时因代数不符被丢弃。
+    pub async fn admit(self: &Arc<Self>, runtime: &PluginRuntime) {
+        if self.quota_bytes == 0
+            || self.root.is_none()
+            || !runtime.grants().permissions.contains(SCRATCH_PERMISSION)
+        {
+            return;
+        }
+        let scratch = self.clone();
+        let plugin_id = runtime.id.clone();
+        let changed = tokio::task::spawn_blocking(move || scratch.check_quota(&plugin_id))
+            .await
+            .unwrap_or(false);
+        if changed {
+            runtime.instances.clear();
+        }
+    }
+
+    /// 重新统计插件临时目录的占用，返回超额状态是否发生变化
+    pub fn check_quota(&self, plugin_id: &str) -> bool {
+        let Some(dir) = self.path_for(plugin_id) else {
+            return false;
+        };
+        let used = if dir.exists() { dir_size(&dir) } else { 0 };
+        let over = self.quota_bytes > 0 && used > self.quota_bytes;
+        let mut over_quota = self.over_quota.lock().unwrap();
+        if over == over_quota.contains(plugin_id) {
+            return false;
+        }
+        if over {
+            warn!(
+                "[Scratch] '{}' uses {} bytes (quota {}), mounting scratch dir read-only",
+                plugin_id, used, self.quota_bytes
+            );
+            over_quota.insert(plugin_id.to_string());
+        } else {
+            info!(
+                "[Scratch] '{}' is back within its scratch quota ({} bytes)",
+                plugin_id, used
+            );
+            over_quota.remove(plugin_id);
+        }
+        true
+    }
+
+    /// 删除插件临时目录（卸载且不保留数据时）
+    pub fn remove(&self, plugin_id: &str) -> anyhow::Result<()> {
+        let Some(dir) = self.path_for(plugin_id) else {
+            return Ok(());
+        };
+        if dir.exists() {
+            std::fs::remove_dir_all(&dir)?;
+            info!("[Scratch] Removed scratch dir of '{}'", plugin_id);
+        }
+        self.over_quota.lock().unwrap().remove(plugin_id);
+        Ok(())
+    }
+}
+
+fn dir_size(dir: &Path) -> u64 {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return 0;
+    };
+    entries
+        .filter_map(Result::ok)
+        .map(|entry| match entry.metadata() {
+            Ok(meta) if meta.is_dir() => dir_size(&entry.path()),
+            Ok(meta) => meta.len(),
+            Err(_) => 0,
+        })
+        .sum()
+}
@@ -0,0 +1,154 @@
+use base64::Engine as _;
+use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+use tracing::{debug, warn};
+
+use super::PluginRejection;
+use crate::config::{SignatureMode, SignatureSettings};
+
+/// 独立签名文件后缀（位于包文件旁，例如 `auth.vtx.sig`）
+pub const DETACHED_SIGNATURE_SUFFIX: &str = ".sig";
+
+/// 签名载荷的域分隔前缀
+const SIGNED_PAYLOAD_PREFIX: &[u8] = b"vtx-package-signature:v2\0";
+
+/// 插件包签名校验器
+///
+/// 签名覆盖 `signed_payload` 给出的载荷：组件字节加上去掉 `signature` 字段后的规范化元数据，
+/// 因此改写路由策略、依赖等元数据同样会导致校验失败。签名可来自独立签名文件，
+/// 也可嵌入 v2 元数据的 `signature` 字段；两者均为 base64 编码的 ed25519 签名。
+pub struct SignatureVerifier {
+    mode: SignatureMode,
+    keys: Vec<(String, VerifyingKey)>,
+}
+
+impl SignatureVerifier {
+    pub fn new(settings: &SignatureSettings) -> anyhow::Result<Self> {
+        let mut keys = Vec::with_capacity(settings.trusted_keys.len());
+        for key in &settings.trusted_keys {
+            let raw = decode_base64(&key.public_key).map_err(|e| {
+                anyhow::anyhow!(
+                    "Invalid public key for publisher '{}': {}",
+                    key.publisher,
+                    e
+                )
+            })?;
+            let raw: [u8; 32] = raw.try_into().map_err(|_| {
+                anyhow::anyhow!(
+                    "Public key for publisher '{}' must be 32 bytes",
+                    key.publisher
+                )
+            })?;
+            let verifying_key = VerifyingKey::from_bytes(&raw).map_err(|e| {
+                anyhow::anyhow!(
+                    "Invalid public key for publisher '{}': {}",
+                    key.publisher,
+                    e
+                )
+            })?;
+            keys.push((key.publisher.clone(), verifying_key));
+        }
+
+        if settings.mode == SignatureMode::Enforce && keys.is_empty() {
+            warn!("[Signature] Enforce mode without trusted keys: every plugin will be rejected");
+        }
+
+        Ok(Self {
+            mode: settings.mode,
+            keys,
+        })
+    }
+
+    /// 校验签名并返回可信发布者
+    ///
+    /// `enforce` 模式下校验失败返回 `PluginRejection::SignatureRejected`；
+    /// `warn` 模式仅记录日志；`off` 模式不做校验。
+    pub fn verify(
+        &self,
+        component: &[u8],
+        metadata: Option<&[u8]>,
+        detached: Option<&[u8]>,
+        source: &str,
+    ) -> Result<Option<String>, PluginRejection> {
+        if self.mode == SignatureMode::Off {
+            return Ok(None);
+        }
+
+        match self.check(component, metadata, detached) {
+            Ok(publisher) => {
+                debug!("[Signature] {} signed by '{}'", source, publisher);
+                Ok(Some(publisher))
+            }
+            Err(message) if self.mode == SignatureMode::Warn => {
+                warn!(
+                    "[Signature] {}: {} (warn mode, loading anyway)",
+                    source, message
+                );
+                Ok(None)
+            }
+            Err(message) => Err(PluginRejection::SignatureRejected {
+                message: format!("{}: {}", source, message),
+            }),
+        }
+    }
+
+    fn check(
+        &self,
+        component: &[u8],
+        metadata: Option<&[u8]>,
+        detached: Option<&[u8]>,
+    ) -> Result<String, String> {
+        let encoded = match detached {
+            Some(bytes) => String::from_utf8_lossy(bytes).trim().to_string(),
+            None => embedded_signature(metadata).ok_or("package is not signed")?,
+        };
+        let raw = decode_base64(&encoded).map_err(|e| format!("malformed signature: {}", e))?;
+        let signature =
+            Signature::from_slice(&raw).map_err(|e| format!("malformed signature: {}", e))?;
+        let payload = signed_payload(component, metadata);
+
+        self.keys
+            .iter()
+            .find(|(_, key)| key.verify(&payload, &signature).is_ok())
+            .map(|(publisher, _)| publisher.clone())
+            .ok_or_else(|| "signature does not match any trusted publisher key".to_string())
+    }
+}
+
+/// 发布者需要签名的载荷
+///
+/// 依次为域分隔前缀、组件长度（u64 小端）、组件字节与规范化元数据。JSON 对象元数据去掉
+/// `signature` 字段后按键排序序列化；其他格式的元数据按原始字节参与签名。
+pub fn signed_payload(component: &[u8], metadata: Option<&[u8]>) -> Vec<u8> {
+    let metadata = match metadata {
+        Some(bytes) => match serde_json::from_slice::<serde_json::Value>(bytes) {
+            Ok(serde_json::Value::Object(mut fields)) => {
+                fields.remove("signature");
+                serde_json::to_vec(&fields).unwrap_or_else(|_| bytes.to_vec())
+            }
+            _ => bytes.to_vec(),
+        },
+        None => Vec::new(),
+    };
+
+    let mut payload =
+        Vec::with_capacity(SIGNED_PAYLOAD_PREFIX.len() + 8 + component.len() + metadata.len());
+    payload.extend_from_slice(SIGNED_PAYLOAD_PREFIX);
+    payload.extend_from_slice(&(component.len() as u64).to_le_bytes());
+    payload.extend_from_slice(component);
+    payload.extend_from_slice(&metadata);
+    payload
+}
+
+fn embedded_signature(metadata: Option<&[u8]>) -> Option<String> {
+    #[derive(serde::Deserialize)]
+    struct Signed {
+        signature: Option<String>,
+    }
+
+    let signed: Signed = serde_json::from_slice(metadata?).ok()?;
+    signed.signature
+}
+
+fn decode_base64(value: &str) -> Result<Vec<u8>, base64::DecodeError> {
+    base64::engine::general_purpose::STANDARD.decode(value.trim())
+}
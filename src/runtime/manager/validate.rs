@@ -0,0 +1,160 @@
+use serde::Serialize;
+
+use super::dependencies::PluginDependency;
+use super::install::find_route_conflict;
+use super::{loader, PluginManager, PluginRejection, VtxPackageMetadata};
+use crate::runtime::vtx_host_impl::api::vtx_types::{HttpAllowRule, Manifest};
+
+/// 插件包预检报告（不注册、不迁移、不写入）
+#[derive(Debug, Serialize)]
+pub struct PluginValidationReport {
+    pub manifest: ManifestView,
+    pub vtx_meta: Option<VtxPackageMetadata>,
+    /// 签名校验通过的发布者
+    pub publisher: Option<String>,
+    pub dependencies: Vec<PluginDependency>,
+    pub subscriptions: Vec<String>,
+    pub permissions: Vec<String>,
+    pub http: Vec<HttpAllowRuleView>,
+    /// 规范化后的声明表名
+    pub tables: Vec<String>,
+    /// 改写后的迁移语句（按声明顺序）
+    pub migrations: Vec<String>,
+    /// 当前数据库中该插件已应用的迁移数量
+    pub applied_migrations: usize,
+    /// 若实际安装会遇到的冲突
+    pub conflicts: Vec<PluginRejection>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ManifestView {
+    pub id: String,
+    pub name: String,
+    pub version: String,
+    pub description: String,
+    pub entrypoint: String,
+}
+
+impl From<&Manifest> for ManifestView {
+    fn from(manifest: &Manifest) -> Self {
+        Self {
+            id: manifest.id.clone(),
+            name: manifest.name.clone(),
+            version: manifest.version.clone(),
+            description: manifest.description.clone(),
+            entrypoint: manifest.entrypoint.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct HttpAllowRuleView {
+    pub scheme: String,
+    pub host: String,
+    pub port: Option<u16>,
+    pub path: Option<String>,
+    pub methods: Option<Vec<String>>,
+    pub allow_headers: Option<Vec<String>>,
+    pub max_request_bytes: Option<u64>,
+    pub max_response_bytes: Option<u64>,
+    pub follow_redirects: Option<bool>,
+    pub redirect_policy: Option<String>,
+}
+
+impl From<&HttpAllowRule> for HttpAllowRuleView {
+    fn from(rule: &HttpAllowRule) -> Self {
+        Self {
+            scheme: rule.scheme.clone(),
+            host: rule.host.clone(),
+            port: rule.port,
+            path: rule.path.clone(),
+            methods: rule.methods.clone(),
+            allow_headers: rule.allow_headers.clone(),
+            max_request_bytes: rule.max_request_bytes,
+            max_response_bytes: rule.max_response_bytes,
+            follow_redirects: rule.follow_redirects,
+            redirect_policy: rule.redirect_policy.clone(),
+        }
+    }
+}
+
+impl PluginManager {
+    /// 对上传的包执行完整加载流程的只读部分
+    ///
+    /// 迁移语句非法时返回 `PluginRejection::MigrationRejected`，其中包含被拒绝的原始语句；
+    /// 签名校验同样生效。
+    pub async fn validate_package(
+        &self,
+        bytes: &[u8],
+        detached_signature: Option<&[u8]>,
+    ) -> anyhow::Result<PluginValidationReport> {
+        let load_result = loader::inspect_package(
+            &self.engine,
+            &self.component_cache,
+            &self.signature_verifier,
+            &self.registry,
+            &self.linker,
+            &self.deadlines,
+            self.max_memory_bytes,
+            loader::PackageSource {
+                bytes,
+                detached_signature,
+                source_hint: "upload.vtx",
+            },
+            self.vtx_ffmpeg.clone(),
+            self.vfs.clone(),
+            self.event_bus.clone(),
+        )
+        .await?;
+
+        let plugin_id = &load_result.plugin_id;
+        let mut conflicts = Vec::new();
+        {
+            let routes = self.routes.read().unwrap();
+            if let Some(conflict) =
+                find_route_conflict(&routes, plugin_id, &load_result.manifest.entrypoint)
+            {
+                conflicts.push(conflict);
+            }
+        }
+        if let Err(rejection) = self.check_dependencies(plugin_id, &load_result.dependencies) {
+            conflicts.push(rejection);
+        }
+        match self.install_root() {
+            Ok(root) => {
+                if let Some(registered_uri) = self.registry.get_installation(plugin_id)? {
+                    let shadowed = !registered_uri.starts_with(&root.uri)
+                        && !self.outranks(&root.uri, &registered_uri)
+                        && self.vfs.head(&registered_uri).await.is_ok();
+                    if shadowed {
+                        conflicts.push(PluginRejection::InstallationLocked {
+                            plugin_id: plugin_id.clone(),
+                            registered_uri,
+                            attempted_uri: root.uri.clone(),
+                        });
+                    }
+                }
+            }
+            Err(rejection) => conflicts.push(rejection),
+        }
+
+        Ok(PluginValidationReport {
+            manifest: ManifestView::from(&load_result.manifest),
+            vtx_meta: load_result.vtx_meta.clone(),
+            publisher: load_result.publisher.clone(),
+            dependencies: load_result.dependencies.clone(),
+            subscriptions: load_result.policy.subscriptions.clone(),
+            permissions: load_result.policy.permissions.clone(),
+            http: load_result
+                .policy
+                .http
+                .iter()
+                .map(HttpAllowRuleView::from)
+                .collect(),
+            applied_migrations: self.registry.get_plugin_version(plugin_id),
+            tables: load_result.resources,
+            migrations: load_result.migrations,
+            conflicts,
+        })
+    }
+}
@@ -0,0 +1,153 @@
+use semver::Version;
+use sha2::{Digest, Sha256};
+use tracing::warn;
+
+use super::{loader, PluginManager, PluginRejection, PluginRuntime};
+use crate::storage::plugin_history::{
+    PluginVersionRecord, OUTCOME_ACTIVATED, OUTCOME_REJECTED, OUTCOME_UNINSTALLED,
+};
+
+/// 插件包内容哈希（SHA-256 十六进制）
+pub fn content_hash(package: &[u8]) -> String {
+    hex::encode(Sha256::digest(package))
+}
+
+/// 降级保护：manifest 版本低于已安装版本，或迁移列表短于已应用数量时拒绝
+///
+/// 任一版本号不是合法 semver 时无法比较，只检查迁移数量。
+pub fn check_downgrade(
+    plugin_id: &str,
+    installed_version: Option<&str>,
+    attempted_version: &str,
+    applied_migrations: usize,
+    declared_migrations: usize,
+) -> Result<(), PluginRejection> {
+    if let Some(installed) = installed_version {
+        let older = match (Version::parse(installed), Version::parse(attempted_version)) {
+            (Ok(installed), Ok(attempted)) => attempted < installed,
+            _ => false,
+        };
+        if older {
+            return Err(PluginRejection::Downgrade {
+                plugin_id: plugin_id.to_string(),
+                installed_version: installed.to_string(),
+                attempted_version: attempted_version.to_string(),
+            });
+        }
+    }
+    if declared_migrations < applied_migrations {
+        return Err(PluginRejection::MigrationsBehind {
+            plugin_id: plugin_id.to_string(),
+            applied: applied_migrations,
+            declared: declared_migrations,
+        });
+    }
+    Ok(())
+}
+
+/// 重新加载检查：与当前安装记录内容相同的包（例如回滚后从磁盘重载）不视为降级
+///
+/// 回滚不会撤销新版本已应用的迁移，回滚到的包声明的迁移可能少于已应用数量；
+/// 该包已由管理员确认过，不再做迁移数量检查。
+pub fn check_reload(
+    plugin_id: &str,
+    installed: Option<&PluginVersionRecord>,
+    attempted_version: &str,
+    package: &[u8],
+    applied_migrations: usize,
+    declared_migrations: usize,
+) -> Result<(), PluginRejection> {
+    if installed.is_some_and(|record| record.content_hash == content_hash(package)) {
+        return Ok(());
+    }
+    check_downgrade(
+        plugin_id,
+        installed.map(|record| record.version.as_str()),
+        attempted_version,
+        applied_migrations,
+        declared_migrations,
+    )
+}
+
+impl PluginManager {
+    /// 检查待加载的包是否为降级；`force` 时只记录警告
+    pub(super) fn guard_downgrade(
+        &self,
+        load_result: &loader::LoadResult,
+        package: &[u8],
+        force: bool,
+    ) -> anyhow::Result<()> {
+        let plugin_id = &load_result.plugin_id;
+        let installed = self.registry.installed_plugin_version(plugin_id)?;
+        let checked = check_reload(
+            plugin_id,
+            installed.as_ref(),
+            &load_result.manifest.version,
+            package,
+            self.registry.get_plugin_version(plugin_id),
+            load_result.migrations.len(),
+        );
+        match checked {
+            Ok(()) => Ok(()),
+            Err(rejection) if force => {
+                warn!("[Install] Forced past downgrade protection: {}", rejection);
+                Ok(())
+            }
+            Err(rejection) => Err(rejection.into()),
+        }
+    }
+
+    /// 写入版本历史
+    pub(super) fn record_version(
+        &self,
+        plugin_id: &str,
+        version: &str,
+        package: &[u8],
+        outcome: Result<&str, &anyhow::Error>,
+        forced: bool,
+    ) {
+        let (label, error) = match outcome {
+            Ok(label) => (label, None),
+            Err(e) => (OUTCOME_REJECTED, Some(e.to_string())),
+        };
+        if let Err(e) = self.registry.record_plugin_history(
+            plugin_id,
+            version,
+            &content_hash(package),
+            label,
+            error.as_deref(),
+            forced,
+        ) {
+            warn!("[Install] Failed to record version history: {}", e);
+        }
+    }
+
+    /// 记录一次加载尝试的版本历史
+    pub(super) fn record_load<T>(
+        &self,
+        plugin_id: &str,
+        version: &str,
+        package: &[u8],
+        outcome: &anyhow::Result<T>,
+        forced: bool,
+    ) {
+        let outcome = outcome.as_ref().map(|_| OUTCOME_ACTIVATED);
+        self.record_version(plugin_id, version, package, outcome, forced);
+    }
+
+    /// 彻底卸载后解除降级保护
+    pub(super) fn record_uninstalled(&self, plugin_id: &str, runtime: Option<&PluginRuntime>) {
+        let (version, package) = runtime
+            .map(|r| (r.manifest.version.as_str(), r.package.as_ref()))
+            .unwrap_or_default();
+        self.record_version(plugin_id, version, package, Ok(OUTCOME_UNINSTALLED), false);
+    }
+
+    pub fn list_versions(
+        &self,
+        plugin_id: &str,
+        limit: i64,
+    ) -> anyhow::Result<Vec<PluginVersionRecord>> {
+        self.registry.list_plugin_history(plugin_id, limit)
+    }
+}
@@ -1,28 +1,108 @@
 use futures_util::StreamExt;
+use notify::{RecursiveMode, Watcher};
 use std::collections::HashMap;
-use std::time::Duration;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime};
 use tokio::runtime::Handle;
-use tracing::{error, info};
+use tracing::{debug, error, info, warn};
+use url::Url;
 
-use super::{is_vtx_uri, PluginManager};
+use super::versions::content_hash;
+use super::{is_vtx_path, is_vtx_uri, PluginManager};
+use crate::config::{WatchBackend, WatchSettings};
 
-/// Starts a polling watcher that scans the VFS plugin root on an interval.
-pub fn spawn_watcher(manager: PluginManager, handle: Handle) {
-    handle.spawn(async move {
-        info!("[HotReload] Polling plugin root: {}", manager.plugin_root);
+/// 管理器自己写入插件根目录的包（安装、回滚）
+///
+/// 写入前登记 URI 与内容哈希；监听器随后对同一内容触发的重载会被跳过，
+/// 避免重复执行迁移、冒烟验证并写入第二条版本历史。登记在首次匹配或过期后失效。
+pub struct SelfWrites {
+    ttl: Duration,
+    entries: Mutex<HashMap<String, (String, Instant)>>,
+}
 
-        let mut known: HashMap<String, ObjectSignature> = HashMap::new();
-        let mut ticker = tokio::time::interval(Duration::from_millis(2000));
+impl SelfWrites {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
 
-        loop {
-            ticker.tick().await;
-            if let Err(e) = scan_once(&manager, &mut known).await {
-                error!("[HotReload] Polling error: {}", e);
+    /// 按监听配置推算登记有效期：覆盖轮询间隔与多次去抖
+    pub fn for_settings(settings: &WatchSettings) -> Self {
+        let window = settings.poll_interval_ms.max(settings.debounce_ms);
+        Self::new(Duration::from_millis(window.saturating_mul(4)).max(Duration::from_secs(30)))
+    }
+
+    pub fn mark(&self, uri: &str, package: &[u8]) {
+        let mut entries = self.entries.lock().unwrap();
+        entries.retain(|_, (_, at)| at.elapsed() < self.ttl);
+        entries.insert(uri.to_string(), (content_hash(package), Instant::now()));
+    }
+
+    pub fn unmark(&self, uri: &str) {
+        self.entries.lock().unwrap().remove(uri);
+    }
+
+    /// 该内容是否由管理器刚刚写入；匹配后登记即失效
+    pub fn take(&self, uri: &str, package: &[u8]) -> bool {
+        let mut entries = self.entries.lock().unwrap();
+        let matched = entries
+            .get(uri)
+            .is_some_and(|(hash, at)| at.elapsed() < self.ttl && *hash == content_hash(package));
+        if matched {
+            entries.remove(uri);
+        }
+        matched
+    }
+}
+
+/// Starts the watcher for one plugin root.
+///
+/// `file://` roots use native filesystem notifications; other roots (e.g. `s3://`)
+/// fall back to polling `list_objects` on the configured interval.
+pub fn spawn_watcher(
+    manager: PluginManager,
+    root: String,
+    settings: WatchSettings,
+    handle: Handle,
+) {
+    handle.spawn(async move {
+        if settings.backend == WatchBackend::Auto {
+            if let Some(path) = local_root(&root) {
+                match run_notify(&manager, &path, &settings).await {
+                    Ok(()) => return,
+                    Err(e) => warn!(
+                        "[HotReload] Filesystem notifications unavailable for {}: {}. Falling back to polling.",
+                        path.display(),
+                        e
+                    ),
+                }
             }
         }
+        run_polling(manager, &root, &settings).await;
     });
 }
 
+async fn run_polling(manager: PluginManager, root: &str, settings: &WatchSettings) {
+    info!(
+        "[HotReload] Polling plugin root every {}ms: {}",
+        settings.poll_interval_ms, root
+    );
+
+    let mut known: HashMap<String, ObjectSignature> = HashMap::new();
+    let mut ticker =
+        tokio::time::interval(Duration::from_millis(settings.poll_interval_ms.max(100)));
+
+    loop {
+        ticker.tick().await;
+        if let Err(e) = scan_once(&manager, root, &mut known).await {
+            error!("[HotReload] Polling error: {}", e);
+        }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq)]
 struct ObjectSignature {
     size: u64,
@@ -32,16 +112,17 @@ struct ObjectSignature {
 
 async fn scan_once(
     manager: &PluginManager,
+    root: &str,
     known: &mut HashMap<String, ObjectSignature>,
 ) -> anyhow::Result<()> {
-    let mut entries = manager.vfs.list_objects(&manager.plugin_root).await?;
+    let mut entries = manager.vfs.list_objects(root).await?;
     let mut current: HashMap<String, ObjectSignature> = HashMap::new();
     let mut changed: Vec<String> = Vec::new();
 
     while let Some(item) = entries.next().await {
         match item {
             Ok(obj) => {
-                if !is_vtx_uri(&obj.uri) {
+                if !is_vtx_uri(&obj.uri) || is_partial_uri(&obj.uri) {
                     continue;
                 }
                 let sig = ObjectSignature {
@@ -73,13 +154,180 @@ async fn scan_once(
     }
 
     for uri in changed {
-        let manager = manager.clone();
-        tokio::spawn(async move {
-            if let Err(e) = manager.load_one(&uri).await {
-                error!("[HotReload] Failed to reload plugin {}: {}", uri, e);
-            }
-        });
+        spawn_load(manager, uri);
     }
 
     Ok(())
 }
+
+/// 文件大小与修改时间，用于判断写入是否完成
+#[derive(Clone, Debug, PartialEq)]
+struct FileState {
+    size: u64,
+    modified: Option<SystemTime>,
+}
+
+/// 已收到事件、等待稳定的文件
+struct PendingChange {
+    deadline: Instant,
+    observed: Option<FileState>,
+}
+
+/// 基于文件系统通知的监听
+///
+/// 事件先去抖，到期后检查文件大小与修改时间：与上一次观察一致才加载，
+/// 否则继续等待一个去抖周期；文件已不存在则卸载。
+async fn run_notify(
+    manager: &PluginManager,
+    root: &Path,
+    settings: &WatchSettings,
+) -> anyhow::Result<()> {
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+    let mut watcher = notify::recommended_watcher(move |event| {
+        let _ = tx.send(event);
+    })?;
+    watcher.watch(root, RecursiveMode::Recursive)?;
+    info!(
+        "[HotReload] Watching plugin root for filesystem events: {}",
+        root.display()
+    );
+
+    let debounce = Duration::from_millis(settings.debounce_ms.max(50));
+    let mut ticker = tokio::time::interval(debounce / 2);
+    let mut pending: HashMap<PathBuf, PendingChange> = HashMap::new();
+
+    loop {
+        tokio::select! {
+            Some(event) = rx.recv() => match event {
+                Ok(event) => {
+                    if event.need_rescan() {
+                        warn!("[HotReload] Filesystem events were dropped; some changes may be missed");
+                    }
+                    for path in event.paths {
+                        if !is_candidate_path(&path) {
+                            continue;
+                        }
+                        debug!("[HotReload] {:?}: {}", event.kind, path.display());
+                        let deadline = Instant::now() + debounce;
+                        pending
+                            .entry(path)
+                            .and_modify(|change| change.deadline = deadline)
+                            .or_insert(PendingChange {
+                                deadline,
+                                observed: None,
+                            });
+                    }
+                }
+                Err(e) => error!("[HotReload] Filesystem watch error: {}", e),
+            },
+            _ = ticker.tick() => settle(manager, &mut pending, debounce),
+        }
+    }
+}
+
+fn settle(
+    manager: &PluginManager,
+    pending: &mut HashMap<PathBuf, PendingChange>,
+    debounce: Duration,
+) {
+    let now = Instant::now();
+    let due: Vec<PathBuf> = pending
+        .iter()
+        .filter(|(_, change)| change.deadline <= now)
+        .map(|(path, _)| path.clone())
+        .collect();
+
+    for path in due {
+        let state = file_state(&path);
+        let Some(change) = pending.get_mut(&path) else {
+            continue;
+        };
+        match state {
+            Some(state) if change.observed.as_ref() != Some(&state) => {
+                change.observed = Some(state);
+                change.deadline = now + debounce;
+            }
+            state => {
+                pending.remove(&path);
+                let Ok(uri) = Url::from_file_path(&path) else {
+                    continue;
+                };
+                if state.is_some() {
+                    spawn_load(manager, uri.to_string());
+                } else {
+                    manager.uninstall_by_uri(uri.as_str());
+                }
+            }
+        }
+    }
+}
+
+fn spawn_load(manager: &PluginManager, uri: String) {
+    let manager = manager.clone();
+    tokio::spawn(async move {
+        if let Err(e) = manager.load_one(&uri).await {
+            error!("[HotReload] Failed to reload plugin {}: {}", uri, e);
+        }
+    });
+}
+
+fn file_state(path: &Path) -> Option<FileState> {
+    let meta = std::fs::metadata(path).ok()?;
+    meta.is_file().then(|| FileState {
+        size: meta.len(),
+        modified: meta.modified().ok(),
+    })
+}
+
+fn local_root(plugin_root: &str) -> Option<PathBuf> {
+    let url = Url::parse(plugin_root).ok()?;
+    if url.scheme() != "file" {
+        return None;
+    }
+    url.to_file_path().ok()
+}
+
+fn is_candidate_path(path: &Path) -> bool {
+    let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+        return false;
+    };
+    is_vtx_path(name) && !is_partial_name(name)
+}
+
+fn is_partial_uri(uri: &str) -> bool {
+    Url::parse(uri)
+        .ok()
+        .and_then(|url| {
+            url.path_segments()
+                .and_then(|mut segments| segments.next_back().map(is_partial_name))
+        })
+        .unwrap_or(false)
+}
+
+/// 隐藏文件、编辑器备份与下载 / 复制中的临时文件
+fn is_partial_name(name: &str) -> bool {
+    const PARTIAL_SUFFIXES: &[&str] = &[".tmp", ".part", ".partial", ".crdownload", ".swp"];
+    let lower = name.to_ascii_lowercase();
+    name.starts_with('.')
+        || name.starts_with('~')
+        || name.ends_with('~')
+        || PARTIAL_SUFFIXES
+            .iter()
+            .any(|suffix| lower.ends_with(suffix))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn candidate_path_skips_partial_files() {
+        assert!(is_candidate_path(Path::new("/plugins/auth.vtx")));
+        assert!(!is_candidate_path(Path::new("/plugins/auth.vtx.part")));
+        assert!(!is_candidate_path(Path::new("/plugins/.auth.vtx")));
+        assert!(!is_candidate_path(Path::new("/plugins/auth.vtx~")));
+        assert!(!is_candidate_path(Path::new("/plugins/auth.wasm")));
+        assert!(is_partial_uri("file:///plugins/.auth.vtx"));
+        assert!(!is_partial_uri("s3://bucket/plugins/auth.vtx"));
+    }
+}
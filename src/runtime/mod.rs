@@ -1,8 +1,12 @@
 pub mod bus;
 pub mod context;
+pub mod deadline;
 pub mod executor;
 pub mod ffmpeg;
+pub mod fuel;
 pub mod job_registry;
 pub mod jobs;
 pub mod manager;
+pub mod plugin_logs;
+pub mod request_data;
 pub mod vtx_host_impl;
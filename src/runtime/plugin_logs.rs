@@ -0,0 +1,213 @@
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::io::AsyncWrite;
+use tokio::sync::broadcast;
+use wasmtime_wasi::cli::{IsTerminal, StdoutStream};
+
+/// 未换行输出的最大缓冲长度，超出后按一行提交
+const MAX_PENDING_BYTES: usize = 8 * 1024;
+/// 实时订阅通道容量
+const FOLLOW_CAPACITY: usize = 1024;
+/// 关联插件输出与请求的请求头
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// 插件输出流
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LogStream {
+    Stdout,
+    Stderr,
+}
+
+/// 插件输出的一行
+#[derive(Debug, Clone, Serialize)]
+pub struct PluginLogLine {
+    /// 全局递增序号，可用于增量拉取
+    pub seq: u64,
+    pub plugin_id: String,
+    pub stream: LogStream,
+    pub request_id: Option<String>,
+    /// Unix 时间戳（毫秒）
+    pub timestamp_ms: u64,
+    pub line: String,
+}
+
+/// 按插件划分的 stdout / stderr 环形缓冲
+///
+/// 每行同时以 `vtx::plugin` 为 target 写入 `tracing`，并广播给实时订阅者。
+pub struct PluginLogs {
+    capacity: usize,
+    buffers: Mutex<HashMap<String, VecDeque<PluginLogLine>>>,
+    seq: AtomicU64,
+    follow: broadcast::Sender<PluginLogLine>,
+}
+
+impl PluginLogs {
+    pub fn new(capacity: usize) -> Self {
+        let (follow, _) = broadcast::channel(FOLLOW_CAPACITY);
+        Self {
+            capacity,
+            buffers: Mutex::new(HashMap::new()),
+            seq: AtomicU64::new(0),
+            follow,
+        }
+    }
+
+    pub fn push(
+        &self,
+        plugin_id: &str,
+        stream: LogStream,
+        request_id: Option<String>,
+        line: String,
+    ) {
+        match stream {
+            LogStream::Stdout => tracing::info!(
+                target: "vtx::plugin",
+                plugin_id,
+                request_id = request_id.as_deref().unwrap_or("-"),
+                "{}",
+                line
+            ),
+            LogStream::Stderr => tracing::warn!(
+                target: "vtx::plugin",
+                plugin_id,
+                request_id = request_id.as_deref().unwrap_or("-"),
+                "{}",
+                line
+            ),
+        }
+        if self.capacity == 0 {
+            return;
+        }
+
+        let entry = PluginLogLine {
+            seq: self.seq.fetch_add(1, Ordering::Relaxed) + 1,
+            plugin_id: plugin_id.to_string(),
+            stream,
+            request_id,
+            timestamp_ms: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_millis() as u64)
+                .unwrap_or(0),
+            line,
+        };
+        {
+            let mut buffers = self.buffers.lock().unwrap();
+            let buffer = buffers.entry(plugin_id.to_string()).or_default();
+            if buffer.len() >= self.capacity {
+                buffer.pop_front();
+            }
+            buffer.push_back(entry.clone());
+        }
+        let _ = self.follow.send(entry);
+    }
+
+    /// 最近的输出（按序号升序），`after` 用于只取该序号之后的行
+    pub fn recent(&self, plugin_id: &str, limit: usize, after: Option<u64>) -> Vec<PluginLogLine> {
+        let buffers = self.buffers.lock().unwrap();
+        let Some(buffer) = buffers.get(plugin_id) else {
+            return Vec::new();
+        };
+        let lines: Vec<&PluginLogLine> = buffer
+            .iter()
+            .filter(|line| after.is_none_or(|after| line.seq > after))
+            .collect();
+        let skip = lines.len().saturating_sub(limit);
+        lines.into_iter().skip(skip).cloned().collect()
+    }
+
+    /// 订阅所有插件的新输出
+    pub fn subscribe(&self) -> broadcast::Receiver<PluginLogLine> {
+        self.follow.subscribe()
+    }
+
+    pub fn clear(&self, plugin_id: &str) {
+        self.buffers.lock().unwrap().remove(plugin_id);
+    }
+}
+
+/// 接入 WASI stdout / stderr 的输出端
+///
+/// 按行切分后写入 `PluginLogs`；`request_id` 与所属 `StreamContext` 共享，随调用切换。
+#[derive(Clone)]
+pub struct PluginOutput {
+    logs: Arc<PluginLogs>,
+    plugin_id: Arc<str>,
+    stream: LogStream,
+    request_id: Arc<Mutex<Option<String>>>,
+    pending: Arc<Mutex<Vec<u8>>>,
+}
+
+impl PluginOutput {
+    pub fn new(
+        logs: Arc<PluginLogs>,
+        plugin_id: &str,
+        stream: LogStream,
+        request_id: Arc<Mutex<Option<String>>>,
+    ) -> Self {
+        Self {
+            logs,
+            plugin_id: Arc::from(plugin_id),
+            stream,
+            request_id,
+            pending: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    fn write_bytes(&self, bytes: &[u8]) {
+        let mut pending = self.pending.lock().unwrap();
+        pending.extend_from_slice(bytes);
+        while let Some(pos) = pending.iter().position(|b| *b == b'\n') {
+            let line: Vec<u8> = pending.drain(..=pos).collect();
+            self.emit(&line[..line.len() - 1]);
+        }
+        if pending.len() >= MAX_PENDING_BYTES {
+            let line = std::mem::take(&mut *pending);
+            self.emit(&line);
+        }
+    }
+
+    fn emit(&self, line: &[u8]) {
+        let line = String::from_utf8_lossy(line);
+        let line = line.strip_suffix('\r').unwrap_or(&line).to_string();
+        let request_id = self.request_id.lock().unwrap().clone();
+        self.logs
+            .push(&self.plugin_id, self.stream, request_id, line);
+    }
+}
+
+impl IsTerminal for PluginOutput {
+    fn is_terminal(&self) -> bool {
+        false
+    }
+}
+
+impl StdoutStream for PluginOutput {
+    fn async_stream(&self) -> Box<dyn AsyncWrite + Send + Sync> {
+        Box::new(self.clone())
+    }
+}
+
+impl AsyncWrite for PluginOutput {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        self.write_bytes(buf);
+        Poll::Ready(Ok(buf.len()))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+}
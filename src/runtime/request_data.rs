@@ -0,0 +1,106 @@
+use axum::http::{header, HeaderMap, HeaderName, HeaderValue};
+use bytes::Bytes;
+
+/// 插件读取当前请求数据的保留 URI 前缀（通过 vtx-vfs `open-uri`）
+pub const REQUEST_URI_PREFIX: &str = "vtx-request:";
+/// 白名单内的请求头，内容为 JSON 对象（键为小写头名，同名多值以 `, ` 连接）
+pub const REQUEST_HEADERS_URI: &str = "vtx-request:headers";
+/// 请求体原始字节
+pub const REQUEST_BODY_URI: &str = "vtx-request:body";
+/// 插件设置响应头：向该 URI 的 buffer 写入 `Name: value` 行（每行一个，可重复）
+pub const RESPONSE_HEADERS_URI: &str = "vtx-response:headers";
+
+/// 单次调用最多设置的响应头数量
+const MAX_RESPONSE_HEADERS: usize = 64;
+
+/// 由宿主管理、插件不能覆盖的响应头
+const PROTECTED_RESPONSE_HEADERS: &[&str] = &[
+    "content-length",
+    "content-range",
+    "accept-ranges",
+    "transfer-encoding",
+    "connection",
+    "keep-alive",
+    "upgrade",
+    "trailer",
+    "x-request-id",
+];
+
+/// 转发给插件的请求头与请求体
+///
+/// vtx-protocol 5.0 的 `http-request` 只有 method / path / query，插件在 `handle` 中通过
+/// `open-uri` 打开上面的保留 URI 读取其余部分。数据随调用上下文切换，复用的实例也只能读到本次请求。
+#[derive(Debug, Default)]
+pub struct RequestData {
+    pub headers: Vec<(String, String)>,
+    pub body: Bytes,
+}
+
+impl RequestData {
+    /// 按白名单（不区分大小写）筛选请求头
+    pub fn new(headers: &HeaderMap, allowlist: &[String], body: Bytes) -> Self {
+        let headers = allowlist
+            .iter()
+            .filter_map(|name| {
+                let name = name.trim().to_ascii_lowercase();
+                let values: Vec<&str> = headers
+                    .get_all(name.as_str())
+                    .iter()
+                    .filter_map(|v| v.to_str().ok())
+                    .collect();
+                (!values.is_empty()).then(|| (name, values.join(", ")))
+            })
+            .collect();
+        Self { headers, body }
+    }
+
+    /// 请求头的 JSON 表示
+    pub fn headers_json(&self) -> Vec<u8> {
+        let object: serde_json::Map<String, serde_json::Value> = self
+            .headers
+            .iter()
+            .map(|(name, value)| (name.clone(), serde_json::Value::String(value.clone())))
+            .collect();
+        serde_json::to_vec(&object).unwrap_or_else(|_| b"{}".to_vec())
+    }
+
+    /// 请求体的 Content-Type（仅当该头在白名单内）
+    pub fn content_type(&self) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(name, _)| name == header::CONTENT_TYPE.as_str())
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// 解析插件写入的响应头行，追加到 `headers`
+///
+/// 整次写入要么全部接受，要么全部拒绝（格式错误、受保护的头或超出数量上限）。
+pub fn append_response_headers(
+    headers: &mut Vec<(HeaderName, HeaderValue)>,
+    data: &[u8],
+) -> Result<(), String> {
+    let text = std::str::from_utf8(data).map_err(|_| "Response headers must be UTF-8")?;
+    let mut parsed = Vec::new();
+    for line in text.lines().map(str::trim).filter(|line| !line.is_empty()) {
+        let (name, value) = line
+            .split_once(':')
+            .ok_or_else(|| format!("Malformed response header line: {}", line))?;
+        let name = HeaderName::from_bytes(name.trim().as_bytes())
+            .map_err(|_| format!("Invalid response header name: {}", name))?;
+        if PROTECTED_RESPONSE_HEADERS.contains(&name.as_str()) {
+            return Err(format!("Response header '{}' is managed by the host", name));
+        }
+        let value = HeaderValue::from_str(value.trim())
+            .map_err(|_| format!("Invalid value for response header '{}'", name))?;
+        parsed.push((name, value));
+    }
+    if headers.len() + parsed.len() > MAX_RESPONSE_HEADERS {
+        return Err(format!(
+            "At most {} response headers per call",
+            MAX_RESPONSE_HEADERS
+        ));
+    }
+    headers.extend(parsed);
+    Ok(())
+}
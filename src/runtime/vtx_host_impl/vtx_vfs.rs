@@ -3,6 +3,9 @@ use wasmtime::component::Resource;
 
 use crate::common::buffer::{BufferType, RealBuffer};
 use crate::runtime::context::{SecurityPolicy, StreamContext};
+use crate::runtime::request_data::{
+    self, REQUEST_BODY_URI, REQUEST_HEADERS_URI, REQUEST_URI_PREFIX, RESPONSE_HEADERS_URI,
+};
 use futures_util::StreamExt;
 
 use super::api;
@@ -38,6 +41,9 @@ impl api::vtx_vfs::Host for StreamContext {
             tracing::warn!("[Security] Blocked vfs access: {}", uri);
             return Err("Permission Denied".into());
         }
+        if uri.starts_with(REQUEST_URI_PREFIX) || uri == RESPONSE_HEADERS_URI {
+            return self.open_request_data(&uri);
+        }
         if self.policy == SecurityPolicy::Plugin && !self.has_permission("file:read") {
             tracing::warn!("[Security] Missing file:read permission: {}", uri);
             return Err("Permission Denied".into());
@@ -144,6 +150,37 @@ impl api::vtx_vfs::Host for StreamContext {
     }
 }
 
+impl StreamContext {
+    /// 打开当前请求的请求头 / 请求体，或用于设置响应头的 buffer（无需 file:read，数据只属于本次调用）
+    fn open_request_data(&mut self, uri: &str) -> Result<Resource<RealBuffer>, String> {
+        let request = self
+            .request
+            .as_ref()
+            .ok_or_else(|| "No request bound to this call".to_string())?;
+        let (data, mime) = match uri {
+            REQUEST_HEADERS_URI => (request.headers_json(), "application/json".to_string()),
+            REQUEST_BODY_URI => (
+                request.body.to_vec(),
+                request
+                    .content_type()
+                    .unwrap_or("application/octet-stream")
+                    .to_string(),
+            ),
+            RESPONSE_HEADERS_URI => (Vec::new(), "text/plain".to_string()),
+            _ => return Err(format!("Unknown request uri: {}", uri)),
+        };
+
+        self.table
+            .push(RealBuffer {
+                inner: BufferType::Memory(Cursor::new(data)),
+                uri_hint: Some(uri.to_string()),
+                mime_override: Some(mime),
+                process_handle: None,
+            })
+            .map_err(|e| format!("Resource Table Error: {}", e))
+    }
+}
+
 impl api::vtx_vfs::HostBuffer for StreamContext {
     async fn size(&mut self, resource: Resource<RealBuffer>) -> u64 {
         let rb = match self.table.get_mut(&resource) {
@@ -203,6 +240,24 @@ impl api::vtx_vfs::HostBuffer for StreamContext {
     }
 
     async fn write(&mut self, resource: Resource<RealBuffer>, data: Vec<u8>) -> u64 {
+        let is_response_headers = match self.table.get(&resource) {
+            Ok(rb) => rb.uri_hint.as_deref() == Some(RESPONSE_HEADERS_URI),
+            Err(_) => return 0,
+        };
+        if is_response_headers {
+            return match request_data::append_response_headers(&mut self.response_headers, &data) {
+                Ok(()) => data.len() as u64,
+                Err(e) => {
+                    tracing::warn!(
+                        "[Plugin] {:?} rejected response headers: {}",
+                        self.plugin_id,
+                        e
+                    );
+                    0
+                }
+            };
+        }
+
         if self.policy == SecurityPolicy::Plugin && !self.has_permission("file:write") {
             return 0;
         }
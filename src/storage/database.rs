@@ -141,6 +141,96 @@ pub(crate) fn initialize_pool(
              END
              WHERE file_path NOT LIKE '%://%';",
         ),
+        // 插件启用状态表（无记录视为启用）
+        M::up(
+            "CREATE TABLE IF NOT EXISTS sys_plugin_states (
+                plugin_id TEXT PRIMARY KEY,
+                enabled INTEGER NOT NULL DEFAULT 1,
+                updated_at TEXT DEFAULT CURRENT_TIMESTAMP
+            );",
+        ),
+        // 插件加载 / 热重载记录
+        M::up(
+            "CREATE TABLE IF NOT EXISTS sys_plugin_reloads (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                plugin_id TEXT,
+                source_uri TEXT NOT NULL,
+                version TEXT,
+                outcome TEXT NOT NULL,
+                error TEXT,
+                created_at TEXT DEFAULT CURRENT_TIMESTAMP
+            );
+            CREATE INDEX IF NOT EXISTS idx_plugin_reloads_plugin
+            ON sys_plugin_reloads(plugin_id, id);",
+        ),
+        // 签名校验通过的发布者
+        M::up("ALTER TABLE sys_plugin_metadata ADD COLUMN publisher TEXT;"),
+        // 插件迁移前快照
+        M::up(
+            "CREATE TABLE IF NOT EXISTS sys_plugin_snapshots (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                plugin_id TEXT NOT NULL,
+                version INTEGER NOT NULL,
+                created_at TEXT DEFAULT CURRENT_TIMESTAMP
+            );
+            CREATE INDEX IF NOT EXISTS idx_plugin_snapshots_plugin
+            ON sys_plugin_snapshots(plugin_id, id);
+            CREATE TABLE IF NOT EXISTS sys_plugin_snapshot_tables (
+                snapshot_id INTEGER NOT NULL,
+                table_name TEXT NOT NULL,
+                copy_name TEXT NOT NULL,
+                schema_sql TEXT NOT NULL,
+                index_sql TEXT NOT NULL DEFAULT '',
+                PRIMARY KEY (snapshot_id, table_name)
+            );",
+        ),
+        // 插件权限授予记录（granted / pending / denied）
+        M::up(
+            "CREATE TABLE IF NOT EXISTS sys_plugin_permissions (
+                plugin_id TEXT NOT NULL,
+                kind TEXT NOT NULL,
+                name TEXT NOT NULL,
+                status TEXT NOT NULL DEFAULT 'pending',
+                requested_at TEXT DEFAULT CURRENT_TIMESTAMP,
+                decided_at TEXT,
+                PRIMARY KEY (plugin_id, kind, name)
+            );",
+        ),
+        // 插件配置项（secret 为 1 时 value 为密文）
+        M::up(
+            "CREATE TABLE IF NOT EXISTS sys_plugin_config (
+                plugin_id TEXT NOT NULL,
+                key TEXT NOT NULL,
+                value TEXT NOT NULL,
+                secret INTEGER NOT NULL DEFAULT 0,
+                updated_at TEXT DEFAULT CURRENT_TIMESTAMP,
+                PRIMARY KEY (plugin_id, key)
+            );",
+        ),
+        // 插件包版本历史（每次加载的 manifest 版本、内容哈希与结果）
+        M::up(
+            "CREATE TABLE IF NOT EXISTS sys_plugin_version_history (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                plugin_id TEXT NOT NULL,
+                version TEXT NOT NULL,
+                content_hash TEXT NOT NULL,
+                outcome TEXT NOT NULL,
+                error TEXT,
+                forced INTEGER NOT NULL DEFAULT 0,
+                loaded_at TEXT DEFAULT CURRENT_TIMESTAMP
+            );
+            CREATE INDEX IF NOT EXISTS idx_plugin_version_history_plugin
+            ON sys_plugin_version_history(plugin_id, id);",
+        ),
+        // 启用权限审批前已安装的插件：首次登记权限时直接授予当时声明的权限
+        M::up(
+            "CREATE TABLE IF NOT EXISTS sys_plugin_permission_grandfathered (
+                plugin_id TEXT PRIMARY KEY
+            );
+            INSERT OR IGNORE INTO sys_plugin_permission_grandfathered (plugin_id)
+            SELECT plugin_id FROM sys_plugin_installations
+            WHERE plugin_id NOT IN (SELECT plugin_id FROM sys_plugin_permissions);",
+        ),
     ]);
 
     if let Err(e) = migrations.to_latest(&mut conn) {
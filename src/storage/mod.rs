@@ -1,5 +1,10 @@
 pub mod database;
 pub mod jobs;
+pub mod plugin_config;
+pub mod plugin_history;
+pub mod plugin_permissions;
+pub mod plugin_reloads;
+pub mod plugin_snapshots;
 pub mod plugins;
 pub mod scan_roots;
 pub mod videos;
@@ -84,6 +89,10 @@ impl VtxVideoRegistry {
         plugins::verify_installation(&self.pool, plugin_id, current_uri)
     }
 
+    pub fn get_installation(&self, plugin_id: &str) -> anyhow::Result<Option<String>> {
+        plugins::get_installation(&self.pool, plugin_id)
+    }
+
     pub fn release_installation(&self, plugin_id: &str) -> anyhow::Result<()> {
         plugins::release_installation(&self.pool, plugin_id)
     }
@@ -96,6 +105,151 @@ impl VtxVideoRegistry {
         plugins::set_plugin_metadata(&self.pool, plugin_id, meta)
     }
 
+    pub fn set_plugin_publisher(
+        &self,
+        plugin_id: &str,
+        publisher: Option<&str>,
+    ) -> anyhow::Result<()> {
+        plugins::set_plugin_publisher(&self.pool, plugin_id, publisher)
+    }
+
+    pub fn get_plugin_publisher(&self, plugin_id: &str) -> anyhow::Result<Option<String>> {
+        plugins::get_plugin_publisher(&self.pool, plugin_id)
+    }
+
+    pub fn set_plugin_enabled(&self, plugin_id: &str, enabled: bool) -> anyhow::Result<()> {
+        plugins::set_plugin_enabled(&self.pool, plugin_id, enabled)
+    }
+
+    pub fn is_plugin_enabled(&self, plugin_id: &str) -> anyhow::Result<bool> {
+        plugins::is_plugin_enabled(&self.pool, plugin_id)
+    }
+
+    pub fn record_plugin_reload(
+        &self,
+        plugin_id: Option<&str>,
+        source_uri: &str,
+        version: Option<&str>,
+        outcome: &str,
+        error: Option<&str>,
+    ) -> anyhow::Result<()> {
+        plugin_reloads::record_plugin_reload(
+            &self.pool, plugin_id, source_uri, version, outcome, error,
+        )
+    }
+
+    pub fn list_plugin_reloads(
+        &self,
+        plugin_id: &str,
+        limit: i64,
+    ) -> anyhow::Result<Vec<plugin_reloads::PluginReloadRecord>> {
+        plugin_reloads::list_plugin_reloads(&self.pool, plugin_id, limit)
+    }
+
+    pub fn record_plugin_history(
+        &self,
+        plugin_id: &str,
+        version: &str,
+        content_hash: &str,
+        outcome: &str,
+        error: Option<&str>,
+        forced: bool,
+    ) -> anyhow::Result<()> {
+        plugin_history::record_plugin_history(
+            &self.pool,
+            plugin_id,
+            version,
+            content_hash,
+            outcome,
+            error,
+            forced,
+        )
+    }
+
+    pub fn list_plugin_history(
+        &self,
+        plugin_id: &str,
+        limit: i64,
+    ) -> anyhow::Result<Vec<plugin_history::PluginVersionRecord>> {
+        plugin_history::list_plugin_history(&self.pool, plugin_id, limit)
+    }
+
+    pub fn installed_plugin_version(
+        &self,
+        plugin_id: &str,
+    ) -> anyhow::Result<Option<plugin_history::PluginVersionRecord>> {
+        plugin_history::installed_plugin_version(&self.pool, plugin_id)
+    }
+
+    pub fn list_plugin_config(
+        &self,
+        plugin_id: &str,
+    ) -> anyhow::Result<Vec<plugin_config::PluginConfigRow>> {
+        plugin_config::list_plugin_config(&self.pool, plugin_id)
+    }
+
+    pub fn set_plugin_config(
+        &self,
+        plugin_id: &str,
+        key: &str,
+        value: &str,
+        secret: bool,
+    ) -> anyhow::Result<()> {
+        plugin_config::set_plugin_config(&self.pool, plugin_id, key, value, secret)
+    }
+
+    pub fn delete_plugin_config(&self, plugin_id: &str, key: &str) -> anyhow::Result<bool> {
+        plugin_config::delete_plugin_config(&self.pool, plugin_id, key)
+    }
+
+    pub fn sync_plugin_permissions(
+        &self,
+        plugin_id: &str,
+        requested: &[(&str, String)],
+    ) -> anyhow::Result<Vec<plugin_permissions::PluginPermissionRecord>> {
+        plugin_permissions::sync_requested_permissions(&self.pool, plugin_id, requested)
+    }
+
+    pub fn list_plugin_permissions(
+        &self,
+        plugin_id: &str,
+    ) -> anyhow::Result<Vec<plugin_permissions::PluginPermissionRecord>> {
+        plugin_permissions::list_plugin_permissions(&self.pool, plugin_id)
+    }
+
+    pub fn decide_plugin_permissions(
+        &self,
+        plugin_id: &str,
+        kind: &str,
+        names: Option<&[String]>,
+        status: &str,
+    ) -> anyhow::Result<usize> {
+        plugin_permissions::decide_plugin_permissions(&self.pool, plugin_id, kind, names, status)
+    }
+
+    pub fn create_plugin_snapshot(&self, plugin_id: &str) -> anyhow::Result<i64> {
+        plugin_snapshots::create_snapshot(&self.pool, plugin_id)
+    }
+
+    pub fn list_plugin_snapshots(
+        &self,
+        plugin_id: &str,
+    ) -> anyhow::Result<Vec<plugin_snapshots::PluginSnapshot>> {
+        plugin_snapshots::list_snapshots(&self.pool, plugin_id)
+    }
+
+    pub fn restore_plugin_snapshot(
+        &self,
+        plugin_id: &str,
+        snapshot_id: i64,
+    ) -> anyhow::Result<Option<i64>> {
+        plugin_snapshots::restore_snapshot(&self.pool, plugin_id, snapshot_id)
+    }
+
+    pub fn prune_plugin_snapshots(&self, plugin_id: &str, keep: usize) -> anyhow::Result<usize> {
+        plugin_snapshots::prune_snapshots(&self.pool, plugin_id, keep)
+    }
+
     pub fn nuke_plugin(&self, plugin_name: &str) -> anyhow::Result<usize> {
         plugins::nuke_plugin(&self.pool, plugin_name)
     }
@@ -0,0 +1,66 @@
+use r2d2::Pool;
+use r2d2_sqlite::SqliteConnectionManager;
+use rusqlite::params;
+
+/// 插件配置项的存储形式（secret 条目的 value 为密文）
+#[derive(Debug, Clone)]
+pub struct PluginConfigRow {
+    pub key: String,
+    pub value: String,
+    pub secret: bool,
+    pub updated_at: String,
+}
+
+pub(crate) fn list_plugin_config(
+    pool: &Pool<SqliteConnectionManager>,
+    plugin_id: &str,
+) -> anyhow::Result<Vec<PluginConfigRow>> {
+    let conn = pool.get()?;
+    let mut stmt = conn.prepare(
+        "SELECT key, value, secret, updated_at
+         FROM sys_plugin_config WHERE plugin_id = ?1 ORDER BY key",
+    )?;
+    let rows = stmt.query_map([plugin_id], |row| {
+        Ok(PluginConfigRow {
+            key: row.get(0)?,
+            value: row.get(1)?,
+            secret: row.get::<_, i64>(2)? != 0,
+            updated_at: row.get(3)?,
+        })
+    })?;
+    Ok(rows.filter_map(Result::ok).collect())
+}
+
+pub(crate) fn set_plugin_config(
+    pool: &Pool<SqliteConnectionManager>,
+    plugin_id: &str,
+    key: &str,
+    value: &str,
+    secret: bool,
+) -> anyhow::Result<()> {
+    let conn = pool.get()?;
+    conn.execute(
+        "INSERT INTO sys_plugin_config (plugin_id, key, value, secret, updated_at)
+         VALUES (?1, ?2, ?3, ?4, CURRENT_TIMESTAMP)
+         ON CONFLICT(plugin_id, key) DO UPDATE SET
+            value = excluded.value,
+            secret = excluded.secret,
+            updated_at = CURRENT_TIMESTAMP",
+        params![plugin_id, key, value, secret as i64],
+    )?;
+    Ok(())
+}
+
+/// 删除配置项，返回是否存在
+pub(crate) fn delete_plugin_config(
+    pool: &Pool<SqliteConnectionManager>,
+    plugin_id: &str,
+    key: &str,
+) -> anyhow::Result<bool> {
+    let conn = pool.get()?;
+    let deleted = conn.execute(
+        "DELETE FROM sys_plugin_config WHERE plugin_id = ?1 AND key = ?2",
+        params![plugin_id, key],
+    )?;
+    Ok(deleted > 0)
+}
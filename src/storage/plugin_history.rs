@@ -0,0 +1,102 @@
+use r2d2::Pool;
+use r2d2_sqlite::SqliteConnectionManager;
+use rusqlite::{params, OptionalExtension};
+use serde::Serialize;
+
+pub const OUTCOME_ACTIVATED: &str = "activated";
+pub const OUTCOME_REJECTED: &str = "rejected";
+pub const OUTCOME_ROLLED_BACK: &str = "rolled_back";
+pub const OUTCOME_UNINSTALLED: &str = "uninstalled";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PluginVersionRecord {
+    pub id: i64,
+    /// manifest 声明的版本
+    pub version: String,
+    /// 插件包内容的 SHA-256（十六进制）
+    pub content_hash: String,
+    pub outcome: String,
+    pub error: Option<String>,
+    /// 是否由管理员强制越过降级保护
+    pub forced: bool,
+    pub loaded_at: String,
+}
+
+/// 记录一次插件包版本的加载结果
+pub(crate) fn record_plugin_history(
+    pool: &Pool<SqliteConnectionManager>,
+    plugin_id: &str,
+    version: &str,
+    content_hash: &str,
+    outcome: &str,
+    error: Option<&str>,
+    forced: bool,
+) -> anyhow::Result<()> {
+    let conn = pool.get()?;
+    conn.execute(
+        "INSERT INTO sys_plugin_version_history
+            (plugin_id, version, content_hash, outcome, error, forced)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![plugin_id, version, content_hash, outcome, error, forced],
+    )?;
+    Ok(())
+}
+
+/// 按时间倒序列出插件的版本历史
+pub(crate) fn list_plugin_history(
+    pool: &Pool<SqliteConnectionManager>,
+    plugin_id: &str,
+    limit: i64,
+) -> anyhow::Result<Vec<PluginVersionRecord>> {
+    let conn = pool.get()?;
+    let mut stmt = conn.prepare(
+        "SELECT id, version, content_hash, outcome, error, forced, loaded_at
+         FROM sys_plugin_version_history WHERE plugin_id = ?1 ORDER BY id DESC LIMIT ?2",
+    )?;
+    let rows = stmt.query_map(params![plugin_id, limit], |row| {
+        Ok(PluginVersionRecord {
+            id: row.get(0)?,
+            version: row.get(1)?,
+            content_hash: row.get(2)?,
+            outcome: row.get(3)?,
+            error: row.get(4)?,
+            forced: row.get(5)?,
+            loaded_at: row.get(6)?,
+        })
+    })?;
+    Ok(rows.filter_map(Result::ok).collect())
+}
+
+/// 当前安装的版本：最近一次成功激活或回滚到的版本记录，彻底卸载后为 `None`
+pub(crate) fn installed_plugin_version(
+    pool: &Pool<SqliteConnectionManager>,
+    plugin_id: &str,
+) -> anyhow::Result<Option<PluginVersionRecord>> {
+    let conn = pool.get()?;
+    let latest = conn
+        .query_row(
+            "SELECT id, version, content_hash, outcome, error, forced, loaded_at
+             FROM sys_plugin_version_history
+             WHERE plugin_id = ?1 AND outcome IN (?2, ?3, ?4)
+             ORDER BY id DESC LIMIT 1",
+            params![
+                plugin_id,
+                OUTCOME_ACTIVATED,
+                OUTCOME_ROLLED_BACK,
+                OUTCOME_UNINSTALLED
+            ],
+            |row| {
+                Ok(PluginVersionRecord {
+                    id: row.get(0)?,
+                    version: row.get(1)?,
+                    content_hash: row.get(2)?,
+                    outcome: row.get(3)?,
+                    error: row.get(4)?,
+                    forced: row.get(5)?,
+                    loaded_at: row.get(6)?,
+                })
+            },
+        )
+        .optional()?;
+    Ok(latest.filter(|record| record.outcome != OUTCOME_UNINSTALLED))
+}
@@ -0,0 +1,112 @@
+use r2d2::Pool;
+use r2d2_sqlite::SqliteConnectionManager;
+use rusqlite::params;
+use serde::Serialize;
+
+pub const STATUS_GRANTED: &str = "granted";
+pub const STATUS_PENDING: &str = "pending";
+pub const STATUS_DENIED: &str = "denied";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PluginPermissionRecord {
+    /// `permission` 或 `http`
+    pub kind: String,
+    pub name: String,
+    pub status: String,
+    pub requested_at: String,
+    pub decided_at: Option<String>,
+}
+
+/// 登记插件声明的权限，尚无记录的条目以 pending 状态写入，已有决定保持不变
+///
+/// 启用权限审批前就已安装的插件在首次登记时直接授予当时声明的权限，
+/// 之后更新新增的权限仍需审批。
+pub(crate) fn sync_requested_permissions(
+    pool: &Pool<SqliteConnectionManager>,
+    plugin_id: &str,
+    requested: &[(&str, String)],
+) -> anyhow::Result<Vec<PluginPermissionRecord>> {
+    let mut conn = pool.get()?;
+    let tx = conn.transaction()?;
+    let grandfathered = tx.execute(
+        "DELETE FROM sys_plugin_permission_grandfathered WHERE plugin_id = ?1",
+        [plugin_id],
+    )? > 0;
+    for (kind, name) in requested {
+        if grandfathered {
+            tx.execute(
+                "INSERT OR IGNORE INTO sys_plugin_permissions
+                 (plugin_id, kind, name, status, decided_at)
+                 VALUES (?1, ?2, ?3, ?4, CURRENT_TIMESTAMP)",
+                params![plugin_id, kind, name, STATUS_GRANTED],
+            )?;
+        } else {
+            tx.execute(
+                "INSERT OR IGNORE INTO sys_plugin_permissions (plugin_id, kind, name, status)
+                 VALUES (?1, ?2, ?3, ?4)",
+                params![plugin_id, kind, name, STATUS_PENDING],
+            )?;
+        }
+    }
+    tx.commit()?;
+    drop(conn);
+    list_plugin_permissions(pool, plugin_id)
+}
+
+pub(crate) fn list_plugin_permissions(
+    pool: &Pool<SqliteConnectionManager>,
+    plugin_id: &str,
+) -> anyhow::Result<Vec<PluginPermissionRecord>> {
+    let conn = pool.get()?;
+    let mut stmt = conn.prepare(
+        "SELECT kind, name, status, requested_at, decided_at
+         FROM sys_plugin_permissions WHERE plugin_id = ?1 ORDER BY kind, name",
+    )?;
+    let rows = stmt.query_map([plugin_id], |row| {
+        Ok(PluginPermissionRecord {
+            kind: row.get(0)?,
+            name: row.get(1)?,
+            status: row.get(2)?,
+            requested_at: row.get(3)?,
+            decided_at: row.get(4)?,
+        })
+    })?;
+    Ok(rows.filter_map(Result::ok).collect())
+}
+
+/// 更新权限状态；`names` 为 `None` 时作用于该类别下全部 pending 条目
+///
+/// 返回实际更新的条目数。
+pub(crate) fn decide_plugin_permissions(
+    pool: &Pool<SqliteConnectionManager>,
+    plugin_id: &str,
+    kind: &str,
+    names: Option<&[String]>,
+    status: &str,
+) -> anyhow::Result<usize> {
+    let mut conn = pool.get()?;
+    let tx = conn.transaction()?;
+    let mut updated = 0;
+    match names {
+        Some(names) => {
+            for name in names {
+                updated += tx.execute(
+                    "UPDATE sys_plugin_permissions
+                     SET status = ?4, decided_at = CURRENT_TIMESTAMP
+                     WHERE plugin_id = ?1 AND kind = ?2 AND name = ?3",
+                    params![plugin_id, kind, name, status],
+                )?;
+            }
+        }
+        None => {
+            updated += tx.execute(
+                "UPDATE sys_plugin_permissions
+                 SET status = ?3, decided_at = CURRENT_TIMESTAMP
+                 WHERE plugin_id = ?1 AND kind = ?2 AND status = ?4",
+                params![plugin_id, kind, status, STATUS_PENDING],
+            )?;
+        }
+    }
+    tx.commit()?;
+    Ok(updated)
+}
@@ -0,0 +1,58 @@
+use r2d2::Pool;
+use r2d2_sqlite::SqliteConnectionManager;
+use rusqlite::params;
+use serde::Serialize;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PluginReloadRecord {
+    pub id: i64,
+    pub plugin_id: Option<String>,
+    pub source_uri: String,
+    pub version: Option<String>,
+    pub outcome: String,
+    pub error: Option<String>,
+    pub created_at: String,
+}
+
+/// 记录一次插件加载 / 热重载尝试
+pub(crate) fn record_plugin_reload(
+    pool: &Pool<SqliteConnectionManager>,
+    plugin_id: Option<&str>,
+    source_uri: &str,
+    version: Option<&str>,
+    outcome: &str,
+    error: Option<&str>,
+) -> anyhow::Result<()> {
+    let conn = pool.get()?;
+    conn.execute(
+        "INSERT INTO sys_plugin_reloads (plugin_id, source_uri, version, outcome, error)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![plugin_id, source_uri, version, outcome, error],
+    )?;
+    Ok(())
+}
+
+/// 按时间倒序列出插件的加载记录
+pub(crate) fn list_plugin_reloads(
+    pool: &Pool<SqliteConnectionManager>,
+    plugin_id: &str,
+    limit: i64,
+) -> anyhow::Result<Vec<PluginReloadRecord>> {
+    let conn = pool.get()?;
+    let mut stmt = conn.prepare(
+        "SELECT id, plugin_id, source_uri, version, outcome, error, created_at
+         FROM sys_plugin_reloads WHERE plugin_id = ?1 ORDER BY id DESC LIMIT ?2",
+    )?;
+    let rows = stmt.query_map(params![plugin_id, limit], |row| {
+        Ok(PluginReloadRecord {
+            id: row.get(0)?,
+            plugin_id: row.get(1)?,
+            source_uri: row.get(2)?,
+            version: row.get(3)?,
+            outcome: row.get(4)?,
+            error: row.get(5)?,
+            created_at: row.get(6)?,
+        })
+    })?;
+    Ok(rows.filter_map(Result::ok).collect())
+}
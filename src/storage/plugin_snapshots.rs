@@ -0,0 +1,266 @@
+use r2d2::Pool;
+use r2d2_sqlite::SqliteConnectionManager;
+use rusqlite::{params, OptionalExtension, Transaction};
+use serde::Serialize;
+use tracing::{info, warn};
+
+/// 快照表名前缀（不在任何插件的可访问命名空间内）
+const SNAPSHOT_TABLE_PREFIX: &str = "__vtx_snap_";
+
+/// 插件数据快照
+#[derive(Debug, Clone, Serialize)]
+pub struct PluginSnapshot {
+    pub id: i64,
+    pub plugin_id: String,
+    /// 快照时的迁移版本计数
+    pub version: i64,
+    pub tables: Vec<String>,
+    pub created_at: String,
+}
+
+/// 为插件已注册的表创建快照（表结构、索引、触发器与数据）
+pub(crate) fn create_snapshot(
+    pool: &Pool<SqliteConnectionManager>,
+    plugin_id: &str,
+) -> anyhow::Result<i64> {
+    let mut conn = pool.get()?;
+    let tx = conn.transaction()?;
+
+    let version: i64 = tx
+        .query_row(
+            "SELECT version FROM sys_plugin_versions WHERE plugin_name = ?1",
+            [plugin_id],
+            |row| row.get(0),
+        )
+        .optional()?
+        .unwrap_or(0);
+    tx.execute(
+        "INSERT INTO sys_plugin_snapshots (plugin_id, version) VALUES (?1, ?2)",
+        params![plugin_id, version],
+    )?;
+    let snapshot_id = tx.last_insert_rowid();
+
+    for table in plugin_tables(&tx, plugin_id)? {
+        let Some(schema_sql) = object_sql(&tx, "table", &table)? else {
+            continue;
+        };
+        let index_sql = dependent_sql(&tx, &table)?;
+        let copy_name = format!("{}{}_{}", SNAPSHOT_TABLE_PREFIX, snapshot_id, table);
+        tx.execute_batch(&format!(
+            "CREATE TABLE \"{}\" AS SELECT * FROM \"{}\";",
+            copy_name, table
+        ))?;
+        tx.execute(
+            "INSERT INTO sys_plugin_snapshot_tables
+                (snapshot_id, table_name, copy_name, schema_sql, index_sql)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![snapshot_id, table, copy_name, schema_sql, index_sql],
+        )?;
+    }
+
+    tx.commit()?;
+    info!(
+        "[Snapshot] Created snapshot #{} for plugin '{}' at version {}",
+        snapshot_id, plugin_id, version
+    );
+    Ok(snapshot_id)
+}
+
+/// 列出插件的快照（新到旧）
+pub(crate) fn list_snapshots(
+    pool: &Pool<SqliteConnectionManager>,
+    plugin_id: &str,
+) -> anyhow::Result<Vec<PluginSnapshot>> {
+    let conn = pool.get()?;
+    let mut stmt = conn.prepare(
+        "SELECT id, plugin_id, version, created_at FROM sys_plugin_snapshots
+         WHERE plugin_id = ?1 ORDER BY id DESC",
+    )?;
+    let mut snapshots: Vec<PluginSnapshot> = stmt
+        .query_map([plugin_id], |row| {
+            Ok(PluginSnapshot {
+                id: row.get(0)?,
+                plugin_id: row.get(1)?,
+                version: row.get(2)?,
+                tables: Vec::new(),
+                created_at: row.get(3)?,
+            })
+        })?
+        .collect::<Result<_, _>>()?;
+
+    let mut tables_stmt = conn.prepare(
+        "SELECT table_name FROM sys_plugin_snapshot_tables WHERE snapshot_id = ?1 ORDER BY table_name",
+    )?;
+    for snapshot in &mut snapshots {
+        snapshot.tables = tables_stmt
+            .query_map([snapshot.id], |row| row.get(0))?
+            .collect::<Result<_, _>>()?;
+    }
+    Ok(snapshots)
+}
+
+/// 将插件的表与迁移版本恢复到指定快照
+///
+/// 快照之后新建的表会被删除并注销。返回 `Ok(None)` 表示快照不存在或不属于该插件。
+pub(crate) fn restore_snapshot(
+    pool: &Pool<SqliteConnectionManager>,
+    plugin_id: &str,
+    snapshot_id: i64,
+) -> anyhow::Result<Option<i64>> {
+    let mut conn = pool.get()?;
+    let tx = conn.transaction()?;
+
+    let version: Option<i64> = tx
+        .query_row(
+            "SELECT version FROM sys_plugin_snapshots WHERE id = ?1 AND plugin_id = ?2",
+            params![snapshot_id, plugin_id],
+            |row| row.get(0),
+        )
+        .optional()?;
+    let Some(version) = version else {
+        return Ok(None);
+    };
+
+    let saved: Vec<(String, String, String, String)> = {
+        let mut stmt = tx.prepare(
+            "SELECT table_name, copy_name, schema_sql, index_sql
+             FROM sys_plugin_snapshot_tables WHERE snapshot_id = ?1",
+        )?;
+        let rows = stmt.query_map([snapshot_id], |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+        })?;
+        rows.collect::<Result<_, _>>()?
+    };
+
+    for table in plugin_tables(&tx, plugin_id)? {
+        tx.execute_batch(&format!("DROP TABLE IF EXISTS \"{}\";", table))?;
+    }
+    tx.execute(
+        "DELETE FROM sys_plugin_resources WHERE plugin_name = ?1 AND resource_type = 'TABLE'",
+        [plugin_id],
+    )?;
+
+    for (table, copy_name, schema_sql, index_sql) in &saved {
+        tx.execute_batch(schema_sql)?;
+        tx.execute_batch(&format!(
+            "INSERT INTO \"{}\" SELECT * FROM \"{}\";",
+            table, copy_name
+        ))?;
+        // 数据写回后再创建索引与触发器，避免触发器在恢复时执行
+        if !index_sql.is_empty() {
+            tx.execute_batch(index_sql)?;
+        }
+        tx.execute(
+            "INSERT OR IGNORE INTO sys_plugin_resources (plugin_name, resource_type, resource_name)
+             VALUES (?1, 'TABLE', ?2)",
+            params![plugin_id, table],
+        )?;
+    }
+
+    tx.execute(
+        "INSERT INTO sys_plugin_versions (plugin_name, version)
+         VALUES (?1, ?2)
+         ON CONFLICT(plugin_name) DO UPDATE
+         SET version = ?2, updated_at = CURRENT_TIMESTAMP",
+        params![plugin_id, version],
+    )?;
+
+    tx.commit()?;
+    warn!(
+        "[Snapshot] Restored plugin '{}' to snapshot #{} (version {})",
+        plugin_id, snapshot_id, version
+    );
+    Ok(Some(version))
+}
+
+/// 仅保留最近 `keep` 个快照，返回删除的数量
+pub(crate) fn prune_snapshots(
+    pool: &Pool<SqliteConnectionManager>,
+    plugin_id: &str,
+    keep: usize,
+) -> anyhow::Result<usize> {
+    let conn = pool.get()?;
+    let expired: Vec<i64> = {
+        let mut stmt = conn.prepare(
+            "SELECT id FROM sys_plugin_snapshots WHERE plugin_id = ?1
+             ORDER BY id DESC LIMIT -1 OFFSET ?2",
+        )?;
+        let rows = stmt.query_map(
+            params![plugin_id, i64::try_from(keep).unwrap_or(i64::MAX)],
+            |row| row.get(0),
+        )?;
+        rows.collect::<Result<_, _>>()?
+    };
+    for snapshot_id in &expired {
+        drop_snapshot(&conn, *snapshot_id)?;
+    }
+    Ok(expired.len())
+}
+
+/// 删除插件的全部快照
+pub(crate) fn delete_snapshots(
+    pool: &Pool<SqliteConnectionManager>,
+    plugin_id: &str,
+) -> anyhow::Result<()> {
+    prune_snapshots(pool, plugin_id, 0).map(|_| ())
+}
+
+fn drop_snapshot(conn: &rusqlite::Connection, snapshot_id: i64) -> anyhow::Result<()> {
+    let copies: Vec<String> = {
+        let mut stmt = conn
+            .prepare("SELECT copy_name FROM sys_plugin_snapshot_tables WHERE snapshot_id = ?1")?;
+        let rows = stmt.query_map([snapshot_id], |row| row.get(0))?;
+        rows.collect::<Result<_, _>>()?
+    };
+    for copy in copies {
+        conn.execute_batch(&format!("DROP TABLE IF EXISTS \"{}\";", copy))?;
+    }
+    conn.execute(
+        "DELETE FROM sys_plugin_snapshot_tables WHERE snapshot_id = ?1",
+        [snapshot_id],
+    )?;
+    conn.execute(
+        "DELETE FROM sys_plugin_snapshots WHERE id = ?1",
+        [snapshot_id],
+    )?;
+    Ok(())
+}
+
+fn plugin_tables(tx: &Transaction<'_>, plugin_id: &str) -> anyhow::Result<Vec<String>> {
+    let mut stmt = tx.prepare(
+        "SELECT resource_name FROM sys_plugin_resources
+         WHERE plugin_name = ?1 AND resource_type = 'TABLE'",
+    )?;
+    let rows = stmt.query_map([plugin_id], |row| row.get::<_, String>(0))?;
+    let tables: Vec<String> = rows.collect::<Result<_, _>>()?;
+    Ok(tables
+        .into_iter()
+        .filter(|t| t.chars().all(|c| c.is_alphanumeric() || c == '_'))
+        .collect())
+}
+
+fn object_sql(tx: &Transaction<'_>, kind: &str, name: &str) -> anyhow::Result<Option<String>> {
+    Ok(tx
+        .query_row(
+            "SELECT sql FROM sqlite_master WHERE type = ?1 AND name = ?2",
+            params![kind, name],
+            |row| row.get(0),
+        )
+        .optional()?)
+}
+
+/// 依附于表的索引与触发器定义
+fn dependent_sql(tx: &Transaction<'_>, table: &str) -> anyhow::Result<String> {
+    let mut stmt = tx.prepare(
+        "SELECT sql FROM sqlite_master
+         WHERE type IN ('index', 'trigger') AND tbl_name = ?1 AND sql IS NOT NULL
+         ORDER BY type",
+    )?;
+    let rows = stmt.query_map([table], |row| row.get::<_, String>(0))?;
+    let statements: Vec<String> = rows.collect::<Result<_, _>>()?;
+    Ok(statements
+        .iter()
+        .map(|sql| format!("{};", sql))
+        .collect::<Vec<_>>()
+        .join("\n"))
+}
@@ -108,6 +108,22 @@ pub(crate) fn verify_installation(
     }
 }
 
+/// 查询插件当前锁定的安装路径
+pub(crate) fn get_installation(
+    pool: &Pool<SqliteConnectionManager>,
+    plugin_id: &str,
+) -> anyhow::Result<Option<String>> {
+    let conn = pool.get()?;
+    let path = conn
+        .query_row(
+            "SELECT file_path FROM sys_plugin_installations WHERE plugin_id = ?1",
+            [plugin_id],
+            |row| row.get(0),
+        )
+        .optional()?;
+    Ok(path)
+}
+
 /// 释放安装锁
 pub(crate) fn release_installation(
     pool: &Pool<SqliteConnectionManager>,
@@ -144,6 +160,24 @@ pub(crate) fn nuke_plugin(
         }
     }
 
+    // 视图不登记为资源，按命名空间前缀清理
+    let prefix = crate::runtime::manager::migration_policy::plugin_prefix(plugin_name);
+    let mut stmt = conn.prepare(
+        "SELECT name FROM sqlite_master WHERE type = 'view' AND substr(name, 1, length(?1)) = ?1",
+    )?;
+    let views: Vec<String> = stmt
+        .query_map([&prefix], |row| row.get(0))?
+        .filter_map(Result::ok)
+        .collect();
+    drop(stmt);
+
+    for view in views {
+        warn!("[Uninstall] Dropping view: {}", view);
+        if view.chars().all(|c| c.is_alphanumeric() || c == '_') {
+            conn.execute(&format!("DROP VIEW IF EXISTS {}", view), [])?;
+        }
+    }
+
     conn.execute(
         "DELETE FROM sys_plugin_resources WHERE plugin_name = ?1",
         [plugin_name],
@@ -157,10 +191,61 @@ pub(crate) fn nuke_plugin(
         "DELETE FROM sys_plugin_metadata WHERE plugin_id = ?1",
         [plugin_name],
     );
+    let _ = conn.execute(
+        "DELETE FROM sys_plugin_states WHERE plugin_id = ?1",
+        [plugin_name],
+    );
+    conn.execute(
+        "DELETE FROM sys_plugin_permissions WHERE plugin_id = ?1",
+        [plugin_name],
+    )?;
+    conn.execute(
+        "DELETE FROM sys_plugin_permission_grandfathered WHERE plugin_id = ?1",
+        [plugin_name],
+    )?;
+    conn.execute(
+        "DELETE FROM sys_plugin_config WHERE plugin_id = ?1",
+        [plugin_name],
+    )?;
+    drop(conn);
+    super::plugin_snapshots::delete_snapshots(pool, plugin_name)?;
 
     Ok(1)
 }
 
+/// 设置插件启用状态
+pub(crate) fn set_plugin_enabled(
+    pool: &Pool<SqliteConnectionManager>,
+    plugin_id: &str,
+    enabled: bool,
+) -> anyhow::Result<()> {
+    let conn = pool.get()?;
+    conn.execute(
+        "INSERT INTO sys_plugin_states (plugin_id, enabled)
+         VALUES (?1, ?2)
+         ON CONFLICT(plugin_id) DO UPDATE
+         SET enabled = ?2, updated_at = CURRENT_TIMESTAMP",
+        params![plugin_id, enabled],
+    )?;
+    Ok(())
+}
+
+/// 查询插件是否启用（无记录视为启用）
+pub(crate) fn is_plugin_enabled(
+    pool: &Pool<SqliteConnectionManager>,
+    plugin_id: &str,
+) -> anyhow::Result<bool> {
+    let conn = pool.get()?;
+    let enabled: Option<bool> = conn
+        .query_row(
+            "SELECT enabled FROM sys_plugin_states WHERE plugin_id = ?1",
+            [plugin_id],
+            |row| row.get(0),
+        )
+        .optional()?;
+    Ok(enabled.unwrap_or(true))
+}
+
 pub(crate) fn set_plugin_metadata(
     pool: &Pool<SqliteConnectionManager>,
     plugin_id: &str,
@@ -191,3 +276,36 @@ pub(crate) fn set_plugin_metadata(
     )?;
     Ok(())
 }
+
+/// 记录签名校验通过的发布者（未签名时清空）
+pub(crate) fn set_plugin_publisher(
+    pool: &Pool<SqliteConnectionManager>,
+    plugin_id: &str,
+    publisher: Option<&str>,
+) -> anyhow::Result<()> {
+    let conn = pool.get()?;
+    conn.execute(
+        "INSERT INTO sys_plugin_metadata (plugin_id, publisher, updated_at)
+        VALUES (?1, ?2, CURRENT_TIMESTAMP)
+        ON CONFLICT(plugin_id) DO UPDATE SET
+            publisher = excluded.publisher,
+            updated_at = CURRENT_TIMESTAMP",
+        params![plugin_id, publisher],
+    )?;
+    Ok(())
+}
+
+pub(crate) fn get_plugin_publisher(
+    pool: &Pool<SqliteConnectionManager>,
+    plugin_id: &str,
+) -> anyhow::Result<Option<String>> {
+    let conn = pool.get()?;
+    let publisher: Option<Option<String>> = conn
+        .query_row(
+            "SELECT publisher FROM sys_plugin_metadata WHERE plugin_id = ?1",
+            [plugin_id],
+            |row| row.get(0),
+        )
+        .optional()?;
+    Ok(publisher.flatten())
+}
@@ -4,7 +4,9 @@ use anyhow::Context;
 use bytes::Bytes;
 use futures_util::stream::BoxStream;
 use futures_util::StreamExt;
-use object_store::{GetOptions, GetRange, ObjectMeta, ObjectStoreExt, Result as ObjectStoreResult};
+use object_store::{
+    GetOptions, GetRange, ObjectMeta, ObjectStoreExt, PutPayload, Result as ObjectStoreResult,
+};
 
 use super::super::entry::VtxVfsStoreEntry;
 use super::super::utils::to_range;
@@ -94,6 +96,30 @@ impl VtxVfsManager {
         Ok(Box::pin(mapped))
     }
 
+    pub async fn put_object(&self, uri: &str, bytes: Bytes) -> anyhow::Result<()> {
+        let resolved = self.resolve(uri)?;
+        let location = resolved
+            .location
+            .clone()
+            .context("URI must point to an object")?;
+        resolved
+            .entry
+            .store
+            .put(&location, PutPayload::from(bytes))
+            .await?;
+        Ok(())
+    }
+
+    pub async fn delete_object(&self, uri: &str) -> anyhow::Result<()> {
+        let resolved = self.resolve(uri)?;
+        let location = resolved
+            .location
+            .clone()
+            .context("URI must point to an object")?;
+        resolved.entry.store.delete(&location).await?;
+        Ok(())
+    }
+
     fn meta_to_object(
         &self,
         entry: &VtxVfsStoreEntry,
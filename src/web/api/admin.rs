@@ -1,9 +1,19 @@
 use crate::runtime::job_registry;
+use crate::runtime::manager::permissions::PermissionDecision;
+use crate::runtime::manager::plugin_config::ConfigRejection;
+use crate::runtime::manager::PluginRejection;
 use crate::runtime::vtx_host_impl::api::vtx_auth_types::UserContext;
+use crate::web::api::ws;
 use crate::web::state::AppState;
 use crate::web::utils::errors;
 use axum::{
-    extract::{Extension, Json, Path, Query, State},
+    body::Bytes,
+    extract::{
+        ws::{rejection::WebSocketUpgradeRejection, WebSocketUpgrade},
+        Extension, Json, Path, Query, State,
+    },
+    http::HeaderMap,
+    response::{IntoResponse, Response},
     Json as AxumJson,
 };
 use serde::Deserialize;
@@ -11,6 +21,9 @@ use std::path::Path as StdPath;
 use std::sync::Arc;
 use url::Url;
 
+/// 携带 base64 编码独立签名的请求头
+const SIGNATURE_HEADER: &str = "x-vtx-signature";
+
 #[derive(Deserialize)]
 pub struct ScanRequest {
     pub path: String,
@@ -27,6 +40,21 @@ pub struct UninstallParams {
     pub keep_data: bool,
 }
 
+#[derive(Deserialize)]
+pub struct InstallPluginParams {
+    pub file_name: Option<String>,
+    /// 允许安装低于当前版本的包
+    #[serde(default)]
+    pub force: bool,
+}
+
+#[derive(Deserialize)]
+pub struct PluginConfigRequest {
+    pub value: String,
+    #[serde(default)]
+    pub secret: bool,
+}
+
 #[derive(Deserialize)]
 pub struct JobSubmitRequest {
     pub job_type: String,
@@ -35,6 +63,19 @@ pub struct JobSubmitRequest {
     pub payload_version: Option<i64>,
 }
 
+#[derive(Deserialize)]
+pub struct PluginLogsParams {
+    pub limit: Option<usize>,
+    /// 只返回该序号之后的行
+    pub after: Option<u64>,
+    /// 为 true 且以 WebSocket 连接时持续推送新输出
+    #[serde(default)]
+    pub follow: bool,
+}
+
+/// 单次查询返回的最大行数
+const MAX_LOG_LINES: usize = 1000;
+
 #[derive(Deserialize)]
 pub struct JobListParams {
     pub limit: Option<i64>,
@@ -125,6 +166,387 @@ pub async fn uninstall_handler(
     }
 }
 
+/// 启用插件接口
+pub async fn enable_plugin_handler(
+    State(state): State<Arc<AppState>>,
+    Path(plugin_id): Path<String>,
+) -> AxumJson<serde_json::Value> {
+    set_plugin_enabled(&state, &plugin_id, true)
+}
+
+/// 停用插件接口（保留安装锁与数据）
+pub async fn disable_plugin_handler(
+    State(state): State<Arc<AppState>>,
+    Path(plugin_id): Path<String>,
+) -> AxumJson<serde_json::Value> {
+    set_plugin_enabled(&state, &plugin_id, false)
+}
+
+fn set_plugin_enabled(
+    state: &AppState,
+    plugin_id: &str,
+    enabled: bool,
+) -> AxumJson<serde_json::Value> {
+    match state.plugin_manager.set_enabled(plugin_id, enabled) {
+        Ok(Some(status)) => AxumJson(success_json(status)),
+        Ok(None) => AxumJson(errors::admin_not_found_json("Plugin not found")),
+        Err(e) => AxumJson(plugin_rejection_json(&e)),
+    }
+}
+
+/// 重置插件熔断器接口
+pub async fn reset_plugin_breaker_handler(
+    State(state): State<Arc<AppState>>,
+    Path(plugin_id): Path<String>,
+) -> AxumJson<serde_json::Value> {
+    match state.plugin_manager.reset_breaker(&plugin_id) {
+        Some(status) => AxumJson(success_json(status)),
+        None => AxumJson(errors::admin_not_found_json("Plugin not found")),
+    }
+}
+
+/// 插件权限授予记录
+pub async fn list_plugin_permissions_handler(
+    State(state): State<Arc<AppState>>,
+    Path(plugin_id): Path<String>,
+) -> AxumJson<serde_json::Value> {
+    match state.plugin_manager.plugin_permissions(&plugin_id) {
+        Ok(Some(records)) => AxumJson(success_with_count(records, "count")),
+        Ok(None) => AxumJson(errors::admin_not_found_json("Plugin not found")),
+        Err(e) => AxumJson(errors::admin_internal_error_json(&e.to_string())),
+    }
+}
+
+/// 插件配置列表（secret 不返回值）
+pub async fn list_plugin_config_handler(
+    State(state): State<Arc<AppState>>,
+    Path(plugin_id): Path<String>,
+) -> AxumJson<serde_json::Value> {
+    match state.plugin_manager.plugin_config(&plugin_id) {
+        Ok(Some(entries)) => AxumJson(success_with_count(entries, "count")),
+        Ok(None) => AxumJson(errors::admin_not_found_json("Plugin not found")),
+        Err(e) => AxumJson(errors::admin_internal_error_json(&e.to_string())),
+    }
+}
+
+/// 写入插件配置项，无需重新加载插件即生效
+pub async fn set_plugin_config_handler(
+    State(state): State<Arc<AppState>>,
+    Path((plugin_id, key)): Path<(String, String)>,
+    Json(payload): Json<PluginConfigRequest>,
+) -> AxumJson<serde_json::Value> {
+    match state
+        .plugin_manager
+        .set_plugin_config(&plugin_id, &key, &payload.value, payload.secret)
+    {
+        Ok(Some(())) => AxumJson(success_json(serde_json::json!({
+            "key": key,
+            "secret": payload.secret,
+        }))),
+        Ok(None) => AxumJson(errors::admin_not_found_json("Plugin not found")),
+        Err(e) => match e.downcast_ref::<ConfigRejection>() {
+            Some(rejection) => AxumJson(errors::admin_bad_request_json(&rejection.to_string())),
+            None => AxumJson(errors::admin_internal_error_json(&e.to_string())),
+        },
+    }
+}
+
+/// 删除插件配置项
+pub async fn delete_plugin_config_handler(
+    State(state): State<Arc<AppState>>,
+    Path((plugin_id, key)): Path<(String, String)>,
+) -> AxumJson<serde_json::Value> {
+    match state.plugin_manager.remove_plugin_config(&plugin_id, &key) {
+        Ok(Some(true)) => AxumJson(success_json(serde_json::json!({ "key": key }))),
+        Ok(Some(false)) => AxumJson(errors::admin_not_found_json("Config key not found")),
+        Ok(None) => AxumJson(errors::admin_not_found_json("Plugin not found")),
+        Err(e) => AxumJson(errors::admin_internal_error_json(&e.to_string())),
+    }
+}
+
+/// 查询某个请求由哪个鉴权插件处理（按 `x-request-id`）
+pub async fn auth_trace_handler(
+    State(state): State<Arc<AppState>>,
+    Path(request_id): Path<String>,
+) -> AxumJson<serde_json::Value> {
+    match state.plugin_manager.auth_trace(&request_id) {
+        Some(trace) => AxumJson(success_json(trace)),
+        None => AxumJson(errors::admin_not_found_json(
+            "No auth record for this request ID",
+        )),
+    }
+}
+
+/// 清空鉴权结果缓存
+pub async fn flush_auth_cache_handler(
+    State(state): State<Arc<AppState>>,
+) -> AxumJson<serde_json::Value> {
+    let flushed = state.plugin_manager.flush_auth_cache();
+    AxumJson(success_json(serde_json::json!({ "flushed": flushed })))
+}
+
+/// 查看插件捕获的 stdout / stderr
+///
+/// `follow=true` 且以 WebSocket 连接时，先推送已缓冲的行，再实时推送新输出。
+pub async fn plugin_logs_handler(
+    State(state): State<Arc<AppState>>,
+    Path(plugin_id): Path<String>,
+    Query(params): Query<PluginLogsParams>,
+    ws: Result<WebSocketUpgrade, WebSocketUpgradeRejection>,
+) -> Response {
+    let limit = params.limit.unwrap_or(200).min(MAX_LOG_LINES);
+    let Some(lines) = state
+        .plugin_manager
+        .recent_plugin_logs(&plugin_id, limit, params.after)
+    else {
+        return AxumJson(errors::admin_not_found_json("Plugin not found")).into_response();
+    };
+
+    match ws {
+        Ok(ws) if params.follow => {
+            let logs = state.plugin_manager.plugin_logs();
+            ws.on_upgrade(move |socket| ws::follow_plugin_logs(socket, logs, plugin_id, lines))
+        }
+        _ => AxumJson(success_with_count(lines, "count")).into_response(),
+    }
+}
+
+/// 批准插件的权限请求（请求体为空时批准全部 pending 条目）
+pub async fn approve_plugin_permissions_handler(
+    State(state): State<Arc<AppState>>,
+    Path(plugin_id): Path<String>,
+    body: Bytes,
+) -> AxumJson<serde_json::Value> {
+    decide_plugin_permissions(&state, &plugin_id, &body, true)
+}
+
+/// 拒绝或撤销插件的权限（请求体为空时拒绝全部 pending 条目）
+pub async fn deny_plugin_permissions_handler(
+    State(state): State<Arc<AppState>>,
+    Path(plugin_id): Path<String>,
+    body: Bytes,
+) -> AxumJson<serde_json::Value> {
+    decide_plugin_permissions(&state, &plugin_id, &body, false)
+}
+
+fn decide_plugin_permissions(
+    state: &AppState,
+    plugin_id: &str,
+    body: &[u8],
+    approve: bool,
+) -> AxumJson<serde_json::Value> {
+    let decision = if body.is_empty() {
+        PermissionDecision::default()
+    } else {
+        match serde_json::from_slice::<PermissionDecision>(body) {
+            Ok(decision) => decision,
+            Err(e) => {
+                return AxumJson(errors::admin_bad_request_json(&format!(
+                    "Invalid permission decision: {}",
+                    e
+                )))
+            }
+        }
+    };
+    match state
+        .plugin_manager
+        .decide_permissions(plugin_id, decision, approve)
+    {
+        Ok(Some(records)) => AxumJson(success_with_count(records, "count")),
+        Ok(None) => AxumJson(errors::admin_not_found_json("Plugin not found")),
+        Err(e) => AxumJson(errors::admin_internal_error_json(&e.to_string())),
+    }
+}
+
+/// 插件重载历史接口
+pub async fn list_plugin_reloads_handler(
+    State(state): State<Arc<AppState>>,
+    Path(plugin_id): Path<String>,
+    Query(params): Query<JobListParams>,
+) -> AxumJson<serde_json::Value> {
+    let limit = params.limit.unwrap_or(50).max(1);
+    match state.plugin_manager.list_reloads(&plugin_id, limit) {
+        Ok(records) => AxumJson(success_with_count(records, "count")),
+        Err(e) => AxumJson(errors::admin_internal_error_json(&e.to_string())),
+    }
+}
+
+/// 插件包版本历史
+pub async fn list_plugin_versions_handler(
+    State(state): State<Arc<AppState>>,
+    Path(plugin_id): Path<String>,
+    Query(params): Query<JobListParams>,
+) -> AxumJson<serde_json::Value> {
+    let limit = params.limit.unwrap_or(50).max(1);
+    match state.plugin_manager.list_versions(&plugin_id, limit) {
+        Ok(records) => AxumJson(success_with_count(records, "count")),
+        Err(e) => AxumJson(errors::admin_internal_error_json(&e.to_string())),
+    }
+}
+
+/// 回滚插件到上一个成功激活的版本
+pub async fn rollback_plugin_handler(
+    State(state): State<Arc<AppState>>,
+    Path(plugin_id): Path<String>,
+) -> AxumJson<serde_json::Value> {
+    match state.plugin_manager.rollback(&plugin_id).await {
+        Ok(Some(status)) => AxumJson(success_json(status)),
+        Ok(None) => AxumJson(errors::admin_not_found_json(
+            "No previous version available for rollback",
+        )),
+        Err(e) => AxumJson(plugin_rejection_json(&e)),
+    }
+}
+
+/// 列出插件的迁移前数据快照
+pub async fn list_plugin_snapshots_handler(
+    State(state): State<Arc<AppState>>,
+    Path(plugin_id): Path<String>,
+) -> AxumJson<serde_json::Value> {
+    match state.plugin_manager.list_snapshots(&plugin_id) {
+        Ok(snapshots) => AxumJson(success_with_count(snapshots, "count")),
+        Err(e) => AxumJson(errors::admin_internal_error_json(&e.to_string())),
+    }
+}
+
+/// 将插件数据表与迁移版本恢复到指定快照
+pub async fn restore_plugin_snapshot_handler(
+    State(state): State<Arc<AppState>>,
+    Path((plugin_id, snapshot_id)): Path<(String, i64)>,
+) -> AxumJson<serde_json::Value> {
+    match state
+        .plugin_manager
+        .restore_snapshot(&plugin_id, snapshot_id)
+    {
+        Ok(Some(version)) => AxumJson(success_json(serde_json::json!({
+            "plugin_id": plugin_id,
+            "snapshot_id": snapshot_id,
+            "version": version,
+        }))),
+        Ok(None) => AxumJson(errors::admin_not_found_json("Snapshot not found")),
+        Err(e) => AxumJson(errors::admin_internal_error_json(&e.to_string())),
+    }
+}
+
+/// 编译缓存统计接口
+pub async fn component_cache_stats_handler(
+    State(state): State<Arc<AppState>>,
+) -> AxumJson<serde_json::Value> {
+    AxumJson(success_json(state.plugin_manager.component_cache_stats()))
+}
+
+/// 实例池统计接口（对比复用与冷实例化的延迟）
+pub async fn instance_pool_stats_handler(
+    State(state): State<Arc<AppState>>,
+) -> AxumJson<serde_json::Value> {
+    AxumJson(success_json(state.plugin_manager.instance_pool_stats()))
+}
+
+/// 插件燃料消耗统计接口
+pub async fn fuel_usage_handler(State(state): State<Arc<AppState>>) -> AxumJson<serde_json::Value> {
+    match state.plugin_manager.fuel_usage() {
+        Some(usage) => AxumJson(success_json(usage)),
+        None => AxumJson(errors::admin_not_found_json("Fuel metering is disabled")),
+    }
+}
+
+/// 清空编译缓存接口
+pub async fn purge_component_cache_handler(
+    State(state): State<Arc<AppState>>,
+) -> AxumJson<serde_json::Value> {
+    match state.plugin_manager.purge_component_cache() {
+        Ok(removed) => AxumJson(success_json(serde_json::json!({ "removed": removed }))),
+        Err(e) => AxumJson(errors::admin_internal_error_json(&e.to_string())),
+    }
+}
+
+/// 上传并安装插件接口
+///
+/// 请求体为 `.vtx` 包内容；可选 `file_name` 指定写入插件根目录的文件名，
+/// 可选 `x-vtx-signature` 请求头携带独立签名。
+pub async fn install_plugin_handler(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<InstallPluginParams>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> AxumJson<serde_json::Value> {
+    if body.is_empty() {
+        return AxumJson(errors::admin_bad_request_json("Empty plugin package"));
+    }
+    match state
+        .plugin_manager
+        .install_package(
+            params.file_name.as_deref(),
+            body,
+            detached_signature(&headers),
+            params.force,
+        )
+        .await
+    {
+        Ok(status) => AxumJson(success_json(status)),
+        Err(e) => {
+            tracing::warn!("[Admin] Plugin install rejected: {}", e);
+            AxumJson(plugin_rejection_json(&e))
+        }
+    }
+}
+
+/// 插件包预检接口（dry-run）
+///
+/// 执行完整的解析、编译与迁移校验流程，但不注册插件、不执行迁移。
+pub async fn validate_plugin_handler(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> AxumJson<serde_json::Value> {
+    if body.is_empty() {
+        return AxumJson(errors::admin_bad_request_json("Empty plugin package"));
+    }
+    let signature = detached_signature(&headers);
+    match state
+        .plugin_manager
+        .validate_package(&body, signature.as_deref())
+        .await
+    {
+        Ok(report) => AxumJson(success_json(report)),
+        Err(e) => AxumJson(plugin_rejection_json(&e)),
+    }
+}
+
+fn detached_signature(headers: &HeaderMap) -> Option<Bytes> {
+    headers
+        .get(SIGNATURE_HEADER)
+        .map(|value| Bytes::copy_from_slice(value.as_bytes()))
+}
+
+fn plugin_rejection_json(err: &anyhow::Error) -> serde_json::Value {
+    let Some(rejection) = err.downcast_ref::<PluginRejection>() else {
+        return errors::admin_internal_error_json(&err.to_string());
+    };
+    let message = rejection.to_string();
+    let mut value = match rejection {
+        PluginRejection::InvalidPackage { .. } | PluginRejection::MigrationRejected { .. } => {
+            errors::admin_bad_request_json(&message)
+        }
+        PluginRejection::RouteConflict { .. }
+        | PluginRejection::InstallationLocked { .. }
+        | PluginRejection::DependencyUnresolved { .. }
+        | PluginRejection::Downgrade { .. }
+        | PluginRejection::MigrationsBehind { .. } => errors::admin_conflict_json(&message),
+        PluginRejection::MigrationFailed { .. } => errors::admin_internal_error_json(&message),
+        PluginRejection::SignatureRejected { .. } | PluginRejection::NoWritableRoot => {
+            errors::admin_forbidden_json(&message)
+        }
+    };
+    if let serde_json::Value::Object(ref mut map) = value {
+        map.insert(
+            "rejection".to_string(),
+            serde_json::to_value(rejection).unwrap_or_default(),
+        );
+    }
+    value
+}
+
 pub async fn list_scan_roots_handler(
     State(state): State<Arc<AppState>>,
 ) -> AxumJson<serde_json::Value> {
@@ -1,8 +1,13 @@
-use crate::runtime::executor::VtxPluginExecutor;
+use crate::runtime::context::CurrentUser;
+use crate::runtime::executor::{ExecutionError, PluginResponse, VtxPluginExecutor};
+use crate::runtime::manager::route_policy::RoutePolicy;
+use crate::runtime::request_data::RequestData;
+use crate::web::middleware::auth::{attach_request_id, authenticate, request_id};
 use crate::web::{state::AppState, utils::errors, utils::streaming::StreamProtocolLayer};
 use axum::{
+    body::Bytes,
     extract::State,
-    http::{HeaderMap, Method, StatusCode, Uri},
+    http::{header, HeaderMap, Method, StatusCode, Uri},
     response::{IntoResponse, Response},
     Json,
 };
@@ -13,12 +18,33 @@ use std::sync::Arc;
 /// 职责：
 /// 1. 拦截所有非系统路由的请求
 /// 2. 在 PluginManager 中匹配最长前缀路由
-/// 3. 将请求转发给对应的插件执行
+/// 3. 按插件的路由策略鉴权，未通过时返回 401 / 403
+/// 4. 将请求转发给对应的插件执行（连续失败的插件被熔断时返回 503）
+///    白名单内的请求头与请求体（上限 `max_request_body_mb`）随调用转发
+/// 5. 合并插件设置的响应头（Content-Length、Content-Range 等仍由宿主管理）
+///
+/// 熔断放行后的所有退出路径都会提交调用结果；请求被取消时由凭据释放探测名额。
+/// 响应附带服务端生成的 `x-request-id`，用于查询鉴权记录与插件输出。
 pub async fn gateway_handler(
     State(state): State<Arc<AppState>>,
     method: Method,
     headers: HeaderMap,
     uri: Uri,
+    body: Bytes,
+) -> Response {
+    let request_id = request_id(&headers);
+    let mut response = dispatch(&state, method, &headers, &uri, body, request_id.clone()).await;
+    attach_request_id(&mut response, &request_id);
+    response
+}
+
+async fn dispatch(
+    state: &Arc<AppState>,
+    method: Method,
+    headers: &HeaderMap,
+    uri: &Uri,
+    body: Bytes,
+    request_id: String,
 ) -> Response {
     let path = uri.path();
     let query = uri.query().unwrap_or("").to_string();
@@ -37,26 +63,81 @@ pub async fn gateway_handler(
         }
     };
 
-    // 2. 执行插件
+    // 2. 路由鉴权（请求 ID 用于关联鉴权记录与插件输出）
+    let plugin_id = plugin_runtime.id.clone();
+    let policy = state.plugin_manager.route_policy(&plugin_runtime);
+    let current_user = match authorize(state, &policy, headers, &request_id).await {
+        Ok(user) => user,
+        Err(response) => {
+            tracing::warn!(
+                "[Gateway] '{}' denied by route policy {} (request {})",
+                plugin_id,
+                policy,
+                request_id
+            );
+            return response;
+        }
+    };
+
+    // 3. 熔断检查（鉴权未通过的请求不占用探测名额）
+    let permit = match state.plugin_manager.admit_call(&plugin_id) {
+        Ok(permit) => permit,
+        Err(retry_after) => {
+            let retry_after_secs = retry_after.as_secs_f64().ceil().max(1.0) as u64;
+            return (
+                StatusCode::SERVICE_UNAVAILABLE,
+                [(header::RETRY_AFTER, retry_after_secs.to_string())],
+                Json(errors::plugin_unavailable_json(&format!(
+                    "Plugin '{}' is temporarily unavailable after repeated failures",
+                    plugin_id
+                ))),
+            )
+                .into_response();
+        }
+    };
+
+    // 4. 执行插件
+    let request = RequestData::new(headers, &state.config.plugins.request_headers, body);
     let result = VtxPluginExecutor::execute_runtime(
-        &state,
+        state,
         plugin_runtime,
         sub_path,
         method.to_string(), // 转换为 String 传给 WASM
         query,
-        None,
+        current_user,
+        request_id,
+        request,
     )
     .await;
+    permit.record(result.is_ok());
 
-    // 3. 处理响应
+    // 5. 处理响应（插件设置的响应头覆盖宿主的默认值）
     match result {
-        Ok((Some(buffer), status_code)) => {
-            StreamProtocolLayer::process(buffer, &headers, status_code, state.vfs.clone()).await
+        Ok(PluginResponse {
+            body,
+            status,
+            headers: plugin_headers,
+        }) => {
+            let mut response = match body {
+                Some(buffer) => {
+                    StreamProtocolLayer::process(buffer, headers, status, state.vfs.clone()).await
+                }
+                None => StatusCode::from_u16(status)
+                    .unwrap_or(StatusCode::INTERNAL_SERVER_ERROR)
+                    .into_response(),
+            };
+            StreamProtocolLayer::apply_plugin_headers(&mut response, plugin_headers);
+            response
+        }
+        Err(err @ ExecutionError::DeadlineExceeded { .. }) => {
+            tracing::error!("[Gateway] Execution timed out: {}", err);
+            (
+                StatusCode::GATEWAY_TIMEOUT,
+                Json(errors::plugin_timeout_json(&err.to_string())),
+            )
+                .into_response()
         }
-        Ok((None, status_code)) => StatusCode::from_u16(status_code)
-            .unwrap_or(StatusCode::INTERNAL_SERVER_ERROR)
-            .into_response(),
-        Err(msg) => {
+        Err(ExecutionError::Failed(msg) | ExecutionError::Rejected(msg)) => {
             tracing::error!("[Gateway] Execution failed: {}", msg);
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
@@ -66,3 +147,64 @@ pub async fn gateway_handler(
         }
     }
 }
+
+/// 按路由策略鉴权，返回传给插件的当前用户
+async fn authorize(
+    state: &Arc<AppState>,
+    policy: &RoutePolicy,
+    headers: &HeaderMap,
+    request_id: &str,
+) -> Result<Option<CurrentUser>, Response> {
+    if *policy == RoutePolicy::Public {
+        return Ok(None);
+    }
+
+    let user = match authenticate(state, headers, request_id).await {
+        Ok(user) => user,
+        Err(401) if *policy == RoutePolicy::OptionalAuth => return Ok(None),
+        Err(401) => {
+            return Err((
+                StatusCode::UNAUTHORIZED,
+                Json(errors::plugin_unauthorized_json("Authentication required")),
+            )
+                .into_response())
+        }
+        Err(403) => {
+            return Err((
+                StatusCode::FORBIDDEN,
+                Json(errors::plugin_forbidden_json("Authentication rejected")),
+            )
+                .into_response())
+        }
+        Err(code) => {
+            let status = StatusCode::from_u16(code).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+            return Err((
+                status,
+                Json(errors::plugin_internal_error_json(&format!(
+                    "Authentication failed with status {}",
+                    code
+                ))),
+            )
+                .into_response());
+        }
+    };
+
+    if let RoutePolicy::RequiredGroup(group) = policy {
+        if !user.groups.iter().any(|g| g == group) {
+            return Err((
+                StatusCode::FORBIDDEN,
+                Json(errors::plugin_forbidden_json(&format!(
+                    "User is not a member of group '{}'",
+                    group
+                ))),
+            )
+                .into_response());
+        }
+    }
+
+    Ok(Some(CurrentUser {
+        user_id: user.user_id,
+        username: user.username,
+        groups: user.groups,
+    }))
+}
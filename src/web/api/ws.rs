@@ -1,4 +1,5 @@
 use crate::runtime::bus::EventBus;
+use crate::runtime::plugin_logs::{PluginLogLine, PluginLogs};
 use crate::web::state::AppState;
 use axum::extract::{
     ws::{Message, WebSocket, WebSocketUpgrade},
@@ -7,6 +8,7 @@ use axum::extract::{
 use axum::response::IntoResponse;
 use serde::Deserialize;
 use std::sync::Arc;
+use tokio::sync::broadcast::error::RecvError;
 use uuid::Uuid;
 
 #[derive(Deserialize)]
@@ -79,3 +81,62 @@ async fn handle_socket(mut socket: WebSocket, event_bus: Arc<EventBus>, topics:
 
     event_bus.unregister_plugin(&client_id).await;
 }
+
+/// 推送插件输出：先发送已缓冲的行，再转发该插件的新输出
+pub async fn follow_plugin_logs(
+    mut socket: WebSocket,
+    logs: Arc<PluginLogs>,
+    plugin_id: String,
+    backlog: Vec<PluginLogLine>,
+) {
+    // 先订阅再发送缓冲，避免两者之间的输出丢失；按序号去重
+    let mut rx = logs.subscribe();
+    let mut last_seq = 0;
+    for line in backlog {
+        last_seq = line.seq;
+        if !send_log_line(&mut socket, &line).await {
+            return;
+        }
+    }
+
+    loop {
+        tokio::select! {
+            line = rx.recv() => {
+                match line {
+                    Ok(line) if line.plugin_id == plugin_id && line.seq > last_seq => {
+                        last_seq = line.seq;
+                        if !send_log_line(&mut socket, &line).await {
+                            break;
+                        }
+                    }
+                    Ok(_) => {}
+                    Err(RecvError::Lagged(skipped)) => {
+                        tracing::warn!(
+                            "[WebSocket] Log follower of '{}' lagged, {} line(s) skipped",
+                            plugin_id,
+                            skipped
+                        );
+                    }
+                    Err(RecvError::Closed) => break,
+                }
+            }
+            msg = socket.recv() => {
+                match msg {
+                    Some(Ok(Message::Close(_))) | None => break,
+                    Some(Ok(_)) => {}
+                    Some(Err(_)) => break,
+                }
+            }
+        }
+    }
+}
+
+async fn send_log_line(socket: &mut WebSocket, line: &PluginLogLine) -> bool {
+    match serde_json::to_string(line) {
+        Ok(text) => socket.send(Message::Text(text.into())).await.is_ok(),
+        Err(err) => {
+            tracing::warn!("[WebSocket] Failed to serialize log line: {}", err);
+            true
+        }
+    }
+}
@@ -1,50 +1,103 @@
+use crate::runtime::plugin_logs::REQUEST_ID_HEADER;
+use crate::runtime::vtx_host_impl::api::vtx_auth_types::UserContext;
 use crate::web::state::AppState;
 use axum::{
     extract::{Request, State},
-    http::StatusCode,
+    http::{HeaderMap, HeaderValue, StatusCode},
     middleware::Next,
-    response::Response,
+    response::{IntoResponse, Response},
 };
 use std::sync::Arc;
 
 /// 插件鉴权中间件
 ///
 /// 职责：调用插件 `verify_identity` 方法进行身份验证。
+/// 每个请求都由服务端生成 `x-request-id`（覆盖调用方提供的值）并写回请求与响应，用于查询鉴权记录。
 /// 前置条件：必须在注册了 `AppState` 的路由组中使用。
 pub async fn auth_middleware(
     State(state): State<Arc<AppState>>,
-    req: Request,
+    mut req: Request,
     next: Next,
-) -> Result<Response, StatusCode> {
-    let headers = req.headers().clone();
-    let manager = state.plugin_manager.clone();
-
-    let handle = tokio::runtime::Handle::current();
-    let auth_result = tokio::task::spawn_blocking(move || {
-        handle.block_on(async { manager.verify_identity(&headers).await })
-    })
-    .await;
+) -> Response {
+    let request_id = request_id(req.headers());
+    if let Ok(value) = HeaderValue::from_str(&request_id) {
+        req.headers_mut().insert(REQUEST_ID_HEADER, value);
+    }
 
-    match auth_result {
-        Ok(plugin_result) => match plugin_result {
-            Ok(user_context) => {
-                tracing::debug!(
-                    "[Auth] Authenticated: {} (id={})",
-                    user_context.username,
-                    user_context.user_id
-                );
-                let mut req = req;
-                req.extensions_mut().insert(user_context);
-                Ok(next.run(req).await)
-            }
-            Err(code) => {
-                tracing::warn!("[Auth] Denied. Code={}", code);
-                Err(StatusCode::from_u16(code).unwrap_or(StatusCode::UNAUTHORIZED))
-            }
-        },
-        Err(e) => {
-            tracing::error!("[Auth] Thread failed: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+    let mut response = match authenticate(&state, req.headers(), &request_id).await {
+        Ok(user_context) => {
+            tracing::debug!(
+                "[Auth] Authenticated: {} (id={})",
+                user_context.username,
+                user_context.user_id
+            );
+            req.extensions_mut().insert(user_context);
+            next.run(req).await
+        }
+        Err(code) => {
+            tracing::warn!("[Auth] Denied. Code={} (request {})", code, request_id);
+            StatusCode::from_u16(code)
+                .unwrap_or(StatusCode::UNAUTHORIZED)
+                .into_response()
         }
+    };
+    attach_request_id(&mut response, &request_id);
+    response
+}
+
+/// 为请求生成服务端 ID
+///
+/// 鉴权记录与插件输出都按该 ID 索引，因此不采用调用方提供的 `x-request-id`，
+/// 避免调用方冒用或覆盖其他请求的记录；调用方的值只写入调试日志。
+pub fn request_id(headers: &HeaderMap) -> String {
+    let request_id = uuid::Uuid::new_v4().to_string();
+    if let Some(client_id) = headers.get(REQUEST_ID_HEADER).and_then(|v| v.to_str().ok()) {
+        tracing::debug!(
+            "[Auth] Request {} (client request id {})",
+            request_id,
+            client_id
+        );
     }
+    request_id
+}
+
+/// 在响应中返回服务端请求 ID，供调用方查询鉴权记录
+pub fn attach_request_id(response: &mut Response, request_id: &str) {
+    if let Ok(value) = HeaderValue::from_str(request_id) {
+        response.headers_mut().insert(REQUEST_ID_HEADER, value);
+    }
+}
+
+/// 通过鉴权链验证请求身份，失败时返回 HTTP 状态码
+///
+/// 缓存命中时不经过阻塞线程，也不实例化鉴权插件。
+pub async fn authenticate(
+    state: &Arc<AppState>,
+    headers: &HeaderMap,
+    request_id: &str,
+) -> Result<UserContext, u16> {
+    let cache_key = state.plugin_manager.auth_cache_key(headers);
+    if let Some(user_context) = state
+        .plugin_manager
+        .cached_identity(cache_key.as_ref(), request_id)
+    {
+        return Ok(user_context);
+    }
+
+    let headers = headers.clone();
+    let manager = state.plugin_manager.clone();
+    let request_id = request_id.to_string();
+    let handle = tokio::runtime::Handle::current();
+    tokio::task::spawn_blocking(move || {
+        handle.block_on(async {
+            manager
+                .verify_identity(&headers, cache_key, &request_id)
+                .await
+        })
+    })
+    .await
+    .unwrap_or_else(|e| {
+        tracing::error!("[Auth] Thread failed: {}", e);
+        Err(500)
+    })
 }
@@ -16,9 +16,15 @@ pub const CODE_CONFLICT: &str = "VTX-CORE-409";
 pub const CODE_ADMIN_INTERNAL: &str = "VTX-ADM-500";
 pub const CODE_ADMIN_BAD_REQUEST: &str = "VTX-ADM-400";
 pub const CODE_ADMIN_NOT_FOUND: &str = "VTX-ADM-404";
+pub const CODE_ADMIN_CONFLICT: &str = "VTX-ADM-409";
+pub const CODE_ADMIN_FORBIDDEN: &str = "VTX-ADM-403";
 
 pub const CODE_PLUGIN_INTERNAL: &str = "VTX-PLG-500";
+pub const CODE_PLUGIN_UNAUTHORIZED: &str = "VTX-PLG-401";
+pub const CODE_PLUGIN_FORBIDDEN: &str = "VTX-PLG-403";
 pub const CODE_PLUGIN_NOT_FOUND: &str = "VTX-PLG-404";
+pub const CODE_PLUGIN_UNAVAILABLE: &str = "VTX-PLG-503";
+pub const CODE_PLUGIN_TIMEOUT: &str = "VTX-PLG-504";
 
 #[allow(dead_code)]
 pub fn internal_error_json(details: &str) -> Value {
@@ -52,6 +58,14 @@ pub fn admin_not_found_json(details: &str) -> Value {
     error_json(CODE_ADMIN_NOT_FOUND, "Not found", Some(details))
 }
 
+pub fn admin_conflict_json(details: &str) -> Value {
+    error_json(CODE_ADMIN_CONFLICT, "Conflict", Some(details))
+}
+
+pub fn admin_forbidden_json(details: &str) -> Value {
+    error_json(CODE_ADMIN_FORBIDDEN, "Forbidden", Some(details))
+}
+
 pub fn plugin_internal_error_json(details: &str) -> Value {
     error_json(CODE_PLUGIN_INTERNAL, "Internal error", Some(details))
 }
@@ -60,6 +74,22 @@ pub fn plugin_not_found_json(details: &str) -> Value {
     error_json(CODE_PLUGIN_NOT_FOUND, "Not found", Some(details))
 }
 
+pub fn plugin_unauthorized_json(details: &str) -> Value {
+    error_json(CODE_PLUGIN_UNAUTHORIZED, "Unauthorized", Some(details))
+}
+
+pub fn plugin_forbidden_json(details: &str) -> Value {
+    error_json(CODE_PLUGIN_FORBIDDEN, "Forbidden", Some(details))
+}
+
+pub fn plugin_unavailable_json(details: &str) -> Value {
+    error_json(CODE_PLUGIN_UNAVAILABLE, "Plugin unavailable", Some(details))
+}
+
+pub fn plugin_timeout_json(details: &str) -> Value {
+    error_json(CODE_PLUGIN_TIMEOUT, "Plugin timed out", Some(details))
+}
+
 pub fn error_json(code: &str, safe_message: &str, details: Option<&str>) -> Value {
     let message = if cfg!(debug_assertions) {
         details.unwrap_or(safe_message)
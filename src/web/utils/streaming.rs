@@ -1,9 +1,10 @@
 use axum::{
     body::{Body, Bytes},
-    http::{header, HeaderMap, StatusCode},
+    http::{header, HeaderMap, HeaderName, HeaderValue, StatusCode},
     response::{IntoResponse, Response},
 };
 use futures_util::Stream;
+use std::collections::HashSet;
 use std::pin::Pin;
 use std::sync::Arc;
 use std::task::{Context, Poll};
@@ -32,6 +33,7 @@ impl Stream for ProcessStream {
 pub struct StreamProtocolLayer;
 
 impl StreamProtocolLayer {
+    /// 将插件返回的缓冲转换为 HTTP 响应
     pub async fn process(
         buffer: RealBuffer,
         headers: &HeaderMap,
@@ -60,6 +62,20 @@ impl StreamProtocolLayer {
         }
     }
 
+    /// 合并插件设置的响应头：同名头整体替换宿主默认值，重复的头（如 Set-Cookie）依次追加
+    ///
+    /// 受保护的头已在插件写入时拒绝（见 `request_data::append_response_headers`）。
+    pub fn apply_plugin_headers(response: &mut Response, headers: Vec<(HeaderName, HeaderValue)>) {
+        let target = response.headers_mut();
+        let mut replaced = HashSet::new();
+        for (name, value) in headers {
+            if replaced.insert(name.clone()) {
+                target.remove(&name);
+            }
+            target.append(name, value);
+        }
+    }
+
     async fn handle_pipe(
         stdout: ChildStdout,
         child: Option<Child>,
@@ -174,3 +174,212 @@ fn migration_policy_e2e_executes_rewritten_sql() {
         .unwrap_or(false);
     assert!(index_exists);
 }
+
+#[test]
+fn validate_rewrite_drop_table() {
+    let declared = declared_set("p1", &["items"]);
+    let rewritten = migration_policy::validate_and_rewrite_migration(
+        "p1",
+        &declared,
+        "DROP TABLE IF EXISTS items",
+    )
+    .expect("rewrite");
+
+    assert_eq!(rewritten, "DROP TABLE IF EXISTS vtx_plugin_p1_items");
+    assert!(
+        migration_policy::validate_and_rewrite_migration("p1", &declared, "DROP TABLE other")
+            .is_err()
+    );
+}
+
+#[test]
+fn validate_rewrite_alter_table_rename() {
+    let declared = declared_set("p1", &["items", "entries"]);
+    let rewritten = migration_policy::validate_and_rewrite_migration(
+        "p1",
+        &declared,
+        "ALTER TABLE items RENAME TO entries",
+    )
+    .expect("rewrite");
+    assert_eq!(
+        rewritten,
+        "ALTER TABLE vtx_plugin_p1_items RENAME TO vtx_plugin_p1_entries"
+    );
+
+    let rewritten = migration_policy::validate_and_rewrite_migration(
+        "p1",
+        &declared,
+        "ALTER TABLE items RENAME COLUMN title TO name",
+    )
+    .expect("rewrite");
+    assert_eq!(
+        rewritten,
+        "ALTER TABLE vtx_plugin_p1_items RENAME COLUMN title TO name"
+    );
+
+    assert!(migration_policy::validate_and_rewrite_migration(
+        "p1",
+        &declared,
+        "ALTER TABLE items RENAME TO sys_users",
+    )
+    .is_err());
+}
+
+#[test]
+fn validate_rewrite_create_view() {
+    let declared = declared_set("p1", &["items", "tags"]);
+    let rewritten = migration_policy::validate_and_rewrite_migration(
+        "p1",
+        &declared,
+        "CREATE VIEW IF NOT EXISTS tagged AS \
+         SELECT items.id, t.name FROM items JOIN tags t ON t.item_id = items.id \
+         WHERE items.id IN (SELECT item_id FROM tags)",
+    )
+    .expect("rewrite");
+
+    assert_eq!(
+        rewritten,
+        "CREATE VIEW IF NOT EXISTS vtx_plugin_p1_tagged AS \
+         SELECT vtx_plugin_p1_items.id, t.name FROM vtx_plugin_p1_items \
+         JOIN vtx_plugin_p1_tags t ON t.item_id = vtx_plugin_p1_items.id \
+         WHERE vtx_plugin_p1_items.id IN (SELECT item_id FROM vtx_plugin_p1_tags)"
+    );
+}
+
+#[test]
+fn validate_view_rejects_foreign_references() {
+    let declared = declared_set("p1", &["items"]);
+    let rejected = [
+        "CREATE VIEW v AS SELECT * FROM items, sys_users",
+        "CREATE VIEW v AS SELECT * FROM items WHERE id IN (SELECT id FROM videos)",
+        "CREATE VIEW v AS SELECT * FROM items WHERE id IN sys_users",
+        "CREATE VIEW v AS SELECT * FROM main.items",
+        "CREATE VIEW v AS SELECT * FROM (items JOIN videos)",
+        "CREATE VIEW v AS SELECT * FROM pragma_table_info('videos')",
+        "CREATE TEMP VIEW v AS SELECT * FROM items",
+        "CREATE TEMPORARY VIEW v AS SELECT * FROM items",
+    ];
+    for sql in rejected {
+        assert!(
+            migration_policy::validate_and_rewrite_migration("p1", &declared, sql).is_err(),
+            "{}",
+            sql
+        );
+    }
+}
+
+#[test]
+fn validate_rewrite_create_trigger() {
+    let declared = declared_set("p1", &["items", "audit"]);
+    let rewritten = migration_policy::validate_and_rewrite_migration(
+        "p1",
+        &declared,
+        "CREATE TRIGGER items_audit AFTER UPDATE OF name ON items FOR EACH ROW \
+         BEGIN INSERT INTO audit (item_id) VALUES (NEW.id); \
+         UPDATE OR IGNORE items SET touched = 1 WHERE id = OLD.id; END",
+    )
+    .expect("rewrite");
+
+    assert_eq!(
+        rewritten,
+        "CREATE TRIGGER vtx_plugin_p1_items_audit AFTER UPDATE OF name ON vtx_plugin_p1_items FOR EACH ROW \
+         BEGIN INSERT INTO vtx_plugin_p1_audit (item_id) VALUES (NEW.id); \
+         UPDATE OR IGNORE vtx_plugin_p1_items SET touched = 1 WHERE id = OLD.id; END"
+    );
+
+    assert!(migration_policy::validate_and_rewrite_migration(
+        "p1",
+        &declared,
+        "CREATE TRIGGER t AFTER INSERT ON items BEGIN DELETE FROM videos; END",
+    )
+    .is_err());
+    assert!(migration_policy::validate_and_rewrite_migration(
+        "p1",
+        &declared,
+        "CREATE TRIGGER t AFTER INSERT ON videos BEGIN DELETE FROM items; END",
+    )
+    .is_err());
+}
+
+#[test]
+fn validate_rewrite_drop_view_and_trigger() {
+    let declared = declared_set("p1", &["items"]);
+    let rewritten = migration_policy::validate_and_rewrite_migration(
+        "p1",
+        &declared,
+        "DROP VIEW IF EXISTS tagged",
+    )
+    .expect("rewrite");
+    assert_eq!(rewritten, "DROP VIEW IF EXISTS vtx_plugin_p1_tagged");
+
+    let rewritten =
+        migration_policy::validate_and_rewrite_migration("p1", &declared, "DROP TRIGGER audit")
+            .expect("rewrite");
+    assert_eq!(rewritten, "DROP TRIGGER vtx_plugin_p1_audit");
+}
+
+#[test]
+fn validate_rewrite_fts5_virtual_table() {
+    let declared = declared_set("p1", &["items", "items_fts"]);
+    let rewritten = migration_policy::validate_and_rewrite_migration(
+        "p1",
+        &declared,
+        "CREATE VIRTUAL TABLE items_fts USING fts5(title, content, content='items', content_rowid='id')",
+    )
+    .expect("rewrite");
+
+    assert_eq!(
+        rewritten,
+        "CREATE VIRTUAL TABLE vtx_plugin_p1_items_fts USING fts5(title, content, \
+         content='vtx_plugin_p1_items', content_rowid='id')"
+    );
+
+    let rejected = [
+        "CREATE VIRTUAL TABLE items_fts USING rtree(id, x0, x1)",
+        "CREATE VIRTUAL TABLE items_fts USING fts5(title, content=videos)",
+        "CREATE VIRTUAL TABLE items_fts USING fts5(title, content='sys_users')",
+        "CREATE VIRTUAL TABLE other_fts USING fts5(title)",
+    ];
+    for sql in rejected {
+        assert!(
+            migration_policy::validate_and_rewrite_migration("p1", &declared, sql).is_err(),
+            "{}",
+            sql
+        );
+    }
+}
+
+#[test]
+fn migration_policy_e2e_executes_extended_dialect() {
+    let declared = declared_set("p1", &["items", "audit", "notes", "items_fts"]);
+    let migrations = [
+        "CREATE TABLE items (id INTEGER PRIMARY KEY, name TEXT)",
+        "CREATE TABLE audit (item_id INTEGER)",
+        "CREATE TRIGGER items_audit AFTER INSERT ON items BEGIN INSERT INTO audit (item_id) VALUES (NEW.id); END",
+        "CREATE VIEW audited AS SELECT items.name FROM items JOIN audit ON audit.item_id = items.id",
+        "CREATE VIRTUAL TABLE items_fts USING fts5(name, content='items', content_rowid='id')",
+        "ALTER TABLE audit RENAME TO notes",
+        "DROP VIEW audited",
+        "CREATE VIEW audited AS SELECT items.name FROM items JOIN notes ON notes.item_id = items.id",
+    ];
+    let conn = rusqlite::Connection::open_in_memory().expect("conn");
+    for sql in migrations {
+        let rewritten =
+            migration_policy::validate_and_rewrite_migration("p1", &declared, sql).expect(sql);
+        conn.execute(&rewritten, []).expect(&rewritten);
+    }
+
+    conn.execute("INSERT INTO vtx_plugin_p1_items (name) VALUES ('clip')", [])
+        .expect("insert");
+    let name: String = conn
+        .query_row("SELECT name FROM vtx_plugin_p1_audited", [], |row| {
+            row.get(0)
+        })
+        .expect("view");
+    assert_eq!(name, "clip");
+
+    let rewritten =
+        migration_policy::validate_and_rewrite_migration("p1", &declared, "DROP TABLE items_fts")
+            .expect("rewrite");
+    conn.execute(&rewritten, []).expect("drop fts");
+}
@@ -1,7 +1,31 @@
+use base64::Engine as _;
+use ed25519_dalek::{Signer, SigningKey};
 use rusqlite::params;
 use tempfile::tempdir;
 use url::Url;
-use vtx_core::runtime::manager::VtxPackageMetadata;
+use vtx_core::config::{
+    AuthCacheSettings, AuthProviderSettings, BreakerSettings, ScratchSettings, SignatureMode,
+    SignatureSettings, TrustedKeySettings,
+};
+use vtx_core::runtime::manager::auth_cache::AuthCache;
+use vtx_core::runtime::manager::auth_chain::AUTH_CAPABILITY;
+use vtx_core::runtime::manager::breaker::{BreakerState, CircuitBreakers};
+use vtx_core::runtime::manager::component_cache::ComponentCache;
+use vtx_core::runtime::manager::dependencies::{load_order, parse_dependencies};
+use vtx_core::runtime::manager::instance_pool::{parse_stateless, InstancePool};
+use vtx_core::runtime::manager::permissions::{self, KIND_PERMISSION};
+use vtx_core::runtime::manager::plugin_config::{is_valid_key, PluginConfigStore};
+use vtx_core::runtime::manager::route_policy::{parse_overrides, parse_route_policy, RoutePolicy};
+use vtx_core::runtime::manager::scratch::{ScratchSpace, SCRATCH_PERMISSION};
+use vtx_core::runtime::manager::signature::{signed_payload, SignatureVerifier};
+use vtx_core::runtime::manager::versions::{check_downgrade, check_reload, content_hash};
+use vtx_core::runtime::manager::watcher::SelfWrites;
+use vtx_core::runtime::manager::PluginRejection;
+use vtx_core::runtime::manager::{PluginPolicy, VtxPackageMetadata};
+use vtx_core::runtime::plugin_logs::{LogStream, PluginLogs, PluginOutput};
+use vtx_core::storage::plugin_history::{
+    OUTCOME_ACTIVATED, OUTCOME_REJECTED, OUTCOME_ROLLED_BACK, OUTCOME_UNINSTALLED,
+};
 use vtx_core::storage::VtxVideoRegistry;
 
 fn make_registry() -> (tempfile::TempDir, VtxVideoRegistry) {
@@ -152,3 +176,1114 @@ fn nuke_plugin_skips_invalid_table_names() {
         .expect("list");
     assert!(resources.is_empty());
 }
+
+#[test]
+fn plugin_enabled_state_roundtrip() {
+    let (_guard, registry) = make_registry();
+    assert!(registry.is_plugin_enabled("plugin").expect("state"));
+
+    registry
+        .set_plugin_enabled("plugin", false)
+        .expect("disable");
+    assert!(!registry.is_plugin_enabled("plugin").expect("state"));
+
+    registry.set_plugin_enabled("plugin", true).expect("enable");
+    assert!(registry.is_plugin_enabled("plugin").expect("state"));
+
+    registry
+        .set_plugin_enabled("plugin", false)
+        .expect("disable");
+    registry.nuke_plugin("plugin").expect("nuke");
+    assert!(registry.is_plugin_enabled("plugin").expect("state"));
+}
+
+#[test]
+fn plugin_reload_history_is_newest_first() {
+    let (_guard, registry) = make_registry();
+    registry
+        .record_plugin_reload(
+            Some("plugin"),
+            "file:///plugins/plugin.vtx",
+            Some("1.0.0"),
+            "activated",
+            None,
+        )
+        .expect("record");
+    registry
+        .record_plugin_reload(
+            Some("plugin"),
+            "file:///plugins/plugin.vtx",
+            None,
+            "rejected",
+            Some("Smoke test get_manifest failed"),
+        )
+        .expect("record");
+    registry
+        .record_plugin_reload(
+            Some("other"),
+            "file:///plugins/other.vtx",
+            None,
+            "activated",
+            None,
+        )
+        .expect("record");
+
+    let records = registry.list_plugin_reloads("plugin", 10).expect("list");
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[0].outcome, "rejected");
+    assert_eq!(
+        records[0].error.as_deref(),
+        Some("Smoke test get_manifest failed")
+    );
+    assert_eq!(records[1].outcome, "activated");
+    assert_eq!(records[1].version.as_deref(), Some("1.0.0"));
+
+    let limited = registry.list_plugin_reloads("plugin", 1).expect("list");
+    assert_eq!(limited.len(), 1);
+}
+
+fn make_engine() -> wasmtime::Engine {
+    let mut config = wasmtime::Config::new();
+    config.wasm_component_model(true);
+    config.async_support(true);
+    wasmtime::Engine::new(&config).expect("engine")
+}
+
+#[test]
+fn component_cache_hits_after_first_compile() {
+    let temp_dir = tempdir().expect("tempdir");
+    let engine = make_engine();
+    let cache = ComponentCache::new(&engine, Some(temp_dir.path().join("cache"))).expect("cache");
+
+    cache
+        .load_or_compile(&engine, b"(component)")
+        .expect("compile");
+    cache
+        .load_or_compile(&engine, b"(component)")
+        .expect("cached");
+
+    let stats = cache.stats();
+    assert!(stats.enabled);
+    assert_eq!(stats.compiles, 1);
+    assert_eq!(stats.hits, 1);
+    assert_eq!(stats.entries, 1);
+
+    assert_eq!(cache.purge().expect("purge"), 1);
+    assert_eq!(cache.stats().entries, 0);
+}
+
+#[test]
+fn component_cache_discards_corrupted_entry() {
+    let temp_dir = tempdir().expect("tempdir");
+    let cache_dir = temp_dir.path().join("cache");
+    let engine = make_engine();
+    let cache = ComponentCache::new(&engine, Some(cache_dir.clone())).expect("cache");
+
+    cache
+        .load_or_compile(&engine, b"(component)")
+        .expect("compile");
+    let entry = std::fs::read_dir(&cache_dir)
+        .expect("read_dir")
+        .next()
+        .expect("entry")
+        .expect("entry")
+        .path();
+    let mut data = std::fs::read(&entry).expect("read");
+    let last = data.len() - 1;
+    data[last] ^= 0xff;
+    std::fs::write(&entry, data).expect("write");
+
+    cache
+        .load_or_compile(&engine, b"(component)")
+        .expect("recompile");
+    let stats = cache.stats();
+    assert_eq!(stats.hits, 0);
+    assert_eq!(stats.compiles, 2);
+    assert_eq!(stats.invalidated, 1);
+    assert_eq!(stats.entries, 1);
+}
+
+#[test]
+fn component_cache_rejects_forged_entry() {
+    use sha2::{Digest, Sha256};
+
+    let temp_dir = tempdir().expect("tempdir");
+    let cache_dir = temp_dir.path().join("cache");
+    let engine = make_engine();
+    let cache = ComponentCache::new(&engine, Some(cache_dir.clone())).expect("cache");
+    // 密钥保存在缓存目录之外
+    let key_path = temp_dir.path().join("cache.key");
+    assert!(key_path.is_file());
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(&key_path)
+            .expect("meta")
+            .permissions()
+            .mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    cache
+        .load_or_compile(&engine, b"(component)")
+        .expect("compile");
+    let entry = std::fs::read_dir(&cache_dir)
+        .expect("read_dir")
+        .next()
+        .expect("entry")
+        .expect("entry")
+        .path();
+    // 只能写缓存目录的一方按无密钥摘要伪造条目
+    let payload = std::fs::read(&entry).expect("read")[32..].to_vec();
+    let mut forged = Sha256::digest(&payload).to_vec();
+    forged.extend_from_slice(&payload);
+    std::fs::write(&entry, forged).expect("write");
+
+    cache
+        .load_or_compile(&engine, b"(component)")
+        .expect("recompile");
+    let stats = cache.stats();
+    assert_eq!(stats.hits, 0);
+    assert_eq!(stats.invalidated, 1);
+
+    // 同一安装的新实例沿用密钥，可以命中缓存
+    let reopened = ComponentCache::new(&engine, Some(cache_dir)).expect("cache");
+    reopened
+        .load_or_compile(&engine, b"(component)")
+        .expect("cached");
+    assert_eq!(reopened.stats().hits, 1);
+}
+
+#[test]
+fn component_cache_disabled_always_compiles() {
+    let engine = make_engine();
+    let cache = ComponentCache::new(&engine, None).expect("cache");
+    cache
+        .load_or_compile(&engine, b"(component)")
+        .expect("compile");
+    cache
+        .load_or_compile(&engine, b"(component)")
+        .expect("compile");
+
+    let stats = cache.stats();
+    assert!(!stats.enabled);
+    assert_eq!(stats.compiles, 2);
+    assert_eq!(cache.purge().expect("purge"), 0);
+}
+
+fn signature_settings(mode: SignatureMode, key: &SigningKey) -> SignatureSettings {
+    SignatureSettings {
+        mode,
+        trusted_keys: vec![TrustedKeySettings {
+            publisher: "vtxdeo".to_string(),
+            public_key: base64::engine::general_purpose::STANDARD
+                .encode(key.verifying_key().to_bytes()),
+        }],
+    }
+}
+
+#[test]
+fn signature_verifier_modes() {
+    let trusted = SigningKey::from_bytes(&[7u8; 32]);
+    let untrusted = SigningKey::from_bytes(&[9u8; 32]);
+    let component = b"component-bytes";
+    let sign = |key: &SigningKey, metadata: Option<&[u8]>| {
+        base64::engine::general_purpose::STANDARD
+            .encode(key.sign(&signed_payload(component, metadata)).to_bytes())
+    };
+    let encode = |key: &SigningKey| sign(key, None);
+
+    let enforce =
+        SignatureVerifier::new(&signature_settings(SignatureMode::Enforce, &trusted)).expect("v");
+    let detached = encode(&trusted);
+    assert_eq!(
+        enforce
+            .verify(component, None, Some(detached.as_bytes()), "a.vtx")
+            .expect("detached"),
+        Some("vtxdeo".to_string())
+    );
+    let unsigned_meta = br#"{"route_auth":"required-group:admin","author":"me"}"#;
+    let metadata = serde_json::json!({
+        "author": "me",
+        "route_auth": "required-group:admin",
+        "signature": sign(&trusted, Some(unsigned_meta)),
+    })
+    .to_string();
+    assert_eq!(
+        enforce
+            .verify(component, Some(metadata.as_bytes()), None, "a.vtx")
+            .expect("embedded"),
+        Some("vtxdeo".to_string())
+    );
+    assert!(matches!(
+        enforce.verify(component, None, None, "a.vtx"),
+        Err(PluginRejection::SignatureRejected { .. })
+    ));
+    let forged = encode(&untrusted);
+    assert!(matches!(
+        enforce.verify(component, None, Some(forged.as_bytes()), "a.vtx"),
+        Err(PluginRejection::SignatureRejected { .. })
+    ));
+    assert!(matches!(
+        enforce.verify(b"tampered", None, Some(detached.as_bytes()), "a.vtx"),
+        Err(PluginRejection::SignatureRejected { .. })
+    ));
+
+    let warn =
+        SignatureVerifier::new(&signature_settings(SignatureMode::Warn, &trusted)).expect("v");
+    assert_eq!(
+        warn.verify(component, None, None, "a.vtx").expect("warn"),
+        None
+    );
+
+    let off = SignatureVerifier::new(&signature_settings(SignatureMode::Off, &trusted)).expect("v");
+    assert_eq!(
+        off.verify(component, None, Some(forged.as_bytes()), "a.vtx")
+            .expect("off"),
+        None
+    );
+}
+
+#[test]
+fn signature_covers_package_metadata() {
+    let trusted = SigningKey::from_bytes(&[7u8; 32]);
+    let component = b"component-bytes";
+    let metadata = br#"{"route_auth":"required-group:admin","stateless":true}"#;
+    let signature = base64::engine::general_purpose::STANDARD.encode(
+        trusted
+            .sign(&signed_payload(component, Some(metadata)))
+            .to_bytes(),
+    );
+    let enforce =
+        SignatureVerifier::new(&signature_settings(SignatureMode::Enforce, &trusted)).expect("v");
+
+    assert!(enforce
+        .verify(
+            component,
+            Some(metadata),
+            Some(signature.as_bytes()),
+            "a.vtx"
+        )
+        .is_ok());
+    // 键顺序与空白不影响规范化载荷
+    assert!(enforce
+        .verify(
+            component,
+            Some(br#"{ "stateless": true, "route_auth": "required-group:admin" }"#),
+            Some(signature.as_bytes()),
+            "a.vtx"
+        )
+        .is_ok());
+
+    for tampered in [
+        &br#"{"route_auth":"public","stateless":true}"#[..],
+        br#"{"route_auth":"required-group:admin","stateless":true,"dependencies":["x"]}"#,
+        br#"{"route_auth":"required-group:admin"}"#,
+    ] {
+        assert!(matches!(
+            enforce.verify(
+                component,
+                Some(tampered),
+                Some(signature.as_bytes()),
+                "a.vtx"
+            ),
+            Err(PluginRejection::SignatureRejected { .. })
+        ));
+    }
+    assert!(matches!(
+        enforce.verify(component, None, Some(signature.as_bytes()), "a.vtx"),
+        Err(PluginRejection::SignatureRejected { .. })
+    ));
+}
+
+#[test]
+fn signature_verifier_rejects_malformed_key() {
+    let settings = SignatureSettings {
+        mode: SignatureMode::Enforce,
+        trusted_keys: vec![TrustedKeySettings {
+            publisher: "broken".to_string(),
+            public_key: "not-base64!".to_string(),
+        }],
+    };
+    assert!(SignatureVerifier::new(&settings).is_err());
+}
+
+#[test]
+fn plugin_publisher_roundtrip() {
+    let (_guard, registry) = make_registry();
+    assert_eq!(registry.get_plugin_publisher("plugin").expect("get"), None);
+
+    registry
+        .set_plugin_publisher("plugin", Some("vtxdeo"))
+        .expect("set");
+    assert_eq!(
+        registry.get_plugin_publisher("plugin").expect("get"),
+        Some("vtxdeo".to_string())
+    );
+
+    registry
+        .set_plugin_publisher("plugin", None)
+        .expect("clear");
+    assert_eq!(registry.get_plugin_publisher("plugin").expect("get"), None);
+}
+
+#[test]
+fn plugin_snapshot_restores_tables_and_version() {
+    let (_guard, registry) = make_registry();
+    {
+        let conn = registry.get_conn().expect("conn");
+        conn.execute_batch(
+            "CREATE TABLE plugin_notes (id INTEGER PRIMARY KEY, body TEXT NOT NULL);
+             CREATE INDEX idx_plugin_notes_body ON plugin_notes(body);
+             INSERT INTO plugin_notes (body) VALUES ('first'), ('second');
+             CREATE TRIGGER plugin_notes_upper AFTER INSERT ON plugin_notes BEGIN
+                 UPDATE plugin_notes SET body = upper(NEW.body) WHERE id = NEW.id;
+             END;",
+        )
+        .expect("seed");
+    }
+    registry.register_resource("plugin", "TABLE", "plugin_notes");
+    registry.set_plugin_version("plugin", 1);
+
+    let snapshot_id = registry.create_plugin_snapshot("plugin").expect("snapshot");
+
+    {
+        let conn = registry.get_conn().expect("conn");
+        conn.execute_batch(
+            "DELETE FROM plugin_notes;
+             ALTER TABLE plugin_notes ADD COLUMN extra TEXT;
+             CREATE TABLE plugin_tags (id INTEGER PRIMARY KEY);",
+        )
+        .expect("migrate");
+    }
+    registry.register_resource("plugin", "TABLE", "plugin_tags");
+    registry.set_plugin_version("plugin", 3);
+
+    assert_eq!(
+        registry
+            .restore_plugin_snapshot("other", snapshot_id)
+            .expect("restore"),
+        None
+    );
+    assert_eq!(
+        registry
+            .restore_plugin_snapshot("plugin", snapshot_id)
+            .expect("restore"),
+        Some(1)
+    );
+    assert_eq!(registry.get_plugin_version("plugin"), 1);
+    assert_eq!(
+        registry
+            .list_plugin_resources("plugin", "TABLE")
+            .expect("resources"),
+        vec!["plugin_notes".to_string()]
+    );
+
+    let conn = registry.get_conn().expect("conn");
+    let bodies: Vec<String> = conn
+        .prepare("SELECT body FROM plugin_notes ORDER BY id")
+        .unwrap()
+        .query_map([], |row| row.get(0))
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(bodies, vec!["first".to_string(), "second".to_string()]);
+    assert!(conn.prepare("SELECT extra FROM plugin_notes").is_err());
+    let objects: i64 = conn
+        .query_row(
+            "SELECT COUNT(*) FROM sqlite_master
+             WHERE name IN ('plugin_tags', 'idx_plugin_notes_body', 'plugin_notes_upper')",
+            [],
+            |row| row.get(0),
+        )
+        .unwrap();
+    assert_eq!(objects, 2);
+}
+
+#[test]
+fn plugin_snapshots_are_pruned_and_nuked() {
+    let (_guard, registry) = make_registry();
+    {
+        let conn = registry.get_conn().expect("conn");
+        conn.execute_batch("CREATE TABLE plugin_items (id INTEGER PRIMARY KEY);")
+            .expect("seed");
+    }
+    registry.register_resource("plugin", "TABLE", "plugin_items");
+
+    for version in 0..4 {
+        registry.set_plugin_version("plugin", version);
+        registry.create_plugin_snapshot("plugin").expect("snapshot");
+    }
+    assert_eq!(
+        registry.prune_plugin_snapshots("plugin", 2).expect("prune"),
+        2
+    );
+
+    let snapshots = registry.list_plugin_snapshots("plugin").expect("list");
+    let versions: Vec<i64> = snapshots.iter().map(|s| s.version).collect();
+    assert_eq!(versions, vec![3, 2]);
+    assert_eq!(snapshots[0].tables, vec!["plugin_items".to_string()]);
+
+    registry.nuke_plugin("plugin").expect("nuke");
+    assert!(registry
+        .list_plugin_snapshots("plugin")
+        .expect("list")
+        .is_empty());
+    let conn = registry.get_conn().expect("conn");
+    let copies: i64 = conn
+        .query_row(
+            "SELECT COUNT(*) FROM sqlite_master WHERE name LIKE '__vtx_snap_%'",
+            [],
+            |row| row.get(0),
+        )
+        .unwrap();
+    assert_eq!(copies, 0);
+}
+
+#[test]
+fn parse_dependencies_from_metadata() {
+    let metadata =
+        br#"{"author":"me","dependencies":{"auth-basic":"^1.2","events":">=0.3, <0.5"}}"#;
+    let deps = parse_dependencies(Some(metadata)).expect("deps");
+    assert_eq!(deps.len(), 2);
+    assert_eq!(deps[0].plugin_id, "auth-basic");
+    assert!(deps[0]
+        .requirement
+        .matches(&semver::Version::parse("1.4.0").unwrap()));
+    assert!(!deps[0]
+        .requirement
+        .matches(&semver::Version::parse("2.0.0").unwrap()));
+
+    assert!(parse_dependencies(None).expect("none").is_empty());
+    assert!(parse_dependencies(Some(br#"{"author":"me"}"#))
+        .expect("undeclared")
+        .is_empty());
+    assert!(parse_dependencies(Some(br#"{"dependencies":{"auth-basic":"not-a-range"}}"#)).is_err());
+}
+
+#[test]
+fn load_order_respects_dependencies() {
+    let nodes = vec![
+        (
+            "gallery".to_string(),
+            vec!["auth".to_string(), "media".to_string()],
+        ),
+        ("media".to_string(), vec!["auth".to_string()]),
+        ("auth".to_string(), Vec::new()),
+        ("external".to_string(), vec!["not-installed".to_string()]),
+    ];
+    let order: Vec<&str> = load_order(&nodes)
+        .into_iter()
+        .map(|i| nodes[i].0.as_str())
+        .collect();
+    assert_eq!(order, vec!["auth", "external", "media", "gallery"]);
+}
+
+#[test]
+fn load_order_appends_cycles_last() {
+    let nodes = vec![
+        ("a".to_string(), vec!["b".to_string()]),
+        ("b".to_string(), vec!["a".to_string()]),
+        ("c".to_string(), Vec::new()),
+    ];
+    let order: Vec<&str> = load_order(&nodes)
+        .into_iter()
+        .map(|i| nodes[i].0.as_str())
+        .collect();
+    assert_eq!(order, vec!["c", "a", "b"]);
+}
+
+#[test]
+fn parse_stateless_from_metadata() {
+    assert!(parse_stateless(Some(br#"{"stateless":true}"#)));
+    assert!(!parse_stateless(Some(br#"{"stateless":false}"#)));
+    assert!(!parse_stateless(Some(br#"{"author":"me"}"#)));
+    assert!(!parse_stateless(Some(b"not json")));
+    assert!(!parse_stateless(None));
+}
+
+#[test]
+fn instance_pool_stats_start_empty() {
+    let disabled = InstancePool::new(0).stats("auth-basic");
+    assert!(!disabled.enabled);
+    assert_eq!(disabled.idle, 0);
+    assert_eq!(disabled.avg_cold_us, None);
+
+    let pool = InstancePool::new(4);
+    assert!(pool.enabled());
+    let stats = pool.stats("auth-basic");
+    assert_eq!(stats.capacity, 4);
+    assert_eq!(stats.pooled_checkouts, 0);
+    assert_eq!(stats.evictions, 0);
+    assert_eq!(stats.avg_pooled_us, None);
+}
+
+#[test]
+fn circuit_breaker_opens_and_recovers_through_probe() {
+    let breakers = CircuitBreakers::new(BreakerSettings {
+        failure_threshold: 2,
+        window_secs: 60,
+        open_secs: 3600,
+    });
+    assert!(!breakers.admit("flaky").unwrap().is_probe());
+    breakers.record_failure("flaky");
+    assert_eq!(breakers.status("flaky").state, BreakerState::Closed);
+    breakers.record_failure("flaky");
+
+    let status = breakers.status("flaky");
+    assert_eq!(status.state, BreakerState::Open);
+    assert!(status.retry_after_secs.unwrap_or(0) > 0);
+    assert!(breakers.admit("flaky").is_err());
+    assert!(breakers.admit("healthy").is_ok());
+
+    breakers.reset("flaky");
+    assert_eq!(breakers.status("flaky").state, BreakerState::Closed);
+    assert!(breakers.admit("flaky").is_ok());
+
+    let probing = CircuitBreakers::new(BreakerSettings {
+        failure_threshold: 1,
+        window_secs: 60,
+        open_secs: 0,
+    });
+    probing.record_failure("flaky");
+    let probe = probing.admit("flaky").unwrap();
+    assert!(probe.is_probe());
+    assert_eq!(probing.status("flaky").state, BreakerState::HalfOpen);
+    assert!(probing.admit("flaky").is_err());
+    probe.record(true);
+    assert_eq!(probing.status("flaky").state, BreakerState::Closed);
+    assert_eq!(probing.status("flaky").failures, 0);
+}
+
+#[test]
+fn circuit_breaker_releases_abandoned_probe() {
+    let breakers = CircuitBreakers::new(BreakerSettings {
+        failure_threshold: 1,
+        window_secs: 60,
+        open_secs: 0,
+    });
+    breakers.record_failure("flaky");
+    let probe = breakers.admit("flaky").unwrap();
+    assert!(probe.is_probe());
+    assert!(breakers.admit("flaky").is_err());
+
+    drop(probe);
+    assert_eq!(breakers.status("flaky").state, BreakerState::Open);
+    let next = breakers.admit("flaky").unwrap();
+    assert!(next.is_probe());
+    next.record(true);
+    assert_eq!(breakers.status("flaky").state, BreakerState::Closed);
+
+    // 关闭状态下丢弃凭据不计入失败
+    drop(breakers.admit("flaky").unwrap());
+    assert_eq!(breakers.status("flaky").failures, 0);
+}
+
+#[test]
+fn plugins_installed_before_permission_approval_keep_declared_permissions() {
+    let temp_dir = tempdir().expect("tempdir");
+    let db_path = temp_dir.path().join("vtx.db");
+    let db_path = db_path.to_string_lossy().to_string();
+    let registry = VtxVideoRegistry::new(&db_path, 1).expect("registry");
+    let plugin_path = temp_dir.path().join("legacy.vtx");
+    std::fs::write(&plugin_path, "x").expect("write");
+    let plugin_uri = Url::from_file_path(&plugin_path).unwrap().to_string();
+    assert!(registry
+        .verify_installation("legacy", &plugin_uri)
+        .expect("verify"));
+
+    // 回退到最后一个迁移之前，模拟升级前已安装插件的数据库
+    {
+        let conn = registry.get_conn().expect("conn");
+        let version: i64 = conn
+            .query_row("PRAGMA user_version", [], |row| row.get(0))
+            .expect("user_version");
+        conn.execute_batch(&format!(
+            "DROP TABLE sys_plugin_permission_grandfathered; PRAGMA user_version = {};",
+            version - 1
+        ))
+        .expect("downgrade");
+    }
+    drop(registry);
+    let registry = VtxVideoRegistry::new(&db_path, 1).expect("upgraded registry");
+
+    let mut policy = PluginPolicy {
+        permissions: vec!["sql:write".to_string(), "ffmpeg:execute".to_string()],
+        ..Default::default()
+    };
+    let records = registry
+        .sync_plugin_permissions("legacy", &permissions::requested(&policy))
+        .unwrap();
+    assert!(records.iter().all(|r| r.status == "granted"));
+    assert_eq!(permissions::resolve(&policy, &records).permissions.len(), 2);
+
+    // 升级后的更新新增的权限仍需审批
+    policy.permissions.push("file:write".to_string());
+    let records = registry
+        .sync_plugin_permissions("legacy", &permissions::requested(&policy))
+        .unwrap();
+    let grants = permissions::resolve(&policy, &records);
+    assert!(grants.permissions.contains("sql:write"));
+    assert!(!grants.permissions.contains("file:write"));
+
+    // 升级后新安装的插件不受影响
+    let records = registry
+        .sync_plugin_permissions("fresh", &permissions::requested(&policy))
+        .unwrap();
+    assert!(records.iter().all(|r| r.status == "pending"));
+}
+
+#[test]
+fn plugin_permissions_stay_pending_until_approved() {
+    let (_dir, registry) = make_registry();
+    let mut policy = PluginPolicy {
+        permissions: vec!["sql:write".to_string()],
+        ..Default::default()
+    };
+
+    let records = registry
+        .sync_plugin_permissions("demo", &permissions::requested(&policy))
+        .unwrap();
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].status, "pending");
+    assert!(permissions::resolve(&policy, &records)
+        .permissions
+        .is_empty());
+
+    let updated = registry
+        .decide_plugin_permissions("demo", KIND_PERMISSION, None, "granted")
+        .unwrap();
+    assert_eq!(updated, 1);
+
+    // 更新后新增的权限不会随已有授予自动生效
+    policy.permissions.push("ffmpeg:execute".to_string());
+    let records = registry
+        .sync_plugin_permissions("demo", &permissions::requested(&policy))
+        .unwrap();
+    let grants = permissions::resolve(&policy, &records);
+    assert!(grants.permissions.contains("sql:write"));
+    assert!(!grants.permissions.contains("ffmpeg:execute"));
+
+    registry
+        .decide_plugin_permissions(
+            "demo",
+            KIND_PERMISSION,
+            Some(&["sql:write".to_string()]),
+            "denied",
+        )
+        .unwrap();
+    let records = registry.list_plugin_permissions("demo").unwrap();
+    assert!(permissions::resolve(&policy, &records)
+        .permissions
+        .is_empty());
+
+    registry.nuke_plugin("demo").unwrap();
+    assert!(registry.list_plugin_permissions("demo").unwrap().is_empty());
+}
+
+#[test]
+fn auth_capability_requires_approval() {
+    let (_dir, registry) = make_registry();
+    let policy = PluginPolicy {
+        permissions: vec![AUTH_CAPABILITY.to_string()],
+        ..Default::default()
+    };
+
+    let records = registry
+        .sync_plugin_permissions("rogue-auth", &permissions::requested(&policy))
+        .unwrap();
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].name, AUTH_CAPABILITY);
+    assert_eq!(records[0].status, "pending");
+    assert!(!permissions::resolve(&policy, &records)
+        .permissions
+        .contains(AUTH_CAPABILITY));
+
+    registry
+        .decide_plugin_permissions("rogue-auth", KIND_PERMISSION, None, "granted")
+        .unwrap();
+    let records = registry.list_plugin_permissions("rogue-auth").unwrap();
+    assert!(permissions::resolve(&policy, &records)
+        .permissions
+        .contains(AUTH_CAPABILITY));
+}
+
+#[test]
+fn plugin_config_encrypts_secrets_at_rest() {
+    let (_dir, registry) = make_registry();
+    let store = PluginConfigStore::new(registry.clone(), Some("master-key"));
+
+    store.set("demo", "FEATURE_FLAG", "on", false).unwrap();
+    store.set("demo", "API_KEY", "s3cr3t", true).unwrap();
+    assert!(store.set("demo", "VTX_ENV", "dev", false).is_err());
+    assert!(store.set("demo", "bad-key", "x", false).is_err());
+
+    let env = store.env("demo");
+    assert!(env.contains(&("API_KEY".to_string(), "s3cr3t".to_string())));
+    assert!(env.contains(&("FEATURE_FLAG".to_string(), "on".to_string())));
+
+    let rows = registry.list_plugin_config("demo").unwrap();
+    let secret = rows.iter().find(|r| r.key == "API_KEY").unwrap();
+    assert!(secret.secret);
+    assert!(!secret.value.contains("s3cr3t"));
+    let listed = store.list("demo").unwrap();
+    assert!(listed.iter().all(|e| !e.secret || e.value.is_none()));
+
+    // 主密钥不匹配时跳过 secret，明文配置仍然生效
+    let other = PluginConfigStore::new(registry.clone(), Some("other-key"));
+    assert_eq!(
+        other.env("demo").as_ref(),
+        &vec![("FEATURE_FLAG".to_string(), "on".to_string())]
+    );
+    assert!(other.set("demo", "TOKEN", "x", true).is_ok());
+    let plain_only = PluginConfigStore::new(registry, None);
+    assert!(plain_only.set("demo", "TOKEN", "x", true).is_err());
+
+    assert!(store.remove("demo", "FEATURE_FLAG").unwrap());
+    assert!(!store.remove("demo", "FEATURE_FLAG").unwrap());
+    assert!(!store.env("demo").iter().any(|(k, _)| k == "FEATURE_FLAG"));
+
+    assert!(is_valid_key("_private"));
+    assert!(!is_valid_key("1ST"));
+    assert!(!is_valid_key(""));
+}
+
+#[test]
+fn scratch_dir_requires_permission_and_respects_quota() {
+    let dir = tempdir().unwrap();
+    let scratch = ScratchSpace::new(&ScratchSettings {
+        root: Some(dir.path().join("scratch")),
+        quota_mb: 1,
+    })
+    .unwrap();
+
+    let mut permissions = std::collections::HashSet::new();
+    assert!(scratch.dir_for("demo", &permissions).is_none());
+    permissions.insert(SCRATCH_PERMISSION.to_string());
+    let mount = scratch.dir_for("../demo", &permissions).unwrap();
+    let plugin_dir = mount.path.clone();
+    assert!(plugin_dir.starts_with(dir.path().join("scratch")));
+    assert!(plugin_dir.is_dir());
+    assert!(mount.writable);
+
+    std::fs::write(plugin_dir.join("small.bin"), vec![0u8; 1024]).unwrap();
+    assert!(!scratch.check_quota("../demo"));
+    std::fs::create_dir_all(plugin_dir.join("nested")).unwrap();
+    std::fs::write(
+        plugin_dir.join("nested/big.bin"),
+        vec![0u8; 2 * 1024 * 1024],
+    )
+    .unwrap();
+    // 超出配额后只收回写权限，不删除插件的文件
+    assert!(scratch.check_quota("../demo"));
+    assert!(scratch.is_over_quota("../demo"));
+    assert!(!scratch.dir_for("../demo", &permissions).unwrap().writable);
+    assert!(plugin_dir.join("nested/big.bin").is_file());
+    assert!(!scratch.check_quota("../demo"));
+
+    std::fs::remove_file(plugin_dir.join("nested/big.bin")).unwrap();
+    assert!(scratch.check_quota("../demo"));
+    assert!(scratch.dir_for("../demo", &permissions).unwrap().writable);
+
+    scratch.remove("../demo").unwrap();
+    assert!(!plugin_dir.exists());
+
+    let disabled = ScratchSpace::new(&ScratchSettings::default()).unwrap();
+    assert!(disabled.dir_for("demo", &permissions).is_none());
+}
+
+#[tokio::test]
+async fn plugin_output_is_split_into_bounded_tagged_lines() {
+    use tokio::io::AsyncWriteExt;
+
+    let logs = std::sync::Arc::new(PluginLogs::new(3));
+    let request_id = std::sync::Arc::new(std::sync::Mutex::new(Some("req-1".to_string())));
+    let mut stdout = PluginOutput::new(logs.clone(), "demo", LogStream::Stdout, request_id.clone());
+    let mut stderr = PluginOutput::new(logs.clone(), "demo", LogStream::Stderr, request_id.clone());
+
+    stdout.write_all(b"first\r\nsec").await.unwrap();
+    stdout.write_all(b"ond\nthird\n").await.unwrap();
+    *request_id.lock().unwrap() = None;
+    stderr.write_all(b"oops\n").await.unwrap();
+
+    let lines = logs.recent("demo", 10, None);
+    let text: Vec<&str> = lines.iter().map(|l| l.line.as_str()).collect();
+    assert_eq!(text, vec!["second", "third", "oops"]);
+    assert_eq!(lines[0].request_id.as_deref(), Some("req-1"));
+    assert_eq!(lines[2].stream, LogStream::Stderr);
+    assert!(lines[2].request_id.is_none());
+
+    let after = logs.recent("demo", 10, Some(lines[1].seq));
+    assert_eq!(after.len(), 1);
+    assert_eq!(logs.recent("demo", 1, None)[0].line, "oops");
+    assert!(logs.recent("other", 10, None).is_empty());
+
+    logs.clear("demo");
+    assert!(logs.recent("demo", 10, None).is_empty());
+}
+
+#[test]
+fn downgrade_protection_checks_version_and_migrations() {
+    assert!(check_downgrade("demo", None, "0.1.0", 0, 0).is_ok());
+    assert!(check_downgrade("demo", Some("1.2.0"), "1.2.0", 3, 3).is_ok());
+    assert!(check_downgrade("demo", Some("1.2.0"), "1.3.0", 3, 4).is_ok());
+    assert!(matches!(
+        check_downgrade("demo", Some("1.2.0"), "1.1.9", 3, 3),
+        Err(PluginRejection::Downgrade { .. })
+    ));
+    assert!(matches!(
+        check_downgrade("demo", Some("1.2.0"), "1.2.1", 3, 2),
+        Err(PluginRejection::MigrationsBehind {
+            applied: 3,
+            declared: 2,
+            ..
+        })
+    ));
+    // 非 semver 版本无法比较，只检查迁移数量
+    assert!(check_downgrade("demo", Some("nightly"), "0.0.1", 0, 0).is_ok());
+}
+
+#[test]
+fn plugin_version_history_tracks_installed_version() {
+    let (_guard, registry) = make_registry();
+    assert!(registry.installed_plugin_version("demo").unwrap().is_none());
+
+    let hash = content_hash(b"package-v1");
+    registry
+        .record_plugin_history("demo", "1.0.0", &hash, OUTCOME_ACTIVATED, None, false)
+        .unwrap();
+    registry
+        .record_plugin_history(
+            "demo",
+            "0.9.0",
+            &content_hash(b"package-v0"),
+            OUTCOME_REJECTED,
+            Some("downgrade"),
+            false,
+        )
+        .unwrap();
+    assert_eq!(
+        registry
+            .installed_plugin_version("demo")
+            .unwrap()
+            .map(|record| record.version)
+            .as_deref(),
+        Some("1.0.0")
+    );
+
+    let history = registry.list_plugin_history("demo", 10).unwrap();
+    assert_eq!(history.len(), 2);
+    assert_eq!(history[0].outcome, OUTCOME_REJECTED);
+    assert_eq!(history[1].content_hash, hash);
+    assert_eq!(hash.len(), 64);
+
+    registry
+        .record_plugin_history("demo", "1.0.0", &hash, OUTCOME_UNINSTALLED, None, false)
+        .unwrap();
+    assert!(registry.installed_plugin_version("demo").unwrap().is_none());
+}
+
+#[test]
+fn rolled_back_package_reloads_from_disk() {
+    let (_guard, registry) = make_registry();
+    let (v1, v2) = (&b"package-v1"[..], &b"package-v2"[..]);
+    registry
+        .record_plugin_history(
+            "demo",
+            "1.0.0",
+            &content_hash(v1),
+            OUTCOME_ACTIVATED,
+            None,
+            false,
+        )
+        .unwrap();
+    // v2 新增了一条迁移
+    registry.set_plugin_version("demo", 3);
+    registry
+        .record_plugin_history(
+            "demo",
+            "1.1.0",
+            &content_hash(v2),
+            OUTCOME_ACTIVATED,
+            None,
+            false,
+        )
+        .unwrap();
+    registry
+        .record_plugin_history(
+            "demo",
+            "1.0.0",
+            &content_hash(v1),
+            OUTCOME_ROLLED_BACK,
+            None,
+            false,
+        )
+        .unwrap();
+
+    let reload = |package: &[u8], version: &str, declared: usize| {
+        let installed = registry.installed_plugin_version("demo").unwrap();
+        check_reload(
+            "demo",
+            installed.as_ref(),
+            version,
+            package,
+            registry.get_plugin_version("demo"),
+            declared,
+        )
+    };
+    // 回滚后的包从磁盘重载（迁移数量少于已应用数量）
+    assert!(reload(v1, "1.0.0", 2).is_ok());
+    registry
+        .record_plugin_history(
+            "demo",
+            "1.0.0",
+            &content_hash(v1),
+            OUTCOME_ACTIVATED,
+            None,
+            false,
+        )
+        .unwrap();
+    // 重启后再次加载同一个包
+    assert!(reload(v1, "1.0.0", 2).is_ok());
+
+    // 其他包仍受降级保护
+    assert!(matches!(
+        reload(b"package-v1-patched", "1.0.0", 2),
+        Err(PluginRejection::MigrationsBehind { .. })
+    ));
+    assert!(matches!(
+        reload(b"package-v0", "0.9.0", 3),
+        Err(PluginRejection::Downgrade { .. })
+    ));
+    assert!(reload(v2, "1.1.0", 3).is_ok());
+}
+
+#[test]
+fn auth_cache_keys_on_declared_headers_only() {
+    use vtx_core::runtime::vtx_host_impl::api::vtx_auth_types::UserContext;
+
+    let provider = |plugin_id: &str, headers: &[&str]| AuthProviderSettings {
+        plugin_id: plugin_id.to_string(),
+        continue_on: vec![401],
+        cache_headers: headers.iter().map(|h| h.to_string()).collect(),
+        legacy: false,
+    };
+    let request = |token: &str, trace: &str| {
+        let mut headers = axum::http::HeaderMap::new();
+        headers.insert("authorization", token.parse().unwrap());
+        headers.insert("x-trace", trace.parse().unwrap());
+        headers
+    };
+    let user = UserContext {
+        user_id: "u1".to_string(),
+        username: "alice".to_string(),
+        groups: Vec::new(),
+        metadata: "{}".to_string(),
+    };
+
+    let cache = AuthCache::new(&AuthCacheSettings {
+        ttl_secs: 60,
+        max_entries: 2,
+    });
+    let chain = vec![
+        provider("auth-a", &["Authorization"]),
+        provider("auth-b", &["authorization"]),
+    ];
+    assert!(cache
+        .key(&[provider("auth-a", &[])], &request("t1", "x"))
+        .is_none());
+    assert!(cache.key(&[], &request("t1", "x")).is_none());
+
+    let key = cache.key(&chain, &request("t1", "x")).unwrap();
+    assert_eq!(key, cache.key(&chain, &request("t1", "y")).unwrap());
+    assert_ne!(key, cache.key(&chain, &request("t2", "x")).unwrap());
+    assert_ne!(key, cache.key(&chain[..1], &request("t1", "x")).unwrap());
+
+    cache.insert(key, "auth-b", &user, vec!["auth-a".into(), "auth-b".into()]);
+    let (provider_id, cached) = cache.get(&key).unwrap();
+    assert_eq!(provider_id, "auth-b");
+    assert_eq!(cached.user_id, "u1");
+    assert_eq!(cache.invalidate_plugin("auth-c"), 0);
+    assert_eq!(cache.invalidate_plugin("auth-a"), 1);
+    assert!(cache.get(&key).is_none());
+
+    for token in ["t1", "t2", "t3"] {
+        let key = cache.key(&chain, &request(token, "x")).unwrap();
+        cache.insert(key, "auth-a", &user, vec!["auth-a".into()]);
+    }
+    assert_eq!(cache.clear(), 2);
+
+    let disabled = AuthCache::new(&AuthCacheSettings {
+        ttl_secs: 0,
+        max_entries: 16,
+    });
+    assert!(disabled.key(&chain, &request("t1", "x")).is_none());
+}
+
+#[test]
+fn route_policy_parses_declarations_and_overrides() {
+    for raw in [
+        "public",
+        "optional-auth",
+        "required-auth",
+        "required-group:admins",
+    ] {
+        assert_eq!(raw.parse::<RoutePolicy>().unwrap().to_string(), raw);
+    }
+    assert_eq!(
+        " required-group: editors ".parse::<RoutePolicy>().unwrap(),
+        RoutePolicy::RequiredGroup("editors".into())
+    );
+    assert!("required-group:".parse::<RoutePolicy>().is_err());
+    assert!("private".parse::<RoutePolicy>().is_err());
+
+    assert_eq!(
+        parse_route_policy(Some(br#"{"route_auth":"required-auth"}"#)).unwrap(),
+        Some(RoutePolicy::RequiredAuth)
+    );
+    assert_eq!(
+        parse_route_policy(Some(br#"{"author":"me"}"#)).unwrap(),
+        None
+    );
+    assert_eq!(parse_route_policy(None).unwrap(), None);
+    // 旧版包的空元数据或非对象元数据按未声明处理
+    assert_eq!(parse_route_policy(Some(b"")).unwrap(), None);
+    assert_eq!(parse_route_policy(Some(b"not json")).unwrap(), None);
+    assert_eq!(
+        parse_route_policy(Some(br#"["route_auth"]"#)).unwrap(),
+        None
+    );
+    assert!(parse_route_policy(Some(br#"{"route_auth":"admins"}"#)).is_err());
+    assert!(parse_route_policy(Some(br#"{"route_auth":true}"#)).is_err());
+    assert!(parse_route_policy(Some(br#"{"route_auth":{"group":"admins"}}"#)).is_err());
+
+    let mut raw = std::collections::HashMap::new();
+    raw.insert("auth-basic".to_string(), "optional-auth".to_string());
+    assert_eq!(
+        parse_overrides(&raw).unwrap().get("auth-basic"),
+        Some(&RoutePolicy::OptionalAuth)
+    );
+    raw.insert("auth-basic".to_string(), "nobody".to_string());
+    assert!(parse_overrides(&raw).is_err());
+}
+
+#[test]
+fn self_writes_suppress_one_matching_reload() {
+    let writes = SelfWrites::new(std::time::Duration::from_secs(60));
+    let uri = "file:///plugins/auth.vtx";
+    writes.mark(uri, b"package-v2");
+
+    assert!(!writes.take("file:///plugins/other.vtx", b"package-v2"));
+    // 外部随后写入的不同内容不受影响
+    assert!(!writes.take(uri, b"package-v3"));
+    assert!(writes.take(uri, b"package-v2"));
+    assert!(!writes.take(uri, b"package-v2"));
+
+    writes.mark(uri, b"package-v2");
+    writes.unmark(uri);
+    assert!(!writes.take(uri, b"package-v2"));
+
+    let expired = SelfWrites::new(std::time::Duration::ZERO);
+    expired.mark(uri, b"package-v2");
+    assert!(!expired.take(uri, b"package-v2"));
+}
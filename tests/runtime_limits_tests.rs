@@ -1,5 +1,5 @@
 use axum::extract::State;
-use axum::http::StatusCode;
+use axum::http::{HeaderMap, HeaderValue, StatusCode};
 use axum::response::Redirect;
 use axum::routing::get;
 use axum::Router;
@@ -13,14 +13,20 @@ use url::Url;
 use wasmtime::StoreLimitsBuilder;
 
 use vtx_core::common::events::{EventContext, VtxEvent};
+use vtx_core::config::{DeadlineOverride, DeadlineSettings, FuelSettings};
 use vtx_core::runtime::bus::EventBus;
 use vtx_core::runtime::context::{SecurityPolicy, StreamContext, StreamContextConfig};
+use vtx_core::runtime::deadline::{self, PluginCall};
 use vtx_core::runtime::ffmpeg::VtxFfmpegManager;
+use vtx_core::runtime::fuel::{self, FuelMeter};
+use vtx_core::runtime::request_data::RequestData;
 use vtx_core::runtime::vtx_host_impl::api;
 use vtx_core::runtime::vtx_host_impl::api::vtx_http_client::Host as HttpHost;
 use vtx_core::runtime::vtx_host_impl::api::vtx_vfs::Host as VfsHost;
+use vtx_core::runtime::vtx_host_impl::api::vtx_vfs::HostBuffer;
 use vtx_core::storage::VtxVideoRegistry;
 use vtx_core::vtx_vfs::VtxVfsManager;
+use vtx_core::web::utils::streaming::StreamProtocolLayer;
 
 fn write_ffmpeg_stub(dir: &Path) -> PathBuf {
     let (name, contents) = if cfg!(windows) {
@@ -78,6 +84,11 @@ fn make_context(
         permissions: HashSet::new(),
         http_allowlist,
         vfs,
+        env: Vec::new(),
+        scratch_dir: None,
+        logs: None,
+        request_id: None,
+        request: None,
     });
 
     (temp_dir, ctx)
@@ -169,6 +180,79 @@ async fn vfs_read_range_respects_limit() {
     assert_eq!(bytes, b"0123");
 }
 
+#[tokio::test]
+async fn request_data_exposes_allowlisted_headers_and_body() {
+    let (_temp, mut ctx) = make_context(1024, Vec::new());
+    assert!(ctx.open_uri("vtx-request:headers".into()).await.is_err());
+
+    let mut headers = HeaderMap::new();
+    headers.insert("Content-Type", HeaderValue::from_static("text/plain"));
+    headers.append("accept", HeaderValue::from_static("text/html"));
+    headers.append("accept", HeaderValue::from_static("*/*"));
+    headers.insert("authorization", HeaderValue::from_static("Bearer secret"));
+    let allowlist = vec!["Accept".to_string(), "content-type".to_string()];
+    ctx.request = Some(Arc::new(RequestData::new(
+        &headers,
+        &allowlist,
+        "hello".into(),
+    )));
+
+    let handle = ctx
+        .open_uri("vtx-request:headers".into())
+        .await
+        .expect("headers");
+    let bytes = ctx.read(handle, 0, 1024).await;
+    let json: serde_json::Value = serde_json::from_slice(&bytes).expect("json");
+    assert_eq!(
+        json,
+        serde_json::json!({ "accept": "text/html, */*", "content-type": "text/plain" })
+    );
+
+    let handle = ctx.open_uri("vtx-request:body".into()).await.expect("body");
+    assert_eq!(ctx.read(handle, 0, 1024).await, b"hello");
+
+    assert!(ctx.open_uri("vtx-request:cookies".into()).await.is_err());
+}
+
+#[tokio::test]
+async fn plugin_response_headers_override_host_defaults() {
+    let (_temp, mut ctx) = make_context(1024, Vec::new());
+    assert!(ctx.open_uri("vtx-response:headers".into()).await.is_err());
+
+    ctx.request = Some(Arc::new(RequestData::default()));
+    let handle = ctx
+        .open_uri("vtx-response:headers".into())
+        .await
+        .expect("response headers");
+    let lines = b"Set-Cookie: a=1\nSet-Cookie: b=2\nCache-Control: private\n".to_vec();
+    assert_eq!(ctx.write(handle, lines.clone()).await, lines.len() as u64);
+    let handle = ctx
+        .open_uri("vtx-response:headers".into())
+        .await
+        .expect("response headers");
+    assert_eq!(
+        ctx.write(handle, b"Location: /login\nContent-Length: 1".to_vec())
+            .await,
+        0
+    );
+
+    let mut response = axum::response::Response::builder()
+        .header("cache-control", "no-cache")
+        .header("content-length", "5")
+        .body(axum::body::Body::from("hello"))
+        .expect("response");
+    StreamProtocolLayer::apply_plugin_headers(
+        &mut response,
+        std::mem::take(&mut ctx.response_headers),
+    );
+    let headers = response.headers();
+    let cookies: Vec<_> = headers.get_all("set-cookie").iter().collect();
+    assert_eq!(cookies, ["a=1", "b=2"]);
+    assert_eq!(headers["cache-control"], "private");
+    assert_eq!(headers["content-length"], "5");
+    assert!(headers.get("location").is_none());
+}
+
 #[derive(Clone)]
 struct RedirectState {
     base_url: String,
@@ -307,3 +391,114 @@ async fn http_redirect_allowlist_blocks_unmatched_target() {
 
     server.abort();
 }
+
+#[test]
+fn deadline_overrides_apply_per_plugin() {
+    let mut settings = DeadlineSettings::default();
+    settings.overrides.insert(
+        "slow-plugin".to_string(),
+        DeadlineOverride {
+            handle_ms: Some(120_000),
+            event_ms: Some(0),
+            auth_ms: None,
+        },
+    );
+
+    assert_eq!(
+        deadline::limit_ms(&settings, "slow-plugin", PluginCall::Handle),
+        120_000
+    );
+    assert_eq!(
+        deadline::limit_ms(&settings, "slow-plugin", PluginCall::Event),
+        0
+    );
+    assert_eq!(
+        deadline::limit_ms(&settings, "slow-plugin", PluginCall::Authenticate),
+        settings.auth_ms
+    );
+    assert_eq!(
+        deadline::limit_ms(&settings, "other", PluginCall::Handle),
+        settings.handle_ms
+    );
+    // 加载检查始终有期限，不受按插件覆盖影响
+    assert!(settings.inspect_ms > 0);
+    assert_eq!(
+        deadline::limit_ms(&settings, "slow-plugin", PluginCall::Inspect),
+        settings.inspect_ms
+    );
+}
+
+#[test]
+fn epoch_deadline_interrupts_infinite_loop() {
+    let mut config = wasmtime::Config::new();
+    config.epoch_interruption(true);
+    let engine = wasmtime::Engine::new(&config).expect("engine");
+    deadline::spawn_epoch_ticker(&engine, Duration::from_millis(5)).expect("ticker");
+
+    let module = wasmtime::Module::new(&engine, r#"(module (func (export "spin") (loop br 0)))"#)
+        .expect("module");
+    let mut store = wasmtime::Store::new(&engine, ());
+    let settings = DeadlineSettings {
+        epoch_tick_ms: 5,
+        ..DeadlineSettings::default()
+    };
+    deadline::arm(&mut store, &settings, 50);
+
+    let instance = wasmtime::Instance::new(&mut store, &module, &[]).expect("instance");
+    let spin = instance
+        .get_typed_func::<(), ()>(&mut store, "spin")
+        .expect("spin");
+    let started = std::time::Instant::now();
+    let err = spin.call(&mut store, ()).expect_err("deadline");
+    assert!(deadline::is_deadline_exceeded(&err));
+    assert!(started.elapsed() < Duration::from_secs(5));
+}
+
+#[test]
+fn fuel_meter_records_usage_and_budget_exhaustion() {
+    let mut config = wasmtime::Config::new();
+    config.consume_fuel(true);
+    let engine = wasmtime::Engine::new(&config).expect("engine");
+    let module = wasmtime::Module::new(
+        &engine,
+        r#"(module
+            (func (export "spin") (loop br 0))
+            (func (export "add") (param i32 i32) (result i32)
+                local.get 0 local.get 1 i32.add))"#,
+    )
+    .expect("module");
+
+    let meter = FuelMeter::new(&FuelSettings {
+        enabled: true,
+        call_budget: 10_000,
+    });
+    let mut store = wasmtime::Store::new(&engine, ());
+    fuel::prime(&mut store);
+    let instance = wasmtime::Instance::new(&mut store, &module, &[]).expect("instance");
+
+    let add = instance
+        .get_typed_func::<(i32, i32), i32>(&mut store, "add")
+        .expect("add");
+    meter.arm(&mut store);
+    let sum = add.call(&mut store, (2, 3));
+    meter.record(&store, "math", PluginCall::Handle, sum.as_ref().err());
+    assert_eq!(sum.expect("sum"), 5);
+
+    let spin = instance
+        .get_typed_func::<(), ()>(&mut store, "spin")
+        .expect("spin");
+    meter.arm(&mut store);
+    let err = spin.call(&mut store, ()).expect_err("out of fuel");
+    assert!(fuel::is_fuel_exhausted(&err));
+    meter.record(&store, "math", PluginCall::Event, Some(&err));
+
+    let usage = meter.usage();
+    assert_eq!(usage.len(), 2);
+    assert_eq!(usage[0].entrypoint, "handle");
+    assert_eq!(usage[0].calls, 1);
+    assert!(usage[0].fuel_consumed > 0);
+    assert_eq!(usage[0].budget_exhausted, 0);
+    assert_eq!(usage[1].entrypoint, "handle_event");
+    assert_eq!(usage[1].fuel_consumed, 10_000);
+    assert_eq!(usage[1].budget_exhausted, 1);
+}
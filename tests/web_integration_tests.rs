@@ -1,7 +1,7 @@
 use axum::{
     body::Body,
     http::{Request, StatusCode},
-    routing::{delete, get, post},
+    routing::{delete, get, post, put},
     Router,
 };
 use futures_util::StreamExt;
@@ -19,7 +19,10 @@ use url::Url;
 use uuid::Uuid;
 use vtx_core::{
     common::events::{EventContext, VtxEvent},
-    config::VtxSettings,
+    config::{
+        AuthCacheSettings, BreakerSettings, DeadlineSettings, FuelSettings, PluginRootSettings,
+        PoolSettings, ScratchSettings, SignatureSettings, VtxSettings, WatchSettings,
+    },
     runtime::{
         bus::EventBus,
         context::StreamContext,
@@ -30,6 +33,7 @@ use vtx_core::{
     vtx_vfs::VtxVfsManager,
     web::{
         api::{admin, ws},
+        middleware::auth::auth_middleware,
         state::AppState,
     },
 };
@@ -65,6 +69,10 @@ fn file_uri(path: &Path) -> String {
 }
 
 async fn make_state() -> (Arc<AppState>, tempfile::TempDir) {
+    make_state_with_root(false).await
+}
+
+async fn make_state_with_root(read_only: bool) -> (Arc<AppState>, tempfile::TempDir) {
     let temp_dir = tempdir().expect("tempdir");
     let db_path = temp_dir.path().join("vtx.db");
     let registry = VtxVideoRegistry::new(db_path.to_string_lossy().as_ref(), 1).expect("registry");
@@ -85,19 +93,39 @@ async fn make_state() -> (Arc<AppState>, tempfile::TempDir) {
 
     let plugin_manager = PluginManager::new(PluginManagerConfig {
         engine: engine.clone(),
-        plugin_root: temp_dir
-            .path()
-            .join("plugins")
-            .to_string_lossy()
-            .to_string(),
+        plugin_roots: vec![PluginRootSettings {
+            location: temp_dir
+                .path()
+                .join("plugins")
+                .to_string_lossy()
+                .to_string(),
+            priority: 0,
+            read_only,
+        }],
         registry: registry.clone(),
         vfs: vfs.clone(),
         linker,
-        auth_provider: None,
+        auth_providers: Vec::new(),
+        auth_cache: AuthCacheSettings::default(),
+        route_policies: std::collections::HashMap::new(),
         vtx_ffmpeg: vtx_ffmpeg.clone(),
         max_buffer_read_bytes: 4 * 1024 * 1024,
         max_memory_bytes: 32 * 1024 * 1024,
         event_bus: event_bus.clone(),
+        component_cache_dir: Some(temp_dir.path().join("cache")),
+        signature: SignatureSettings::default(),
+        snapshot_retention: 5,
+        watch: WatchSettings::default(),
+        pool: PoolSettings::default(),
+        deadlines: DeadlineSettings::default(),
+        fuel: FuelSettings::default(),
+        breaker: BreakerSettings::default(),
+        scratch: ScratchSettings {
+            root: Some(temp_dir.path().join("scratch")),
+            quota_mb: 16,
+        },
+        secrets_key: Some("test-master-key".to_string()),
+        log_buffer_lines: 100,
     })
     .await
     .expect("plugin_manager");
@@ -658,3 +686,403 @@ async fn admin_scan_rejects_path_outside_roots() {
     assert_eq!(status, StatusCode::OK);
     assert_eq!(payload["code"], "VTX-ADM-400");
 }
+
+#[tokio::test]
+async fn admin_install_plugin_rejects_invalid_package() {
+    let (state, _temp_dir) = make_state().await;
+
+    let app = Router::new()
+        .nest(
+            "/admin",
+            Router::new().route("/plugins", post(admin::install_plugin_handler)),
+        )
+        .with_state(state.clone());
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/admin/plugins?file_name=broken.vtx")
+                .header("content-type", "application/octet-stream")
+                .body(Body::from("not a vtx package"))
+                .unwrap(),
+        )
+        .await
+        .expect("response");
+
+    let (status, payload) = read_json(response).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(payload["code"], "VTX-ADM-400");
+    assert_eq!(payload["rejection"]["reason"], "invalid_package");
+    assert!(state.plugin_manager.list_plugins().is_empty());
+}
+
+#[tokio::test]
+async fn admin_install_plugin_rejects_without_writable_root() {
+    let (state, _temp_dir) = make_state_with_root(true).await;
+
+    let app = Router::new()
+        .nest(
+            "/admin",
+            Router::new().route("/plugins", post(admin::install_plugin_handler)),
+        )
+        .with_state(state.clone());
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/admin/plugins?file_name=broken.vtx")
+                .header("content-type", "application/octet-stream")
+                .body(Body::from("not a vtx package"))
+                .unwrap(),
+        )
+        .await
+        .expect("response");
+
+    let (status, payload) = read_json(response).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(payload["code"], "VTX-ADM-403");
+    assert_eq!(payload["rejection"]["reason"], "no_writable_root");
+}
+
+#[tokio::test]
+async fn admin_install_plugin_rejects_empty_body() {
+    let (state, _temp_dir) = make_state().await;
+
+    let app = Router::new()
+        .nest(
+            "/admin",
+            Router::new().route("/plugins", post(admin::install_plugin_handler)),
+        )
+        .with_state(state);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/admin/plugins")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .expect("response");
+
+    let (status, payload) = read_json(response).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(payload["code"], "VTX-ADM-400");
+}
+
+#[tokio::test]
+async fn admin_validate_plugin_reports_invalid_package() {
+    let (state, _temp_dir) = make_state().await;
+
+    let app = Router::new()
+        .nest(
+            "/admin",
+            Router::new().route("/plugins/validate", post(admin::validate_plugin_handler)),
+        )
+        .with_state(state.clone());
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/admin/plugins/validate")
+                .header("content-type", "application/octet-stream")
+                .body(Body::from(vec![0x56, 0x54, 0x58, 0x01, 0x00]))
+                .unwrap(),
+        )
+        .await
+        .expect("response");
+
+    let (status, payload) = read_json(response).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(payload["code"], "VTX-ADM-400");
+    assert_eq!(payload["rejection"]["reason"], "invalid_package");
+    assert!(state.plugin_manager.list_plugins().is_empty());
+}
+
+#[tokio::test]
+async fn admin_disable_missing_plugin_returns_not_found() {
+    let (state, _temp_dir) = make_state().await;
+
+    let app = Router::new()
+        .nest(
+            "/admin",
+            Router::new()
+                .route("/plugins/{id}/enable", post(admin::enable_plugin_handler))
+                .route("/plugins/{id}/disable", post(admin::disable_plugin_handler)),
+        )
+        .with_state(state);
+
+    for action in ["enable", "disable"] {
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri(format!("/admin/plugins/missing/{}", action))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .expect("response");
+
+        let (status, payload) = read_json(response).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(payload["code"], "VTX-ADM-404");
+    }
+}
+
+#[tokio::test]
+async fn admin_reset_breaker_for_missing_plugin_returns_not_found() {
+    let (state, _temp_dir) = make_state().await;
+
+    let app = Router::new()
+        .nest(
+            "/admin",
+            Router::new().route(
+                "/plugins/{id}/breaker/reset",
+                post(admin::reset_plugin_breaker_handler),
+            ),
+        )
+        .with_state(state);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/admin/plugins/missing/breaker/reset")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .expect("response");
+
+    let (status, payload) = read_json(response).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(payload["code"], "VTX-ADM-404");
+}
+
+#[tokio::test]
+async fn admin_set_plugin_config_for_missing_plugin_returns_not_found() {
+    let (state, _temp_dir) = make_state().await;
+
+    let app = Router::new()
+        .nest(
+            "/admin",
+            Router::new().route(
+                "/plugins/{id}/config/{key}",
+                put(admin::set_plugin_config_handler),
+            ),
+        )
+        .with_state(state);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("PUT")
+                .uri("/admin/plugins/missing/config/API_KEY")
+                .header("content-type", "application/json")
+                .body(Body::from(r#"{"value":"x","secret":true}"#))
+                .unwrap(),
+        )
+        .await
+        .expect("response");
+
+    let (status, payload) = read_json(response).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(payload["code"], "VTX-ADM-404");
+}
+
+#[tokio::test]
+async fn admin_plugin_permissions_for_missing_plugin_returns_not_found() {
+    let (state, _temp_dir) = make_state().await;
+
+    let app = Router::new()
+        .nest(
+            "/admin",
+            Router::new()
+                .route(
+                    "/plugins/{id}/permissions",
+                    get(admin::list_plugin_permissions_handler),
+                )
+                .route(
+                    "/plugins/{id}/permissions/approve",
+                    post(admin::approve_plugin_permissions_handler),
+                ),
+        )
+        .with_state(state);
+
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/admin/plugins/missing/permissions")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .expect("response");
+    let (status, payload) = read_json(response).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(payload["code"], "VTX-ADM-404");
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/admin/plugins/missing/permissions/approve")
+                .body(Body::from("not json"))
+                .unwrap(),
+        )
+        .await
+        .expect("response");
+    let (status, payload) = read_json(response).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(payload["code"], "VTX-ADM-400");
+}
+
+#[tokio::test]
+async fn admin_rollback_without_previous_version_returns_not_found() {
+    let (state, _temp_dir) = make_state().await;
+
+    let app = Router::new()
+        .nest(
+            "/admin",
+            Router::new().route(
+                "/plugins/{id}/rollback",
+                post(admin::rollback_plugin_handler),
+            ),
+        )
+        .with_state(state);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/admin/plugins/missing/rollback")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .expect("response");
+
+    let (status, payload) = read_json(response).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(payload["code"], "VTX-ADM-404");
+}
+
+#[tokio::test]
+async fn admin_plugin_logs_for_missing_plugin_returns_not_found() {
+    let (state, _temp_dir) = make_state().await;
+
+    let app = Router::new()
+        .nest(
+            "/admin",
+            Router::new().route("/plugins/{id}/logs", get(admin::plugin_logs_handler)),
+        )
+        .with_state(state);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/admin/plugins/missing/logs?limit=10")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .expect("response");
+
+    let (status, payload) = read_json(response).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(payload["code"], "VTX-ADM-404");
+}
+
+#[tokio::test]
+async fn auth_without_providers_is_denied_and_traced() {
+    let (state, _temp_dir) = make_state().await;
+
+    let app = Router::new()
+        .nest(
+            "/admin",
+            Router::new()
+                .route(
+                    "/auth/requests/{request_id}",
+                    get(admin::auth_trace_handler),
+                )
+                .layer(axum::middleware::from_fn_with_state(
+                    state.clone(),
+                    auth_middleware,
+                )),
+        )
+        .with_state(state.clone());
+
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/admin/auth/requests/anything")
+                .header("x-request-id", "req-auth-1")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .expect("response");
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    // 请求 ID 由服务端生成，调用方提供的值不能用来索引鉴权记录
+    let request_id = response.headers()["x-request-id"]
+        .to_str()
+        .unwrap()
+        .to_string();
+    assert_ne!(request_id, "req-auth-1");
+    assert!(state.plugin_manager.auth_trace("req-auth-1").is_none());
+
+    let trace = state
+        .plugin_manager
+        .auth_trace(&request_id)
+        .expect("auth trace recorded");
+    assert_eq!(trace.status, 401);
+    assert!(trace.provider.is_none());
+    assert!(trace.attempts.is_empty());
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri(format!("/admin/auth/requests/{}", request_id))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .expect("response");
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    assert!(response.headers().contains_key("x-request-id"));
+}
+
+#[tokio::test]
+async fn admin_flush_auth_cache_reports_count() {
+    let (state, _temp_dir) = make_state().await;
+
+    let app = Router::new()
+        .nest(
+            "/admin",
+            Router::new().route("/auth/cache", delete(admin::flush_auth_cache_handler)),
+        )
+        .with_state(state);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("DELETE")
+                .uri("/admin/auth/cache")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .expect("response");
+
+    let (status, payload) = read_json(response).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(payload["data"]["flushed"], 0);
+}